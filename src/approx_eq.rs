@@ -0,0 +1,104 @@
+//! Fuzzy comparisons with the `approx` crate (requires the `approx` feature)
+//!
+//! Polygons are equal if they have the same flags and the same number of
+//! nodes, and the nodes are pairwise equal. Like `PartialEq`, the measures
+//! are compared exactly and the cached winding order and bounding box are
+//! ignored. The nodes are not rotated, so
+//! compare results that were brought into canonical order (see
+//! `MultiPolygon::sort_canonical`).
+
+use approx::{AbsDiffEq, RelativeEq};
+use bbox::Bbox;
+use polygon::Polygon;
+use {Point2D, fsize};
+
+impl AbsDiffEq for Point2D {
+    type Epsilon = fsize;
+
+    fn default_epsilon() -> fsize {
+        fsize::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: fsize) -> bool {
+        self.x.abs_diff_eq(&other.x, epsilon) && self.y.abs_diff_eq(&other.y, epsilon)
+    }
+}
+
+impl RelativeEq for Point2D {
+    fn default_max_relative() -> fsize {
+        fsize::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: fsize, max_relative: fsize) -> bool {
+        self.x.relative_eq(&other.x, epsilon, max_relative) && self.y.relative_eq(&other.y, epsilon, max_relative)
+    }
+}
+
+impl AbsDiffEq for Bbox {
+    type Epsilon = fsize;
+
+    fn default_epsilon() -> fsize {
+        fsize::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: fsize) -> bool {
+        self.min().abs_diff_eq(&other.min(), epsilon) && self.max().abs_diff_eq(&other.max(), epsilon)
+    }
+}
+
+impl RelativeEq for Bbox {
+    fn default_max_relative() -> fsize {
+        fsize::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: fsize, max_relative: fsize) -> bool {
+        self.min().relative_eq(&other.min(), epsilon, max_relative) &&
+        self.max().relative_eq(&other.max(), epsilon, max_relative)
+    }
+}
+
+impl AbsDiffEq for Polygon {
+    type Epsilon = fsize;
+
+    fn default_epsilon() -> fsize {
+        fsize::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: fsize) -> bool {
+        same_flags(self, other) && self.nodes.iter().zip(other.nodes.iter()).all(|(a, b)| a.abs_diff_eq(b, epsilon))
+    }
+}
+
+impl RelativeEq for Polygon {
+    fn default_max_relative() -> fsize {
+        fsize::default_max_relative()
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: fsize, max_relative: fsize) -> bool {
+        same_flags(self, other) && self.nodes.iter().zip(other.nodes.iter()).all(|(a, b)| a.relative_eq(b, epsilon, max_relative))
+    }
+}
+
+/// Checks everything but the coordinates of the nodes
+fn same_flags(a: &Polygon, b: &Polygon) -> bool {
+    a.nodes.len() == b.nodes.len() && a.is_closed == b.is_closed && a.is_hole == b.is_hole && a.measures == b.measures
+}
+
+#[test]
+fn test_approx_eq() {
+    let p = Point2D { x: 1.0, y: 2.0 };
+    let q = Point2D { x: 1.0 + 1e-6, y: 2.0 };
+    assert!(p.abs_diff_eq(&q, 1e-5));
+    assert!(!p.abs_diff_eq(&q, 1e-7));
+    assert!(p.relative_eq(&q, 0.0, 1e-5));
+
+    let a = Polygon::rect(0.0, 0.0, 1.0, 1.0);
+    let b = Polygon::rect(0.0, 0.0, 1.0 + 1e-6, 1.0);
+    assert!(a.relative_eq(&b, 1e-5, 1e-5));
+    assert!(a.bounding_box().abs_diff_eq(&b.bounding_box(), 1e-5));
+    assert!(!a.abs_diff_eq(&Polygon::rect(0.0, 0.0, 1.0, 1.0).with_nodes(a.nodes[..3].to_vec()), 1.0));
+
+    let mut hole = b.clone();
+    hole.is_hole = true;
+    assert!(!a.abs_diff_eq(&hole, 1.0));
+}
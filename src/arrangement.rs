@@ -0,0 +1,639 @@
+//! Planar arrangement of two sets of rings as a doubly-connected edge list (DCEL)
+//!
+//! The arrangement splits the plane into faces along all edges of both inputs.
+//! Every face is labeled by whether it lies inside A and / or inside B, so any
+//! boolean operation (and custom ones) can be derived by selecting faces.
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use error::ClipError;
+use intersections::find_intersections_guarded;
+use kernel::{Kernel, ExactKernel};
+use polygon::Polygon;
+use options::{ClipOptions, OperationGuard};
+use provenance::{EdgeRef, EdgeSource, VertexSource, TracedPolygon};
+use utils::{calculate_signed_area, locate_point_in_ring, PointLocation};
+use {Point2D, Segment, fsize};
+
+/// One direction of an edge in the arrangement
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HalfEdge {
+    /// Index of the vertex this half-edge starts at
+    pub origin: usize,
+    /// Index of the half-edge running in the opposite direction
+    pub twin: usize,
+    /// Next half-edge along the boundary of `face`
+    pub next: usize,
+    /// Previous half-edge along the boundary of `face`
+    pub prev: usize,
+    /// Index of the face to the left of this half-edge
+    pub face: usize,
+    /// Does crossing this edge toggle the containment in A?
+    pub on_a: bool,
+    /// Does crossing this edge toggle the containment in B?
+    pub on_b: bool,
+    /// Number of edges of A running in the direction of this half-edge, minus
+    /// the number of edges of A running against it
+    pub winding_a: i32,
+    /// Same as `winding_a`, for the edges of B
+    pub winding_b: i32,
+    /// Edge of A that this edge is a part of
+    pub source_a: Option<EdgeRef>,
+    /// Edge of B that this edge is a part of
+    pub source_b: Option<EdgeRef>,
+}
+
+/// Parity and origin of an edge, while the arrangement is built
+#[derive(Debug, Copy, Clone, Default)]
+struct EdgeInfo {
+    on_a: bool,
+    on_b: bool,
+    // net number of input edges running from the smaller to the larger vertex index
+    winding_a: i32,
+    winding_b: i32,
+    source_a: Option<EdgeRef>,
+    source_b: Option<EdgeRef>,
+}
+
+/// Face of the arrangement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Face {
+    /// A half-edge on the outer boundary, `None` for the unbounded face
+    pub outer_component: Option<usize>,
+    /// One half-edge on the boundary of every hole in the face
+    pub inner_components: Vec<usize>,
+    /// Is the face inside of A?
+    pub in_a: bool,
+    /// Is the face inside of B?
+    pub in_b: bool,
+    /// Winding number of the rings of A around the face
+    pub winding_a: i32,
+    /// Winding number of the rings of B around the face
+    pub winding_b: i32,
+}
+
+/// Half-edge structure of the overlay of two sets of rings
+#[derive(Debug, Clone, Default)]
+pub struct Arrangement {
+    /// All vertices, including the intersection points of the inputs
+    pub vertices: Vec<Point2D>,
+    /// Measure of every vertex (see `Polygon::measures`), interpolated along
+    /// the input edges for intersection points. Where the inputs meet, the
+    /// measures of A take precedence.
+    pub measures: Vec<Option<fsize>>,
+    /// Origin of every vertex, A is reported as the subject and B as the clip
+    pub vertex_sources: Vec<VertexSource>,
+    /// Half-edges, the twin of half-edge `i` is always `i ^ 1`
+    pub half_edges: Vec<HalfEdge>,
+    /// Faces, the first face is always the unbounded face
+    pub faces: Vec<Face>,
+}
+
+impl Arrangement {
+
+    /// Builds the arrangement of the rings of `a` and `b`
+    ///
+    /// Holes are given as separate rings, containment is determined with the
+    /// even-odd rule. The winding numbers of the faces are tracked as well, for
+    /// the non-zero rule. Rings with less than three nodes are ignored.
+    pub fn new(a: &[Polygon], b: &[Polygon]) -> Self {
+        match Self::with_options(a, b, &ClipOptions::default(), &mut OperationGuard::unlimited()) {
+            Ok(arrangement) => arrangement,
+            Err(_) => unreachable!("the default options never abort"),
+        }
+    }
+
+    /// Same as `new`, but reports every crossing of two input edges to
+    /// `options.on_intersection` (vertices that the two edges share are not
+    /// reported) and aborts if the `guard` cancels the operation
+    pub(crate) fn with_options(a: &[Polygon], b: &[Polygon], options: &ClipOptions, guard: &mut OperationGuard)
+                               -> Result<Self, ClipError>
+    {
+
+        let mut segments = Vec::new();
+        let mut segment_measures = Vec::new();
+        let mut segment_refs = Vec::new();
+        let mut from_b = Vec::new();
+
+        for &(is_b, rings) in [(false, a), (true, b)].iter() {
+            for (ring_idx, ring) in rings.iter().enumerate().filter(|&(_, r)| r.nodes.len() > 2) {
+                let len = ring.nodes.len();
+                let measures = ring.measures.as_ref().filter(|m| m.len() == len);
+                for i in 0..len {
+                    segments.push(Segment::new(ring.nodes[i], ring.nodes[(i + 1) % len]));
+                    segment_measures.push(measures.map(|m| (m[i], m[(i + 1) % len])));
+                    segment_refs.push(EdgeRef { ring: ring_idx, edge: i });
+                    from_b.push(is_b);
+                }
+            }
+        }
+
+        // split every segment at the intersections with other segments
+        let mut splits: Vec<Vec<Point2D>> = segments.iter().map(|s| vec![s.a, s.b]).collect();
+        for event in find_intersections_guarded(&segments, guard)? {
+            sweep_trace!("intersection: {:?} of segments {:?}", event.point, event.segments);
+            let is_endpoint = |idx: usize| segments[idx].a == event.point || segments[idx].b == event.point;
+            if event.segments.len() > 2 || event.segments.iter().any(|&idx| !is_endpoint(idx)) {
+                guard.record(1, 0);
+            }
+            if let Some(ref callback) = options.on_intersection {
+                for (n, &i) in event.segments.iter().enumerate() {
+                    for &j in event.segments[n + 1..].iter() {
+                        if !(is_endpoint(i) && is_endpoint(j)) {
+                            callback(event.point, &segments[i], &segments[j]);
+                        }
+                    }
+                }
+            }
+            for idx in event.segments {
+                splits[idx].push(event.point);
+            }
+        }
+
+        let mut vertices = Vec::new();
+        let mut measures = Vec::new();
+        let mut vertex_sources = Vec::new();
+        let mut vertex_ids = HashMap::<Point2D, usize>::new();
+        let mut edges = BTreeMap::<(usize, usize), EdgeInfo>::new();
+
+        for (idx, mut points) in splits.into_iter().enumerate() {
+            let start = segments[idx].a;
+            // points at the same distance differ only by rounding, their
+            // order is that of `Point2D`
+            points.sort_by(|p, q| start.dist(p).partial_cmp(&start.dist(q))
+                                               .unwrap_or(::std::cmp::Ordering::Equal)
+                                               .then(p.cmp(q)));
+            points.dedup();
+            guard.record(0, points.len().saturating_sub(2));
+
+            let length = segments[idx].length();
+            let ids: Vec<usize> = points.into_iter().map(|p| {
+                let id = *vertex_ids.entry(p).or_insert_with(|| {
+                    vertices.push(p);
+                    measures.push(None);
+                    vertex_sources.push(VertexSource::Intersection(None, None));
+                    vertices.len() - 1
+                });
+                if let (None, Some((ma, mb))) = (measures[id], segment_measures[idx]) {
+                    let t = if length > 0.0 { start.dist(&p) / length } else { 0.0 };
+                    measures[id] = Some(ma + (mb - ma) * t);
+                }
+                vertex_sources[id] = vertex_sources[id].merge(p == start, p == segments[idx].b,
+                                                              from_b[idx], segment_refs[idx]);
+                id
+            }).collect();
+
+            for pair in ids.windows(2) {
+                let key = (pair[0].min(pair[1]), pair[0].max(pair[1]));
+                let direction = if pair[0] < pair[1] { 1 } else { -1 };
+                let info = edges.entry(key).or_insert_with(EdgeInfo::default);
+                if from_b[idx] {
+                    info.on_b = !info.on_b;
+                    info.winding_b += direction;
+                    info.source_b = info.source_b.or(Some(segment_refs[idx]));
+                } else {
+                    info.on_a = !info.on_a;
+                    info.winding_a += direction;
+                    info.source_a = info.source_a.or(Some(segment_refs[idx]));
+                }
+            }
+        }
+
+        let mut half_edges = Vec::with_capacity(edges.len() * 2);
+        let mut outgoing = vec![Vec::new(); vertices.len()];
+
+        for (&(u, v), info) in edges.iter() {
+            for &(origin, dest, direction) in [(u, v, 1), (v, u, -1)].iter() {
+                let idx = half_edges.len();
+                outgoing[origin].push(idx);
+                half_edges.push(HalfEdge {
+                    origin: origin,
+                    twin: idx ^ 1,
+                    next: dest,
+                    prev: 0,
+                    face: 0,
+                    on_a: info.on_a,
+                    on_b: info.on_b,
+                    winding_a: info.winding_a * direction,
+                    winding_b: info.winding_b * direction,
+                    source_a: info.source_a,
+                    source_b: info.source_b,
+                });
+            }
+        }
+
+        // sort the outgoing half-edges of every vertex counter-clockwise. The
+        // `next` field temporarily stores the destination vertex.
+        for (vertex, list) in outgoing.iter_mut().enumerate() {
+            let origin = vertices[vertex];
+            if options.deterministic {
+                list.sort_by(|e, f| {
+                    let (u, v) = (half_edges[*e].next, half_edges[*f].next);
+                    compare_directions(&origin, &vertices[u], &vertices[v]).then(u.cmp(&v))
+                });
+                continue;
+            }
+            // edges in the same direction (after rounding) are ordered by
+            // their end vertex, as in the deterministic order
+            list.sort_by(|e, f| {
+                let angle = |he: &HalfEdge| {
+                    let d = vertices[he.next] - origin;
+                    d.y.atan2(d.x)
+                };
+                angle(&half_edges[*e]).partial_cmp(&angle(&half_edges[*f]))
+                                      .unwrap_or(::std::cmp::Ordering::Equal)
+                                      .then(half_edges[*e].next.cmp(&half_edges[*f].next))
+            });
+        }
+
+        // the face to the left of u -> v continues with the half-edge leaving v
+        // that comes clockwise after v -> u
+        let mut next = vec![0; half_edges.len()];
+        for (idx, he) in half_edges.iter().enumerate() {
+            let list = &outgoing[he.next];
+            let pos = list.iter().position(|e| *e == he.twin).unwrap();
+            next[idx] = list[(pos + list.len() - 1) % list.len()];
+        }
+        for idx in 0..half_edges.len() {
+            half_edges[idx].next = next[idx];
+            half_edges[next[idx]].prev = idx;
+        }
+
+        let mut arrangement = Arrangement {
+            vertices: vertices,
+            measures: measures,
+            vertex_sources: vertex_sources,
+            half_edges: half_edges,
+            faces: vec![Face {
+                outer_component: None,
+                inner_components: Vec::new(),
+                in_a: false,
+                in_b: false,
+                winding_a: 0,
+                winding_b: 0,
+            }],
+        };
+
+        arrangement.build_faces();
+        arrangement.label_faces();
+        Ok(arrangement)
+    }
+
+    /// Returns the points of the boundary cycle that contains `half_edge`
+    pub fn cycle(&self, half_edge: usize) -> Vec<Point2D> {
+        self.cycle_vertices(half_edge).into_iter().map(|v| self.vertices[v]).collect()
+    }
+
+    /// Returns the vertex indices of the boundary cycle that contains `half_edge`
+    fn cycle_vertices(&self, half_edge: usize) -> Vec<usize> {
+        let mut vertices = Vec::new();
+        let mut current = half_edge;
+        loop {
+            vertices.push(self.half_edges[current].origin);
+            current = self.half_edges[current].next;
+            if current == half_edge {
+                return vertices;
+            }
+        }
+    }
+
+    /// Creates a polygon from vertex indices, with measures if every vertex has one
+    fn ring_polygon(&self, vertices: &[usize], is_hole: bool) -> Polygon {
+        let mut polygon = Polygon::from_parts(vertices.iter().map(|v| self.vertices[*v]).collect(), is_hole, true);
+        polygon.measures = vertices.iter().map(|v| self.measures[*v]).collect();
+        polygon
+    }
+
+    /// Returns the rings bounding a face: the outer ring first (if the face
+    /// is bounded), followed by its holes
+    pub fn face_rings(&self, face: usize) -> Vec<Polygon> {
+        let face = &self.faces[face];
+        face.outer_component.iter()
+            .map(|e| self.ring_polygon(&self.cycle_vertices(*e), false))
+            .chain(face.inner_components.iter()
+                       .map(|e| self.ring_polygon(&self.cycle_vertices(*e), true)))
+            .collect()
+    }
+
+    /// Merges all faces for which `select(in_a, in_b)` returns true and returns
+    /// the boundary rings of the merged region
+    ///
+    /// For example, `|a, b| a && !b` computes the difference of A and B.
+    /// Counter-clockwise rings are exteriors, clockwise rings are holes.
+    pub fn extract<F>(&self, select: F) -> Vec<Polygon> where F: Fn(bool, bool) -> bool {
+        self.extract_traced(select).into_iter().map(|traced| traced.polygon).collect()
+    }
+
+    /// Same as `extract`, but also reports where every vertex and edge of the
+    /// result comes from
+    pub fn extract_traced<F>(&self, select: F) -> Vec<TracedPolygon> where F: Fn(bool, bool) -> bool {
+        BoundaryRings::new(self, select).collect()
+    }
+
+    /// Groups the half-edges into cycles and assigns them to faces
+    fn build_faces(&mut self) {
+
+        let mut visited = vec![false; self.half_edges.len()];
+        let mut outer_cycles = Vec::new();
+        let mut inner_cycles = Vec::new();
+
+        for start in 0..self.half_edges.len() {
+            if visited[start] {
+                continue;
+            }
+
+            let mut current = start;
+            while !visited[current] {
+                visited[current] = true;
+                current = self.half_edges[current].next;
+            }
+
+            let nodes = self.cycle(start);
+            let area = calculate_signed_area(&nodes);
+            if area > 0.0 {
+                outer_cycles.push((start, nodes, area));
+            } else {
+                inner_cycles.push((start, nodes));
+            }
+        }
+
+        for &(start, _, _) in outer_cycles.iter() {
+            self.faces.push(Face {
+                outer_component: Some(start),
+                inner_components: Vec::new(),
+                in_a: false,
+                in_b: false,
+                winding_a: 0,
+                winding_b: 0,
+            });
+        }
+
+        // a clockwise cycle is a hole in the smallest bounded face containing it
+        for (start, nodes) in inner_cycles {
+            let face = outer_cycles.iter().enumerate()
+                .filter(|&(_, &(_, ref ring, _))| {
+                    locate_point_in_ring(&nodes[0], ring) == PointLocation::Inside
+                })
+                .min_by(|&(_, &(_, _, a)), &(_, &(_, _, b))| {
+                    a.partial_cmp(&b).unwrap_or(::std::cmp::Ordering::Equal)
+                })
+                .map(|(idx, _)| idx + 1)
+                .unwrap_or(0);
+            self.faces[face].inner_components.push(start);
+        }
+
+        for face in 0..self.faces.len() {
+            let components: Vec<usize> = self.faces[face].outer_component.iter().cloned()
+                .chain(self.faces[face].inner_components.iter().cloned())
+                .collect();
+            for start in components {
+                let mut current = start;
+                loop {
+                    self.half_edges[current].face = face;
+                    current = self.half_edges[current].next;
+                    if current == start {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Labels the faces by walking outwards from the unbounded face, toggling
+    /// the containment every time an edge of A or B is crossed. The face to
+    /// the left of an edge has a winding number that is one higher than the
+    /// face to the right.
+    fn label_faces(&mut self) {
+
+        let mut face_edges = vec![Vec::new(); self.faces.len()];
+        for (idx, he) in self.half_edges.iter().enumerate() {
+            face_edges[he.face].push(idx);
+        }
+
+        let mut visited = vec![false; self.faces.len()];
+        let mut queue = VecDeque::new();
+        visited[0] = true;
+        queue.push_back(0);
+
+        while let Some(face) = queue.pop_front() {
+            let (in_a, in_b) = (self.faces[face].in_a, self.faces[face].in_b);
+            let (winding_a, winding_b) = (self.faces[face].winding_a, self.faces[face].winding_b);
+            for e in face_edges[face].iter() {
+                let he = self.half_edges[*e];
+                let neighbour = self.half_edges[he.twin].face;
+                if visited[neighbour] {
+                    continue;
+                }
+                visited[neighbour] = true;
+                self.faces[neighbour].in_a = in_a ^ he.on_a;
+                self.faces[neighbour].in_b = in_b ^ he.on_b;
+                self.faces[neighbour].winding_a = winding_a - he.winding_a;
+                self.faces[neighbour].winding_b = winding_b - he.winding_b;
+                sweep_trace!("face labelled: {} in_a={} in_b={}", neighbour,
+                             self.faces[neighbour].in_a, self.faces[neighbour].in_b);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+}
+
+/// Lazily traces the boundary rings of the selected region of an arrangement,
+/// see `Arrangement::extract_traced`
+pub(crate) struct BoundaryRings<A> {
+    arrangement: A,
+    selected: Vec<bool>,
+    visited: Vec<bool>,
+    start: usize,
+}
+
+impl<A: Borrow<Arrangement>> BoundaryRings<A> {
+
+    pub(crate) fn new<F>(arrangement: A, select: F) -> Self where F: Fn(bool, bool) -> bool {
+        Self::select_faces(arrangement, |f| select(f.in_a, f.in_b))
+    }
+
+    /// Traces the boundary of the faces for which `select` returns true
+    pub(crate) fn select_faces<F>(arrangement: A, select: F) -> Self where F: Fn(&Face) -> bool {
+        let (selected, visited) = {
+            let arr = arrangement.borrow();
+            (arr.faces.iter().map(select).collect(), vec![false; arr.half_edges.len()])
+        };
+        Self {
+            arrangement: arrangement,
+            selected: selected,
+            visited: visited,
+            start: 0,
+        }
+    }
+
+    fn is_boundary(&self, e: usize) -> bool {
+        let arr = self.arrangement.borrow();
+        let he = &arr.half_edges[e];
+        self.selected[he.face] && !self.selected[arr.half_edges[he.twin].face]
+    }
+}
+
+impl<A: Borrow<Arrangement>> Iterator for BoundaryRings<A> {
+    type Item = TracedPolygon;
+
+    fn next(&mut self) -> Option<TracedPolygon> {
+
+        let len = self.visited.len();
+        while self.start < len && (self.visited[self.start] || !self.is_boundary(self.start)) {
+            self.start += 1;
+        }
+        if self.start == len {
+            return None;
+        }
+
+        let mut ring = Vec::new();
+        let mut edges = Vec::new();
+        let mut current = self.start;
+        while !self.visited[current] {
+            self.visited[current] = true;
+            let he = self.arrangement.borrow().half_edges[current];
+            ring.push(he.origin);
+            edges.push(match (he.source_a, he.source_b) {
+                (Some(a), Some(b)) => EdgeSource::Both(a, b),
+                (Some(a), None) => EdgeSource::Subject(a),
+                (None, Some(b)) => EdgeSource::Clip(b),
+                (None, None) => unreachable!("every edge comes from an input ring"),
+            });
+
+            // rotate around the end vertex until the next boundary edge
+            current = profile!(ring_link, {
+                let mut candidate = he.next;
+                while !self.is_boundary(candidate) {
+                    let arr = self.arrangement.borrow();
+                    candidate = arr.half_edges[arr.half_edges[candidate].twin].next;
+                }
+                candidate
+            });
+        }
+
+        let arr = self.arrangement.borrow();
+        let polygon = arr.ring_polygon(&ring, false);
+        let is_hole = calculate_signed_area(&polygon.nodes) < 0.0;
+        Some(TracedPolygon {
+            polygon: Polygon { is_hole: is_hole, .. polygon },
+            vertices: ring.iter().map(|v| arr.vertex_sources[*v].clone()).collect(),
+            edges: edges,
+        })
+    }
+}
+
+/// Compares the directions from `origin` to `a` and to `b` counter-clockwise,
+/// starting after the negative x axis like `atan2`. Uses exact predicates
+/// only, so the order does not depend on the math library of the platform.
+fn compare_directions(origin: &Point2D, a: &Point2D, b: &Point2D) -> ::std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    // 0 for the angles in (-pi, 0], 1 for the angles in (0, pi]
+    let half = |p: &Point2D| if p.y < origin.y || (p.y == origin.y && p.x > origin.x) { 0 } else { 1 };
+    half(a).cmp(&half(b)).then_with(|| match ExactKernel::orient2d(origin, a, b) {
+        Ordering::Greater => Ordering::Less,
+        Ordering::Less => Ordering::Greater,
+        Ordering::Equal => Ordering::Equal,
+    })
+}
+
+#[test]
+fn test_arrangement_two_squares() {
+    let square = |x: fsize, y: fsize| Polygon::from_parts(vec![
+        Point2D { x: x, y: y },
+        Point2D { x: x + 2.0, y: y },
+        Point2D { x: x + 2.0, y: y + 2.0 },
+        Point2D { x: x, y: y + 2.0 },
+    ], false, true);
+
+    let arrangement = Arrangement::new(&[square(0.0, 0.0)], &[square(1.0, 1.0)]);
+
+    assert_eq!(arrangement.vertices.len(), 10);
+    assert_eq!(arrangement.faces.len(), 4);
+    assert_eq!(arrangement.faces[0].in_a, false);
+    assert_eq!(arrangement.faces[0].in_b, false);
+
+    let labels: Vec<(bool, bool)> = arrangement.faces[1..].iter().map(|f| (f.in_a, f.in_b)).collect();
+    assert!(labels.contains(&(true, false)));
+    assert!(labels.contains(&(false, true)));
+    assert!(labels.contains(&(true, true)));
+
+    let intersection = arrangement.extract(|a, b| a && b);
+    assert_eq!(intersection.len(), 1);
+    assert_eq!(calculate_signed_area(&intersection[0].nodes), 1.0);
+
+    let union = arrangement.extract(|a, b| a || b);
+    assert_eq!(union.len(), 1);
+    assert_eq!(calculate_signed_area(&union[0].nodes), 7.0);
+}
+
+#[test]
+fn test_arrangement_large_union() {
+    // a checkerboard, A has the black and B the white cells: every inner
+    // vertex has four edges, from which the tracing has to pick the boundary
+    let n = 30;
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    for i in 0..n {
+        for j in 0..n {
+            let cell = Polygon::rect(i as fsize, j as fsize, 1.0, 1.0);
+            if (i + j) % 2 == 0 { a.push(cell) } else { b.push(cell) }
+        }
+    }
+    let arrangement = Arrangement::new(&a, &b);
+
+    let union = arrangement.extract(|a, b| a || b);
+    assert_eq!(union.len(), 1);
+    assert_eq!(calculate_signed_area(&union[0].nodes), (n * n) as fsize);
+    assert_eq!(union[0].nodes.len(), 4 * n);
+
+    // the cells of A only touch at their corners, every edge is linked once
+    let black = arrangement.extract(|a, _| a);
+    let area: fsize = black.iter().map(|p| calculate_signed_area(&p.nodes)).sum();
+    assert_eq!(area, (n * n / 2) as fsize);
+    assert_eq!(black.iter().map(|p| p.nodes.len()).sum::<usize>(), 4 * n * n / 2);
+}
+
+#[test]
+fn test_arrangement_hole() {
+    let square = |x: fsize, size: fsize| Polygon::from_parts(vec![
+        Point2D { x: x, y: x },
+        Point2D { x: x + size, y: x },
+        Point2D { x: x + size, y: x + size },
+        Point2D { x: x, y: x + size },
+    ], false, true);
+
+    // B lies completely inside of A, so it becomes a hole of A's face
+    let arrangement = Arrangement::new(&[square(0.0, 4.0)], &[square(1.0, 1.0)]);
+    assert_eq!(arrangement.faces.len(), 3);
+
+    let outer = arrangement.faces.iter().position(|f| f.in_a && !f.in_b).unwrap();
+    assert_eq!(arrangement.faces[outer].inner_components.len(), 1);
+    assert_eq!(arrangement.face_rings(outer).len(), 2);
+
+    let difference = arrangement.extract(|a, b| a && !b);
+    let area: fsize = difference.iter().map(|p| calculate_signed_area(&p.nodes)).sum();
+    assert_eq!(area, 15.0);
+}
+
+#[test]
+fn test_arrangement_interpolates_measures() {
+    let mut a = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    a.measures = Some(vec![0.0, 10.0, 20.0, 30.0]);
+    let mut b = Polygon::rect(1.0, -1.0, 2.0, 2.0);
+
+    // B has no measures, so the corners of B have none either
+    let arrangement = Arrangement::new(&[a.clone()], &[b.clone()]);
+    assert!(arrangement.extract(|a, b| a && !b)[0].measures.is_none());
+
+    let crossing = arrangement.vertices.iter().position(|p| *p == Point2D { x: 2.0, y: 1.0 }).unwrap();
+    assert_eq!(arrangement.measures[crossing], Some(15.0));
+
+    // where A and B meet, the measures of A are used
+    b.measures = Some(vec![100.0; 4]);
+    let arrangement = Arrangement::new(&[a], &[b]);
+    let difference = arrangement.extract(|a, b| a && !b);
+    let measures = difference[0].measures.clone().unwrap();
+    assert_eq!(measures.len(), difference[0].nodes.len());
+    assert!(measures.contains(&5.0));
+    assert!(measures.contains(&100.0));
+}
@@ -0,0 +1,69 @@
+//! Pluggable algorithms for the boolean operations of two polygons
+//!
+//! `Polygon::try_clip` runs the backend of `ClipOptions::backend`, or the
+//! built-in algorithm of `ClipOptions::algorithm` if none is set. Since the
+//! built-in algorithms implement `ClipBackend` as well, all of them can be
+//! compared (or benchmarked) through the same call.
+
+use error::ClipError;
+use options::{ClipOptions, ClipAlgorithm};
+use polygon::{Polygon, BoolOpType};
+
+/// Algorithm that computes a boolean operation of two polygons
+pub trait ClipBackend: Send + Sync {
+    /// Computes `subject <operation_type> clip`. An empty result is returned
+    /// as an empty list. The output filters and `max_output_vertices` of the
+    /// options are applied by the caller, so a backend does not have to
+    /// handle them.
+    fn clip(&self, subject: &Polygon, clip: &Polygon, operation_type: BoolOpType, options: &ClipOptions)
+            -> Result<Vec<Polygon>, ClipError>;
+}
+
+impl ClipBackend for ClipAlgorithm {
+    fn clip(&self, subject: &Polygon, clip: &Polygon, operation_type: BoolOpType, options: &ClipOptions)
+            -> Result<Vec<Polygon>, ClipError>
+    {
+        subject.clip_with_algorithm(clip, *self, operation_type, options, &mut options.guard())
+    }
+}
+
+#[test]
+fn test_clip_backend() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // intersects the bounding boxes and counts its calls
+    struct BboxBackend(AtomicUsize);
+
+    impl ClipBackend for BboxBackend {
+        fn clip(&self, subject: &Polygon, clip: &Polygon, _: BoolOpType, _: &ClipOptions) -> Result<Vec<Polygon>, ClipError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(subject.bbox.and_then(|a| clip.bbox.and_then(|b| a.intersection(&b)))
+                .map(|bbox| Polygon::rect(bbox.min().x, bbox.min().y, bbox.width(), bbox.height()))
+                .into_iter()
+                .collect())
+        }
+    }
+
+    let a = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    let b = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+    let backend = Arc::new(BboxBackend(AtomicUsize::new(0)));
+    let options = ClipOptions { backend: Some(backend.clone()), .. Default::default() };
+
+    let result = a.try_clip(&b, BoolOpType::Intersection, &options).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].area(), 1.0);
+    assert_eq!(backend.0.load(Ordering::SeqCst), 1);
+
+    // the output filters still apply
+    let options = ClipOptions { min_output_area: 2.0, .. options };
+    assert!(a.try_clip(&b, BoolOpType::Intersection, &options).unwrap().is_empty());
+    assert_eq!(backend.0.load(Ordering::SeqCst), 2);
+
+    // the built-in algorithms behind the same interface
+    let algorithms: [&dyn ClipBackend; 2] = [&ClipAlgorithm::Arrangement, &ClipAlgorithm::GreinerHormann];
+    for algorithm in algorithms.iter() {
+        let result = algorithm.clip(&a, &Polygon::rect(3.0, 3.0, 1.0, 1.0), BoolOpType::Union, &ClipOptions::default()).unwrap();
+        assert_eq!(result.len(), 2);
+    }
+}
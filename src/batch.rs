@@ -0,0 +1,246 @@
+//! Boolean operations on many polygons at once
+
+use Bbox;
+use arrangement::{Arrangement, BoundaryRings};
+use error::ClipError;
+use multi_polygon::{MultiPolygon, sort_canonical};
+use polygon::{Polygon, BoolOpType, WindingOrder, FillRule};
+use options::{ClipOptions, ClipAlgorithm, OperationGuard};
+use utils::calculate_winding_order;
+
+/// Runs the boolean operation `subject <op> clip` for every subject
+///
+/// Duplicate and collinear vertices are removed from the clipping polygon
+/// once, not for every subject. Subjects whose bounding box does not overlap
+/// the clipping polygon are resolved without building an arrangement, which
+/// makes this fast for viewport clipping, where most features are either
+/// fully outside or small compared to the viewport. Every other subject is
+/// overlaid with the clipping polygon in its own `Arrangement`, like
+/// `Polygon::try_clip` does, so the edges of the clipping polygon are swept
+/// again for each of these subjects.
+///
+/// The result contains one entry per subject, in the order of `subjects`:
+/// the (possibly empty) list of polygons, or the error of the operation, for
+/// example `ClipError::NonFiniteCoordinate` if the subject or the clipping
+/// polygon has a non-finite coordinate.
+pub fn clip_many(subjects: &[Polygon], clip: &Polygon, operation_type: BoolOpType) -> Vec<Result<Vec<Polygon>, ClipError>> {
+
+    let clip = clip.with_nodes(::clean::clean_nodes(&clip.nodes, true, 0.0));
+    let options = ClipOptions::default();
+
+    subjects.iter().map(|subject| {
+        subject.clip_with_algorithm(&clip, ClipAlgorithm::Arrangement, operation_type, &options, &mut OperationGuard::unlimited())
+    }).collect()
+}
+
+/// Calculates the area that is covered by an odd number of the polygons
+///
+/// All polygons are noded in a single arrangement, which is faster and more
+/// precise than a tree of pairwise XORs. The result consists of
+/// counter-clockwise exteriors and clockwise holes with `is_hole` set, see
+/// `MultiPolygon::resolve_fill_rule`.
+pub fn xor_all(polygons: &[Polygon]) -> Vec<Polygon> {
+    MultiPolygon::new(polygons.to_vec()).resolve_fill_rule(FillRule::EvenOdd).polygons
+}
+
+impl Polygon {
+
+    /// Subtracts all `clips` from the polygon at once
+    ///
+    /// The clips that overlap the polygon are merged into one area with
+    /// `resolve_fill_rule` first, so the difference only has to node the
+    /// polygon with the outline of that union instead of with every edge of
+    /// every clip. This pays off when many clips overlap each other, like
+    /// buffered road segments. Returns None if nothing is left of the polygon.
+    pub fn subtract_all(&self, clips: &[Polygon]) -> Option<Vec<Polygon>> {
+
+        let union = MultiPolygon::new(relevant_clips(&self.bounding_box(), clips)).resolve_fill_rule(FillRule::NonZero);
+
+        // the holes of the union run clockwise, so the winding number of the
+        // union is zero exactly where none of the clips covers the plane
+        let arrangement = Arrangement::new(&[self.clone()], &union.polygons);
+        let mut polygons: Vec<Polygon> = BoundaryRings::select_faces(&arrangement, |f| f.in_a && f.winding_b == 0)
+            .map(|traced| traced.polygon.clean(0.0))
+            .collect();
+        sort_canonical(&mut polygons);
+        if polygons.is_empty() { None } else { Some(polygons) }
+    }
+
+    /// Subtracts the clips one after another, without converting the
+    /// intermediate results back into polygons
+    ///
+    /// All clips are noded together with the polygon in a single arrangement,
+    /// and the faces that lie in the polygon but in none of the clips are
+    /// extracted once at the end. This avoids the rounding of the intermediate
+    /// vertices (and the cost of rebuilding them) that repeated calls to
+    /// `difference` would have. Clips that do not overlap the polygon are
+    /// skipped, overlapping clips do not cancel each other out. Returns None
+    /// if nothing is left of the polygon.
+    pub fn subtract_iter<'a>(&self, clips: impl IntoIterator<Item = &'a Polygon>) -> Option<Vec<Polygon>> {
+
+        // with the same orientation, the winding number of the clips is
+        // non-zero exactly where at least one of them covers the plane
+        let relevant = relevant_clips(&self.bounding_box(), clips);
+
+        let arrangement = Arrangement::new(&[self.clone()], &relevant);
+        let mut polygons: Vec<Polygon> = BoundaryRings::select_faces(&arrangement, |f| f.in_a && f.winding_b == 0)
+            .map(|traced| traced.polygon.clean(0.0))
+            .collect();
+        sort_canonical(&mut polygons);
+        if polygons.is_empty() { None } else { Some(polygons) }
+    }
+}
+
+/// Counter-clockwise copies of the clips that may overlap `bbox`
+fn relevant_clips<'a>(bbox: &Bbox, clips: impl IntoIterator<Item = &'a Polygon>) -> Vec<Polygon> {
+    clips.into_iter()
+        .filter(|c| c.nodes.len() > 2 && c.bounding_box().overlaps(bbox))
+        .map(|c| match calculate_winding_order(&c.nodes) {
+            WindingOrder::Clockwise => c.with_nodes(c.nodes.iter().rev().cloned().collect()),
+            _ => c.clone(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_subtract_all() {
+    let subject = Polygon::rect(0.0, 0.0, 4.0, 1.0);
+
+    // overlapping clips must not cancel each other out
+    let clips = vec![
+        Polygon::rect(0.5, -1.0, 1.0, 3.0),
+        Polygon::rect(1.0, -1.0, 1.0, 3.0),
+        Polygon::rect(3.0, -1.0, 2.0, 3.0),
+        Polygon::rect(10.0, 10.0, 1.0, 1.0),
+    ];
+
+    let result = subject.subtract_all(&clips).unwrap();
+    assert_eq!(result.len(), 2);
+    let area: ::fsize = result.iter().map(|p| p.area()).sum();
+    assert_eq!(area, 1.5);
+
+    assert!(subject.subtract_all(&[Polygon::rect(-1.0, -1.0, 6.0, 3.0)]).is_none());
+}
+
+#[test]
+fn test_subtract_all_overlapping_clips() {
+    let subject = Polygon::rect(0.0, 0.0, 10.0, 4.0);
+    let signed_area = |polygons: &[Polygon]| -> ::fsize {
+        polygons.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum()
+    };
+
+    // a chain of overlapping squares across the middle of the subject, half
+    // of them clockwise, with a clip that lies completely inside another one
+    let mut clips: Vec<Polygon> = (0..7).map(|i| {
+        let clip = Polygon::rect(1.0 + i as ::fsize, 1.0, 2.0, 2.0);
+        if i % 2 == 0 { clip } else { clip.with_nodes(clip.nodes.iter().rev().cloned().collect()) }
+    }).collect();
+    clips.push(Polygon::rect(2.5, 1.5, 1.0, 1.0));
+
+    // the union of the clips is one 8 x 2 rectangle, cut out as a single hole
+    let result = subject.subtract_all(&clips).unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(signed_area(&result), 40.0 - 16.0);
+    let hole = result.iter().find(|p| p.is_hole).unwrap();
+    assert_eq!(hole.nodes.len(), 4);
+
+    assert_eq!(Some(result), subject.subtract_iter(clips.iter()));
+}
+
+#[test]
+fn test_clip_many_trivial_cases() {
+    let viewport = Polygon::rect(0.0, 0.0, 10.0, 10.0);
+    let outside = Polygon::rect(20.0, 20.0, 1.0, 1.0);
+    let empty = Polygon::default();
+
+    let subjects = vec![outside.clone(), empty];
+
+    let intersection = clip_many(&subjects, &viewport, BoolOpType::Intersection);
+    assert_eq!(intersection.len(), 2);
+    assert!(intersection.iter().all(|r| r.as_ref().unwrap().is_empty()));
+
+    let difference = clip_many(&subjects, &viewport, BoolOpType::Difference);
+    let difference = difference[0].as_ref().unwrap();
+    assert_eq!(difference.len(), 1);
+    assert_eq!(difference[0].nodes, outside.nodes);
+
+    // invalid input is reported per subject instead of an empty result
+    let broken = Polygon::from(vec![(0.0, 0.0), (::fsize::NAN, 1.0), (1.0, 1.0)]);
+    let result = clip_many(&[broken, outside.clone()], &viewport, BoolOpType::Union);
+    assert_eq!(result[0], Err(ClipError::NonFiniteCoordinate { index: 1 }));
+    assert!(result[1].is_ok());
+}
+
+#[test]
+fn test_clip_many() {
+    let viewport = Polygon::rect(0.0, 0.0, 10.0, 10.0);
+    let overlapping = Polygon::rect(-5.0, -5.0, 10.0, 10.0);
+    let inside = Polygon::rect(2.0, 2.0, 1.0, 1.0);
+    let subjects = vec![overlapping.clone(), inside.clone()];
+
+    let area = |polygons: &[Polygon]| -> ::fsize { polygons.iter().map(|p| p.area()).sum() };
+
+    let intersection: Vec<Vec<Polygon>> = clip_many(&subjects, &viewport, BoolOpType::Intersection).into_iter().map(Result::unwrap).collect();
+    assert_eq!(intersection.len(), 2);
+    assert_eq!(area(&intersection[0]), 25.0);
+    assert_eq!(intersection[1].len(), 1);
+    assert_eq!(area(&intersection[1]), 1.0);
+
+    let difference: Vec<Vec<Polygon>> = clip_many(&subjects, &viewport, BoolOpType::Difference).into_iter().map(Result::unwrap).collect();
+    assert_eq!(area(&difference[0]), 75.0);
+    assert!(difference[1].is_empty());
+
+    // the same results as clipping every subject on its own
+    for &op in [BoolOpType::Union, BoolOpType::Xor].iter() {
+        let many = clip_many(&subjects, &viewport, op);
+        for (subject, result) in subjects.iter().zip(many.into_iter()) {
+            assert_eq!(result, subject.try_clip(&viewport, op, &ClipOptions::default()));
+        }
+    }
+}
+
+#[test]
+fn test_subtract_iter() {
+    let subject = Polygon::rect(0.0, 0.0, 10.0, 10.0);
+
+    // many small holes punched in a row, including clockwise and touching ones
+    let clips: Vec<Polygon> = (0..9).map(|i| {
+        let clip = Polygon::rect(0.5 + i as ::fsize, 4.0, 1.0, 2.0);
+        if i % 2 == 0 { clip } else { clip.with_nodes(clip.nodes.iter().rev().cloned().collect()) }
+    }).collect();
+
+    let result = subject.subtract_iter(clips.iter()).unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result.iter().filter(|p| p.is_hole).count(), 1);
+    let area: ::fsize = result.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum();
+    assert_eq!(area, 82.0);
+
+    // the slot is a single hole without intermediate vertices
+    let hole = result.iter().find(|p| p.is_hole).unwrap();
+    assert_eq!(hole.nodes.len(), 4);
+
+    assert!(subject.subtract_iter(vec![&Polygon::rect(-1.0, -1.0, 12.0, 12.0)]).is_none());
+}
+
+#[test]
+fn test_xor_all() {
+    let area = |polygons: &[Polygon]| -> ::fsize {
+        polygons.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum()
+    };
+
+    // three nested squares: the middle one cuts a ring out of the outer one,
+    // the inner one fills its center again
+    let nested = [Polygon::rect(0.0, 0.0, 6.0, 6.0), Polygon::rect(1.0, 1.0, 4.0, 4.0), Polygon::rect(2.0, 2.0, 2.0, 2.0)];
+    let result = xor_all(&nested);
+    assert_eq!(result.len(), 3);
+    assert_eq!(area(&result), 36.0 - 16.0 + 4.0);
+
+    // three squares overlapping in a common corner region
+    let overlapping = [Polygon::rect(0.0, 0.0, 2.0, 2.0), Polygon::rect(1.0, 0.0, 2.0, 2.0), Polygon::rect(0.5, 1.0, 2.0, 2.0)];
+    let pairwise = MultiPolygon::new(vec![overlapping[0].clone()])
+        .xor(&MultiPolygon::new(vec![overlapping[1].clone()]))
+        .xor(&MultiPolygon::new(vec![overlapping[2].clone()]));
+    assert!((area(&xor_all(&overlapping)) - area(&pairwise.polygons)).abs() < 1e-5);
+
+    assert!(xor_all(&[]).is_empty());
+}
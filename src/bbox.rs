@@ -1,16 +1,79 @@
-use fsize;
+use {Point2D, fsize};
 
 /// Bounding box
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Bbox {
-    pub(crate) top: fsize,
-    pub(crate) right: fsize,
-    pub(crate) bottom: fsize,
-    pub(crate) left: fsize,
+    /// Maximum y coordinate
+    pub top: fsize,
+    /// Maximum x coordinate
+    pub right: fsize,
+    /// Minimum y coordinate
+    pub bottom: fsize,
+    /// Minimum x coordinate
+    pub left: fsize,
 }
 
 impl Bbox {
 
+    /// Creates a new bounding box from the minimum and maximum coordinates
+    ///
+    /// The coordinates are sorted, so passing the corners in the wrong order
+    /// still results in a valid bounding box.
+    #[inline]
+    pub fn new(min_x: fsize, min_y: fsize, max_x: fsize, max_y: fsize) -> Self {
+        Self {
+            top: min_y.max(max_y),
+            right: min_x.max(max_x),
+            bottom: min_y.min(max_y),
+            left: min_x.min(max_x),
+        }
+    }
+
+    /// Calculates the bounding box of the points, returns `None` if there are no points
+    pub fn from_points(points: &[Point2D]) -> Option<Self> {
+        if points.is_empty() {
+            None
+        } else {
+            Some(::utils::calculate_bounding_box(points))
+        }
+    }
+
+    /// Minimum corner (left, bottom)
+    #[inline]
+    pub fn min(&self) -> Point2D {
+        Point2D { x: self.left, y: self.bottom }
+    }
+
+    /// Maximum corner (right, top)
+    #[inline]
+    pub fn max(&self) -> Point2D {
+        Point2D { x: self.right, y: self.top }
+    }
+
+    #[inline]
+    pub fn width(&self) -> fsize {
+        self.right - self.left
+    }
+
+    #[inline]
+    pub fn height(&self) -> fsize {
+        self.top - self.bottom
+    }
+
+    #[inline]
+    pub fn area(&self) -> fsize {
+        self.width() * self.height()
+    }
+
+    #[inline]
+    pub fn center(&self) -> Point2D {
+        Point2D {
+            x: (self.left + self.right) / 2.0,
+            y: (self.bottom + self.top) / 2.0,
+        }
+    }
+
     /// Returns true if two bounding boxes overlap
     #[inline]
     pub fn overlaps(&self, other: &Self) -> bool {
@@ -19,4 +82,56 @@ impl Bbox {
           (other.top < self.bottom) ||
           (other.bottom > self.top))
     }
+
+    /// Returns the smallest bounding box that contains both bounding boxes
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            top: self.top.max(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.min(other.bottom),
+            left: self.left.min(other.left),
+        }
+    }
+
+    /// Returns the overlapping area of two bounding boxes, or `None` if they don't overlap
+    ///
+    /// Bounding boxes that only touch result in a bounding box with zero width or height.
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        Some(Self {
+            top: self.top.min(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.max(other.bottom),
+            left: self.left.max(other.left),
+        })
+    }
+
+    /// Returns true if the point lies inside or on the border of the bounding box
+    #[inline]
+    pub fn contains_point(&self, p: &Point2D) -> bool {
+        p.x >= self.left && p.x <= self.right && p.y >= self.bottom && p.y <= self.top
+    }
+
+    /// Returns true if `other` lies completely inside of this bounding box
+    #[inline]
+    pub fn contains_bbox(&self, other: &Self) -> bool {
+        other.left >= self.left && other.right <= self.right &&
+        other.bottom >= self.bottom && other.top <= self.top
+    }
+
+    /// Grows the bounding box by `margin` in every direction
+    /// (or shrinks it, if `margin` is negative)
+    #[inline]
+    pub fn expand(&self, margin: fsize) -> Self {
+        Self {
+            top: self.top + margin,
+            right: self.right + margin,
+            bottom: self.bottom - margin,
+            left: self.left - margin,
+        }
+    }
 }
@@ -0,0 +1,61 @@
+//! Boolean operations with circles
+//!
+//! The circle is approximated with line segments that deviate no more than
+//! `tolerance` from the true outline (see `Polygon::circle`), so the number
+//! of segments adapts to the radius instead of being guessed by the caller.
+
+use polygon::{Polygon, BoolOpType};
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Calculates the area that is covered by the polygon and the circle
+    pub fn intersect_circle(&self, center: Point2D, radius: fsize, tolerance: fsize) -> Option<Vec<Self>> {
+        self.clip_circle(center, radius, tolerance, BoolOpType::Intersection)
+    }
+
+    /// Calculates the area that is covered by the polygon or the circle
+    pub fn union_circle(&self, center: Point2D, radius: fsize, tolerance: fsize) -> Option<Vec<Self>> {
+        self.clip_circle(center, radius, tolerance, BoolOpType::Union)
+    }
+
+    /// Cuts the circle out of the polygon
+    pub fn difference_circle(&self, center: Point2D, radius: fsize, tolerance: fsize) -> Option<Vec<Self>> {
+        self.clip_circle(center, radius, tolerance, BoolOpType::Difference)
+    }
+
+    /// Runs the boolean operation `operation_type` with a circle as the
+    /// clipping polygon. Returns None if the result is empty.
+    pub fn clip_circle(&self, center: Point2D, radius: fsize, tolerance: fsize, operation_type: BoolOpType)
+    -> Option<Vec<Self>>
+    {
+        let circle = Polygon::circle(center, radius.abs(), tolerance);
+        let mut result: Vec<Polygon> = self.clip_iter(&circle, operation_type).collect();
+        ::multi_polygon::sort_canonical(&mut result);
+        if result.is_empty() { None } else { Some(result) }
+    }
+}
+
+#[test]
+fn test_circle_operations() {
+    use std::f64::consts::PI;
+
+    let square = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+    let corner = Point2D { x: 4.0, y: 4.0 };
+    let area = |polygons: Option<Vec<Polygon>>| -> fsize {
+        polygons.unwrap().iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum()
+    };
+    let quarter = PI as fsize;
+
+    // a quarter of the disc overlaps the square
+    assert!((area(square.intersect_circle(corner, 2.0, 0.001)) - quarter).abs() < 0.01);
+    assert!((area(square.union_circle(corner, 2.0, 0.001)) - (16.0 + 3.0 * quarter)).abs() < 0.03);
+    assert!((area(square.difference_circle(corner, 2.0, 0.001)) - (16.0 - quarter)).abs() < 0.01);
+
+    // a finer tolerance gives a closer approximation
+    let coarse = area(square.intersect_circle(corner, 2.0, 0.1));
+    let fine = area(square.intersect_circle(corner, 2.0, 0.001));
+    assert!(coarse < fine && fine < quarter);
+
+    assert!(square.intersect_circle(Point2D { x: 10.0, y: 10.0 }, 1.0, 0.01).is_none());
+}
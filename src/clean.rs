@@ -0,0 +1,101 @@
+//! Removal of duplicate vertices and collinear midpoints
+
+use polygon::Polygon;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Removes consecutive duplicate vertices and collinear midpoints
+    ///
+    /// Two consecutive vertices closer than `epsilon` are merged into one and
+    /// a vertex is dropped if it is closer than `epsilon` to the line through its
+    /// two neighbours (this also removes zero-area spikes). With an `epsilon` of
+    /// `0.0`, only exact duplicates and exactly collinear points are removed.
+    pub fn clean(&self, epsilon: fsize) -> Self {
+        self.with_nodes(clean_nodes(&self.nodes, self.is_closed, epsilon))
+    }
+}
+
+/// Cleans a list of nodes, see `Polygon::clean`
+///
+/// The boolean operations call this with an `epsilon` of `0.0` before
+/// creating the sweep events, since zero-length edges result in
+/// degenerate events.
+pub(crate) fn clean_nodes(nodes: &[Point2D], is_closed: bool, epsilon: fsize) -> Vec<Point2D> {
+
+    let mut out = Vec::<Point2D>::with_capacity(nodes.len());
+
+    for p in nodes {
+        if let Some(last) = out.last() {
+            if last.dist(p) <= epsilon {
+                continue;
+            }
+        }
+
+        while out.len() >= 2 && is_collinear(&out[out.len() - 2], &out[out.len() - 1], p, epsilon) {
+            out.pop();
+        }
+
+        // popping a spike can leave a duplicate behind
+        if out.last().map(|last| last.dist(p) <= epsilon).unwrap_or(false) {
+            continue;
+        }
+
+        out.push(*p);
+    }
+
+    if !is_closed {
+        return out;
+    }
+
+    // clean up the closing edge of the ring
+    loop {
+        let len = out.len();
+        if len >= 2 && out[len - 1].dist(&out[0]) <= epsilon {
+            out.pop();
+        } else if len >= 3 && is_collinear(&out[len - 2], &out[len - 1], &out[0], epsilon) {
+            out.pop();
+        } else if len >= 3 && is_collinear(&out[len - 1], &out[0], &out[1], epsilon) {
+            out.remove(0);
+        } else {
+            break;
+        }
+    }
+
+    out
+}
+
+/// Is `b` within `epsilon` of the line through `a` and `c`?
+#[inline]
+fn is_collinear(a: &Point2D, b: &Point2D, c: &Point2D, epsilon: fsize) -> bool {
+    let area2 = ::utils::calculate_signed_area3(a, b, c).abs();
+    let base = a.dist(c);
+    if base == 0.0 {
+        // spike going back to where it came from
+        return true;
+    }
+    area2 / base <= epsilon
+}
+
+#[test]
+fn test_clean_duplicates_and_collinear() {
+    let poly = Polygon {
+        nodes: vec![
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 5.0, y: 0.0 },
+            Point2D { x: 10.0, y: 0.0 },
+            Point2D { x: 10.0, y: 10.0 },
+            Point2D { x: 0.0, y: 10.0 },
+            Point2D { x: 0.0, y: 5.0 },
+        ],
+        .. Default::default()
+    };
+    let cleaned = poly.clean(0.0);
+    assert_eq!(cleaned.nodes, vec![
+        Point2D { x: 0.0, y: 0.0 },
+        Point2D { x: 10.0, y: 0.0 },
+        Point2D { x: 10.0, y: 10.0 },
+        Point2D { x: 0.0, y: 10.0 },
+    ]);
+}
@@ -0,0 +1,110 @@
+//! Validation of polygon sets that are supposed to tile an area
+
+use multi_polygon::MultiPolygon;
+use polygon::Polygon;
+
+/// Overlap between two members of a coverage
+#[derive(Debug, Clone)]
+pub struct CoverageOverlap {
+    /// Index (into `polygons`) of the exterior of the first member
+    pub first: usize,
+    /// Index (into `polygons`) of the exterior of the second member
+    pub second: usize,
+    /// The area that is covered by both members
+    pub area: MultiPolygon,
+}
+
+/// Result of `MultiPolygon::validate_coverage`
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReport {
+    /// All pairs of members that overlap
+    pub overlaps: Vec<CoverageOverlap>,
+    /// Areas enclosed by the coverage that no member covers, as
+    /// counter-clockwise exterior rings
+    pub gaps: Vec<Polygon>,
+}
+
+impl CoverageReport {
+
+    /// Returns true if the members neither overlap nor leave gaps
+    pub fn is_valid(&self) -> bool {
+        self.overlaps.is_empty() && self.gaps.is_empty()
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Checks whether the members of the set (every exterior with its holes)
+    /// tile an area exactly, without overlaps and without gaps
+    ///
+    /// Members that only share boundaries are valid. Every pair of members
+    /// whose bounding boxes overlap is intersected, a gap is a hole in the
+    /// union of all members.
+    pub fn validate_coverage(&self) -> CoverageReport {
+
+        let members: Vec<(usize, MultiPolygon)> = self.exteriors_with_holes().into_iter().map(|(exterior, holes)| {
+            let idx = self.polygons.iter().position(|p| ::std::ptr::eq(p, exterior)).unwrap();
+            let mut rings = vec![exterior.clone()];
+            rings.extend(holes.into_iter().cloned());
+            (idx, MultiPolygon::new(rings))
+        }).collect();
+
+        let bboxes: Vec<_> = members.iter().map(|&(idx, _)| self.polygons[idx].bounding_box()).collect();
+        let mut report = CoverageReport::default();
+
+        for i in 0..members.len() {
+            for j in (i + 1)..members.len() {
+                if !bboxes[i].overlaps(&bboxes[j]) {
+                    continue;
+                }
+                let area = members[i].1.intersection(&members[j].1);
+                if !area.polygons.is_empty() {
+                    report.overlaps.push(CoverageOverlap {
+                        first: members[i].0,
+                        second: members[j].0,
+                        area: area,
+                    });
+                }
+            }
+        }
+
+        let union = members.iter().fold(MultiPolygon::new(Vec::new()), |acc, &(_, ref member)| acc.union(member));
+        report.gaps = union.polygons.into_iter().filter(|p| p.is_hole).map(|hole| {
+            let mut nodes = hole.nodes;
+            nodes.reverse();
+            Polygon::new(nodes)
+        }).collect();
+
+        report
+    }
+}
+
+#[test]
+fn test_validate_coverage() {
+
+    // 2x2 grid of unit squares
+    let cells = vec![
+        Polygon::rect(0.0, 0.0, 1.0, 1.0),
+        Polygon::rect(1.0, 0.0, 1.0, 1.0),
+        Polygon::rect(0.0, 1.0, 1.0, 1.0),
+        Polygon::rect(1.0, 1.0, 1.0, 1.0),
+    ];
+    assert!(MultiPolygon::new(cells.clone()).validate_coverage().is_valid());
+
+    // overlapping cells
+    let mut overlapping = cells.clone();
+    overlapping[1] = Polygon::rect(0.9, 0.0, 1.1, 1.0);
+    let report = MultiPolygon::new(overlapping).validate_coverage();
+    assert_eq!(report.overlaps.len(), 1);
+    assert_eq!((report.overlaps[0].first, report.overlaps[0].second), (0, 1));
+    assert!(report.gaps.is_empty());
+
+    // 3x3 grid with a missing center cell
+    let ring: Vec<Polygon> = (0..9).filter(|i| *i != 4).map(|i| {
+        Polygon::rect((i % 3) as ::fsize, (i / 3) as ::fsize, 1.0, 1.0)
+    }).collect();
+    let report = MultiPolygon::new(ring).validate_coverage();
+    assert!(report.overlaps.is_empty());
+    assert_eq!(report.gaps.len(), 1);
+    assert_eq!(report.gaps[0].bounding_box(), ::Bbox::new(1.0, 1.0, 2.0, 2.0));
+}
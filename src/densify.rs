@@ -0,0 +1,111 @@
+//! Densification and resampling of polygon boundaries
+
+use polygon::Polygon;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Inserts vertices along every edge that is longer than `max_edge_length`,
+    /// so that no edge of the result is longer than `max_edge_length`
+    ///
+    /// Existing vertices are kept, new vertices are spaced evenly along the
+    /// original edge. A `max_edge_length` that is not positive returns a copy.
+    pub fn densify(&self, max_edge_length: fsize) -> Self {
+
+        let len = self.nodes.len();
+        if len < 2 || !(max_edge_length > 0.0) {
+            return self.clone();
+        }
+
+        let edge_count = if self.is_closed { len } else { len - 1 };
+        let mut nodes = Vec::<Point2D>::with_capacity(len);
+
+        for i in 0..edge_count {
+            let a = &self.nodes[i];
+            let b = &self.nodes[(i + 1) % len];
+            nodes.push(*a);
+
+            let segments = (a.dist(b) / max_edge_length).ceil() as usize;
+            for s in 1..segments {
+                nodes.push(a.lerp(b, s as fsize / segments as fsize));
+            }
+        }
+
+        if !self.is_closed {
+            nodes.push(self.nodes[len - 1]);
+        }
+
+        self.with_nodes(nodes)
+    }
+
+    /// Replaces the vertices of the polygon with `n_points` vertices that are
+    /// spaced evenly along the boundary
+    ///
+    /// The first vertex is kept. For open chains, the last vertex is kept as well.
+    /// Note that resampling cuts corners, unless the corners happen to lie on a
+    /// sample position.
+    pub fn resample(&self, n_points: usize) -> Self {
+
+        let len = self.nodes.len();
+        let min_points = if self.is_closed { 3 } else { 2 };
+        if len < 2 || n_points < min_points {
+            return self.clone();
+        }
+
+        let edge_count = if self.is_closed { len } else { len - 1 };
+        let edge_lengths: Vec<fsize> = (0..edge_count).map(|i| self.nodes[i].dist(&self.nodes[(i + 1) % len])).collect();
+        let total_length: fsize = edge_lengths.iter().sum();
+
+        if total_length == 0.0 {
+            return self.clone();
+        }
+
+        // closed rings divide the perimeter into n parts, open chains into n - 1
+        let step = if self.is_closed {
+            total_length / n_points as fsize
+        } else {
+            total_length / (n_points - 1) as fsize
+        };
+
+        let mut nodes = Vec::<Point2D>::with_capacity(n_points);
+        let mut edge_idx = 0;
+        let mut edge_start = 0.0;
+
+        for i in 0..n_points {
+            let target = step * i as fsize;
+
+            while edge_idx + 1 < edge_count && edge_start + edge_lengths[edge_idx] < target {
+                edge_start += edge_lengths[edge_idx];
+                edge_idx += 1;
+            }
+
+            let edge_len = edge_lengths[edge_idx];
+            let t = if edge_len == 0.0 { 0.0 } else { ((target - edge_start) / edge_len).max(0.0).min(1.0) };
+            nodes.push(self.nodes[edge_idx].lerp(&self.nodes[(edge_idx + 1) % len], t));
+        }
+
+        if !self.is_closed {
+            // avoid rounding errors at the end of the chain
+            nodes[n_points - 1] = self.nodes[len - 1];
+        }
+
+        self.with_nodes(nodes)
+    }
+}
+
+#[test]
+fn test_densify_square() {
+    let poly = Polygon {
+        nodes: vec![
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 10.0, y: 0.0 },
+            Point2D { x: 10.0, y: 10.0 },
+            Point2D { x: 0.0, y: 10.0 },
+        ],
+        .. Default::default()
+    };
+    let dense = poly.densify(2.5);
+    assert_eq!(dense.nodes.len(), 16);
+    assert_eq!(dense.nodes[1], Point2D { x: 2.5, y: 0.0 });
+    assert_eq!(poly.resample(8).nodes[1], Point2D { x: 5.0, y: 0.0 });
+}
@@ -0,0 +1,197 @@
+//! Import of closed polylines from ASCII DXF files (requires the `dxf` feature)
+
+use polygon::Polygon;
+use multi_polygon::MultiPolygon;
+use flatten::flatten_arc;
+use {Point2D, fsize};
+
+impl MultiPolygon {
+
+    /// Reads all closed `LWPOLYLINE` and `POLYLINE` entities of an ASCII DXF file
+    ///
+    /// Arc segments (vertices with a bulge) are flattened into line segments that
+    /// deviate at most `flatten_tolerance` from the arc. Open polylines and all other
+    /// entities are ignored. DXF has no concept of holes, so `is_hole` is never set -
+    /// use `orient` to classify the contours.
+    ///
+    /// Returns `None` if the file is not a valid ASCII DXF file.
+    pub fn from_dxf(input: &str, flatten_tolerance: fsize) -> Option<Self> {
+
+        let mut lines = input.lines();
+        let mut pairs = Vec::<(i32, &str)>::new();
+
+        while let Some(code) = lines.next() {
+            let code = code.trim();
+            if code.is_empty() {
+                continue;
+            }
+            let value = lines.next()?.trim();
+            pairs.push((code.parse().ok()?, value));
+        }
+
+        let mut polygons = Vec::new();
+        let mut in_entities = false;
+        let mut idx = 0;
+
+        while idx < pairs.len() {
+            match pairs[idx] {
+                (2, "ENTITIES") => in_entities = true,
+                (0, "ENDSEC") => in_entities = false,
+                (0, "LWPOLYLINE") if in_entities => {
+                    let (vertices, closed, next) = read_lwpolyline(&pairs, idx + 1);
+                    if closed {
+                        polygons.extend(polyline_to_polygon(&vertices, flatten_tolerance));
+                    }
+                    idx = next;
+                    continue;
+                },
+                (0, "POLYLINE") if in_entities => {
+                    let (vertices, closed, next) = read_polyline(&pairs, idx + 1);
+                    if closed {
+                        polygons.extend(polyline_to_polygon(&vertices, flatten_tolerance));
+                    }
+                    idx = next;
+                    continue;
+                },
+                _ => { },
+            }
+            idx += 1;
+        }
+
+        Some(MultiPolygon::new(polygons))
+    }
+}
+
+/// A polyline vertex with the bulge of the segment to the next vertex
+#[derive(Debug, Copy, Clone)]
+struct DxfVertex {
+    point: Point2D,
+    bulge: fsize,
+}
+
+/// Is bit 1 (closed) set in the flags of a polyline?
+#[inline]
+fn is_closed_flag(value: &str) -> bool {
+    value.parse::<i32>().map(|flags| flags & 1 == 1).unwrap_or(false)
+}
+
+/// Reads the group codes of a `LWPOLYLINE` entity, starting at `idx`.
+/// Returns the vertices, the closed flag and the index of the next entity.
+fn read_lwpolyline(pairs: &[(i32, &str)], mut idx: usize) -> (Vec<DxfVertex>, bool, usize) {
+    let mut vertices = Vec::new();
+    let mut closed = false;
+
+    while idx < pairs.len() && pairs[idx].0 != 0 {
+        let (code, value) = pairs[idx];
+        match code {
+            70 => closed = is_closed_flag(value),
+            10 => vertices.push(DxfVertex {
+                point: Point2D { x: value.parse().unwrap_or(0.0), y: 0.0 },
+                bulge: 0.0,
+            }),
+            20 => if let Some(v) = vertices.last_mut() { v.point.y = value.parse().unwrap_or(0.0); },
+            42 => if let Some(v) = vertices.last_mut() { v.bulge = value.parse().unwrap_or(0.0); },
+            _ => { },
+        }
+        idx += 1;
+    }
+
+    (vertices, closed, idx)
+}
+
+/// Reads a `POLYLINE` entity with its `VERTEX` entities up to the `SEQEND`
+fn read_polyline(pairs: &[(i32, &str)], mut idx: usize) -> (Vec<DxfVertex>, bool, usize) {
+    let mut vertices = Vec::new();
+    let mut closed = false;
+
+    // header of the polyline
+    while idx < pairs.len() && pairs[idx].0 != 0 {
+        if pairs[idx].0 == 70 {
+            closed = is_closed_flag(pairs[idx].1);
+        }
+        idx += 1;
+    }
+
+    while idx < pairs.len() && pairs[idx] == (0, "VERTEX") {
+        idx += 1;
+        let mut vertex = DxfVertex { point: Point2D { x: 0.0, y: 0.0 }, bulge: 0.0 };
+        while idx < pairs.len() && pairs[idx].0 != 0 {
+            let (code, value) = pairs[idx];
+            match code {
+                10 => vertex.point.x = value.parse().unwrap_or(0.0),
+                20 => vertex.point.y = value.parse().unwrap_or(0.0),
+                42 => vertex.bulge = value.parse().unwrap_or(0.0),
+                _ => { },
+            }
+            idx += 1;
+        }
+        vertices.push(vertex);
+    }
+
+    if idx < pairs.len() && pairs[idx] == (0, "SEQEND") {
+        idx += 1;
+    }
+
+    (vertices, closed, idx)
+}
+
+/// Converts a closed polyline into a polygon, flattening the bulge arcs
+fn polyline_to_polygon(vertices: &[DxfVertex], tolerance: fsize) -> Option<Polygon> {
+    let len = vertices.len();
+    let mut nodes = Vec::<Point2D>::with_capacity(len);
+
+    for i in 0..len {
+        let from = &vertices[i];
+        let to = &vertices[(i + 1) % len];
+        nodes.push(from.point);
+        if from.bulge != 0.0 {
+            bulge_arc(&from.point, &to.point, from.bulge, tolerance, &mut nodes);
+            // the end point of the arc is the next vertex
+            nodes.pop();
+        }
+    }
+
+    let nodes = ::clean::clean_nodes(&nodes, true, 0.0);
+    if nodes.len() < 3 {
+        return None;
+    }
+
+    Some(Polygon::from_parts(nodes, false, true))
+}
+
+/// Flattens the arc between `p1` and `p2` with the given bulge
+/// (tangent of a quarter of the included angle, positive = counter-clockwise)
+fn bulge_arc(p1: &Point2D, p2: &Point2D, bulge: fsize, tolerance: fsize, out: &mut Vec<Point2D>) {
+    let chord = p1.dist(p2);
+    if chord == 0.0 {
+        return;
+    }
+
+    let sweep = 4.0 * bulge.atan();
+    let radius = chord * (1.0 + bulge * bulge) / (4.0 * bulge.abs());
+
+    // signed distance of the center from the middle of the chord, along its left normal
+    let offset = chord / 2.0 * (1.0 - bulge * bulge) / (2.0 * bulge);
+    let center = Point2D {
+        x: (p1.x + p2.x) / 2.0 - (p2.y - p1.y) / chord * offset,
+        y: (p1.y + p2.y) / 2.0 + (p2.x - p1.x) / chord * offset,
+    };
+
+    let start_angle = (p1.y - center.y).atan2(p1.x - center.x);
+    flatten_arc(&center, radius, radius, 0.0, start_angle, sweep, tolerance, out);
+}
+
+#[test]
+fn test_dxf_lwpolyline_with_bulge() {
+    // a 10x10 square whose top edge is a semicircle bulging outwards
+    let dxf = "0\nSECTION\n2\nENTITIES\n0\nLWPOLYLINE\n90\n4\n70\n1\n\
+               10\n0\n20\n0\n10\n10\n20\n0\n10\n10\n20\n10\n42\n1\n10\n0\n20\n10\n\
+               0\nENDSEC\n0\nEOF\n";
+    let multi = MultiPolygon::from_dxf(dxf, 0.01).unwrap();
+    assert_eq!(multi.polygons.len(), 1);
+    let nodes = &multi.polygons[0].nodes;
+    assert!(nodes.len() > 10);
+    // the top of the arc is at y = 15
+    let max_y = nodes.iter().map(|p| p.y).fold(0.0, |a: fsize, b| a.max(b));
+    assert!((max_y - 15.0).abs() < 0.01);
+}
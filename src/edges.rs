@@ -0,0 +1,69 @@
+//! Iterating over the edges of a ring
+
+use Point2D;
+
+/// Iterator over the edges `(start, end)` of a polygon, see `Polygon::edges`
+#[derive(Debug, Clone)]
+pub struct Edges<'a> {
+    nodes: &'a [Point2D],
+    current: usize,
+    count: usize,
+}
+
+impl<'a> Edges<'a> {
+
+    /// Edges between consecutive nodes. If `is_closed` is set, the edge from
+    /// the last to the first node is included.
+    pub(crate) fn new(nodes: &'a [Point2D], is_closed: bool) -> Self {
+        let count = match nodes.len() {
+            0 | 1 => 0,
+            len if is_closed => len,
+            len => len - 1,
+        };
+        Self {
+            nodes: nodes,
+            current: 0,
+            count: count,
+        }
+    }
+
+    /// Same as `next()`, but returns references into the nodes
+    #[inline]
+    pub(crate) fn next_refs(&mut self) -> Option<(&'a Point2D, &'a Point2D)> {
+        if self.current >= self.count {
+            return None;
+        }
+        let start = &self.nodes[self.current];
+        let end = &self.nodes[(self.current + 1) % self.nodes.len()];
+        self.current += 1;
+        Some((start, end))
+    }
+}
+
+impl<'a> Iterator for Edges<'a> {
+    type Item = (Point2D, Point2D);
+
+    #[inline]
+    fn next(&mut self) -> Option<(Point2D, Point2D)> {
+        self.next_refs().map(|(start, end)| (*start, *end))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.current;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Edges<'a> { }
+
+#[test]
+fn test_edges() {
+    let nodes = [Point2D { x: 0.0, y: 0.0 }, Point2D { x: 1.0, y: 0.0 }, Point2D { x: 1.0, y: 1.0 }];
+
+    let closed: Vec<(Point2D, Point2D)> = Edges::new(&nodes, true).collect();
+    assert_eq!(closed, vec![(nodes[0], nodes[1]), (nodes[1], nodes[2]), (nodes[2], nodes[0])]);
+
+    assert_eq!(Edges::new(&nodes, false).len(), 2);
+    assert_eq!(Edges::new(&nodes[..1], true).len(), 0);
+}
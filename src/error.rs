@@ -0,0 +1,97 @@
+//! Errors and warnings of the boolean operations
+
+use std::error::Error;
+use std::fmt;
+use {Point2D, fsize};
+
+/// Reason why a boolean operation did not finish
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClipError {
+    /// The operation was cancelled with `ClipOptions::cancel_token` or took
+    /// longer than `ClipOptions::timeout`
+    Cancelled,
+    /// The operation needed more sweep events than `ClipOptions::max_events`
+    TooManyEvents,
+    /// The result has more vertices than `ClipOptions::max_output_vertices`
+    OutputTooLarge,
+    /// An input vertex has a NaN or infinite coordinate. `index` counts the
+    /// vertices of all input rings, first the subject and then the clip rings.
+    NonFiniteCoordinate { index: usize },
+    /// An input vertex of `clip_i64` has a coordinate whose absolute value is
+    /// larger than `PolygonI64::MAX_COORDINATE`. `index` counts the vertices
+    /// like for `NonFiniteCoordinate`.
+    CoordinateOutOfRange { index: usize },
+}
+
+impl fmt::Display for ClipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ClipError::Cancelled => write!(f, "the boolean operation was cancelled"),
+            ClipError::TooManyEvents => write!(f, "the boolean operation exceeded the maximum number of events"),
+            ClipError::OutputTooLarge => write!(f, "the result exceeded the maximum number of vertices"),
+            ClipError::NonFiniteCoordinate { index } => write!(f, "input vertex {} has a non-finite coordinate", index),
+            ClipError::CoordinateOutOfRange { index } => write!(f, "input vertex {} has a coordinate outside of the integer range", index),
+        }
+    }
+}
+
+impl Error for ClipError { }
+
+/// Problem with the input that the boolean operation worked around, or with
+/// its result
+///
+/// Degenerate input contours are treated as if they were empty: they are
+/// dropped before the operation and reported with one of these warnings.
+/// `ring` counts the input rings, first the subject and then the clip rings.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClipWarning {
+    /// The contour has only one or two vertices
+    TooFewVertices { ring: usize },
+    /// The contour has three or more vertices, but does not enclose any area
+    /// (all vertices are duplicates or lie on a single line)
+    ZeroArea { ring: usize },
+    /// The result differs from the result of `reference_clip`, see
+    /// `ClipOptions::verify`
+    ReferenceMismatch,
+}
+
+impl fmt::Display for ClipWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ClipWarning::TooFewVertices { ring } => write!(f, "input ring {} has less than three vertices and was dropped", ring),
+            ClipWarning::ZeroArea { ring } => write!(f, "input ring {} has zero area and was dropped", ring),
+            ClipWarning::ReferenceMismatch => write!(f, "the result differs from the result of the reference implementation"),
+        }
+    }
+}
+
+/// Reason why a result did not pass `verify_result`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum VerificationError {
+    /// Edges of the result cross or touch at `point`, apart from rings that
+    /// meet in a shared vertex
+    SelfIntersection { point: Point2D },
+    /// Result ring `ring` is clockwise but not a hole, or the other way round
+    WrongOrientation { ring: usize },
+    /// The area of the result is outside of the range that is possible for
+    /// the operation, given the areas of the inputs
+    ImplausibleArea { area: fsize, min: fsize, max: fsize },
+    /// The sample `point` lies inside of the result, but not in the area that
+    /// the operation selects from the inputs (`expected` is false), or the
+    /// other way round
+    PointMismatch { point: Point2D, expected: bool },
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VerificationError::SelfIntersection { point } => write!(f, "the result intersects itself at ({}, {})", point.x, point.y),
+            VerificationError::WrongOrientation { ring } => write!(f, "result ring {} has the wrong orientation", ring),
+            VerificationError::ImplausibleArea { area, min, max } => write!(f, "the result has an area of {}, expected {} to {}", area, min, max),
+            VerificationError::PointMismatch { point, expected } => write!(
+                f, "the point ({}, {}) should {}be inside of the result", point.x, point.y, if expected { "" } else { "not " }),
+        }
+    }
+}
+
+impl Error for VerificationError { }
@@ -0,0 +1,50 @@
+//! Conversions from and to `euclid` (requires the `euclid` feature)
+//!
+//! The conversions work for any unit of `euclid`, so the caller chooses the
+//! coordinate space the points end up in.
+
+use euclid::{Point2D as EuclidPoint, Box2D};
+use bbox::Bbox;
+use {Point2D, fsize};
+
+impl<U> From<EuclidPoint<fsize, U>> for Point2D {
+    fn from(p: EuclidPoint<fsize, U>) -> Self {
+        Point2D { x: p.x, y: p.y }
+    }
+}
+
+impl<U> From<Point2D> for EuclidPoint<fsize, U> {
+    fn from(p: Point2D) -> Self {
+        EuclidPoint::new(p.x, p.y)
+    }
+}
+
+impl<U> From<Box2D<fsize, U>> for Bbox {
+    fn from(b: Box2D<fsize, U>) -> Self {
+        Bbox::new(b.min.x, b.min.y, b.max.x, b.max.y)
+    }
+}
+
+impl<U> From<Bbox> for Box2D<fsize, U> {
+    fn from(b: Bbox) -> Self {
+        Box2D::new(b.min().into(), b.max().into())
+    }
+}
+
+#[test]
+fn test_euclid_conversions() {
+    use euclid::UnknownUnit;
+
+    // a unit of the application
+    struct WorldSpace;
+
+    let p = Point2D { x: 1.5, y: -2.0 };
+    let q: EuclidPoint<fsize, WorldSpace> = p.into();
+    assert_eq!((q.x, q.y), (1.5, -2.0));
+    assert_eq!(Point2D::from(q), p);
+
+    let bbox = Bbox::new(0.0, -1.0, 4.0, 3.0);
+    let b: Box2D<fsize, UnknownUnit> = bbox.into();
+    assert_eq!((b.min.x, b.min.y, b.max.x, b.max.y), (0.0, -1.0, 4.0, 3.0));
+    assert_eq!(Bbox::from(b), bbox);
+}
@@ -0,0 +1,98 @@
+//! Event queue of the intersection sweep
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::iter::FromIterator;
+
+/// Min-priority queue tuned for the access pattern of the sweep
+///
+/// All endpoints of the input are known up front, so they are sorted once and
+/// popped from a plain array. Only the crossings of segments, which the sweep
+/// finds on its way, are inserted later, these go into a small binary heap.
+/// Popping takes the smaller of the next sorted event and the top of the heap.
+pub(crate) struct EventQueue<T: Ord> {
+    /// The initial events, sorted from last to first, so the next one is at the end
+    sorted: Vec<T>,
+    /// The events that were pushed after the queue was created
+    inserted: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> EventQueue<T> {
+
+    /// Creates a queue of the initial events
+    pub(crate) fn new(mut events: Vec<T>) -> Self {
+        events.sort_unstable_by(|a, b| b.cmp(a));
+        Self {
+            sorted: events,
+            inserted: BinaryHeap::new(),
+        }
+    }
+
+    /// Inserts an event
+    #[inline]
+    pub(crate) fn push(&mut self, event: T) {
+        self.inserted.push(Reverse(event));
+    }
+
+    /// Removes and returns the smallest event
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        if self.next_is_inserted() {
+            self.inserted.pop().map(|Reverse(event)| event)
+        } else {
+            self.sorted.pop()
+        }
+    }
+
+    /// Returns the smallest event without removing it
+    pub(crate) fn peek(&self) -> Option<&T> {
+        if self.next_is_inserted() {
+            self.inserted.peek().map(|&Reverse(ref event)| event)
+        } else {
+            self.sorted.last()
+        }
+    }
+
+    /// Whether the smallest event is in the heap of the inserted events
+    fn next_is_inserted(&self) -> bool {
+        match (self.sorted.last(), self.inserted.peek()) {
+            (Some(next), Some(&Reverse(ref inserted))) => inserted < next,
+            (None, Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Number of events in the queue
+    pub(crate) fn len(&self) -> usize {
+        self.sorted.len() + self.inserted.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Ord> FromIterator<T> for EventQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[test]
+fn test_event_queue() {
+    let mut queue: EventQueue<u32> = vec![5, 1, 9, 3].into_iter().collect();
+    assert_eq!(queue.pop(), Some(1));
+
+    // inserted events are merged with the remaining initial ones
+    queue.push(4);
+    queue.push(2);
+    queue.push(10);
+    assert_eq!(queue.len(), 6);
+
+    assert_eq!(queue.peek(), Some(&2));
+    let mut order = Vec::new();
+    while let Some(event) = queue.pop() {
+        order.push(event);
+    }
+    assert_eq!(order, vec![2, 3, 4, 5, 9, 10]);
+    assert!(queue.is_empty());
+}
@@ -0,0 +1,70 @@
+//! Resolving of overlapping rings into exteriors and holes with a fill rule
+
+use arrangement::{Arrangement, BoundaryRings};
+use multi_polygon::{MultiPolygon, sort_canonical};
+use polygon::{Polygon, FillRule};
+
+impl MultiPolygon {
+
+    /// Prepares the contours of a font glyph (or any other outline that is
+    /// filled with the non-zero rule) for clipping or triangulation
+    ///
+    /// The contours may overlap each other and have mixed orientations, as
+    /// is common for glyphs that are composed of several components. Curved
+    /// contours can be flattened with `Polygon::from_quadratic_contour` or
+    /// `Polygon::from_cubic_contour`. See `resolve_fill_rule` for the result.
+    pub fn from_glyph_contours(contours: Vec<Polygon>) -> Self {
+        MultiPolygon::new(contours).resolve_fill_rule(FillRule::NonZero)
+    }
+}
+
+impl<T: Clone> MultiPolygon<T> {
+
+    /// Merges the rings of the set (which may overlap or intersect themselves)
+    /// into rings that do not overlap, filling the areas selected by `fill_rule`
+    ///
+    /// This is a union of the set with itself. The result consists of
+    /// counter-clockwise exteriors and clockwise holes with `is_hole` set,
+    /// without duplicate or collinear vertices. The `is_hole` flags of the
+    /// input are ignored: with `FillRule::NonZero`, holes have to be oriented
+    /// against their exterior to be cut out.
+    pub fn resolve_fill_rule(&self, fill_rule: FillRule) -> Self {
+        let arrangement = Arrangement::new(&self.polygons, &[]);
+        let rings = match fill_rule {
+            FillRule::EvenOdd => BoundaryRings::select_faces(&arrangement, |f| f.in_a),
+            FillRule::NonZero => BoundaryRings::select_faces(&arrangement, |f| f.winding_a != 0),
+        };
+
+        let mut polygons: Vec<Polygon> = rings.map(|traced| traced.polygon.clean(0.0)).collect();
+        sort_canonical(&mut polygons);
+        MultiPolygon::with_data(polygons, self.data.clone())
+    }
+}
+
+#[test]
+fn test_resolve_fill_rule() {
+    use fsize;
+
+    let area = |multi: &MultiPolygon| -> fsize {
+        multi.polygons.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum()
+    };
+    let reversed = |p: Polygon| Polygon::from_parts(p.nodes.into_iter().rev().collect(), false, true);
+
+    // two overlapping squares with the same orientation
+    let overlapping = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 2.0, 2.0), Polygon::rect(1.0, 1.0, 2.0, 2.0)]);
+    let non_zero = overlapping.resolve_fill_rule(FillRule::NonZero);
+    assert_eq!(non_zero.polygons.len(), 1);
+    assert_eq!(non_zero.polygons[0].nodes.len(), 8);
+    assert_eq!(area(&non_zero), 7.0);
+    assert_eq!(area(&overlapping.resolve_fill_rule(FillRule::EvenOdd)), 6.0);
+
+    // the counter of an "O" is cut out if it runs against the outer contour
+    let glyph = MultiPolygon::from_glyph_contours(vec![Polygon::rect(0.0, 0.0, 4.0, 4.0), reversed(Polygon::rect(1.0, 1.0, 2.0, 2.0))]);
+    assert_eq!(glyph.polygons.len(), 2);
+    assert_eq!(glyph.polygons.iter().filter(|p| p.is_hole).count(), 1);
+    assert_eq!(area(&glyph), 12.0);
+
+    let filled = MultiPolygon::from_glyph_contours(vec![Polygon::rect(0.0, 0.0, 4.0, 4.0), Polygon::rect(1.0, 1.0, 2.0, 2.0)]);
+    assert_eq!(filled.polygons.len(), 1);
+    assert_eq!(area(&filled), 16.0);
+}
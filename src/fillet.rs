@@ -0,0 +1,148 @@
+//! Corner rounding (fillets)
+
+use polygon::Polygon;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Replaces every corner of the polygon with a circular arc of the given `radius`
+    ///
+    /// The arcs are approximated with line segments, so that the maximum distance
+    /// between the segments and the true arc is `arc_tolerance`. If two adjacent
+    /// corners do not have enough room for the full radius, the radius of these
+    /// corners is reduced so that the fillets meet in the middle of the edge.
+    ///
+    /// Both convex and concave corners are rounded. Open chains keep
+    /// their first and last point.
+    pub fn round_corners(&self, radius: fsize, arc_tolerance: fsize) -> Self {
+
+        let len = self.nodes.len();
+        if len < 3 || !(radius > 0.0) {
+            return self.clone();
+        }
+
+        let mut nodes = Vec::<Point2D>::with_capacity(len * 4);
+        let (first, last) = if self.is_closed { (0, len) } else { (1, len - 1) };
+
+        if !self.is_closed {
+            nodes.push(self.nodes[0]);
+        }
+
+        for i in first..last {
+            let a = &self.nodes[(i + len - 1) % len];
+            let b = &self.nodes[i];
+            let c = &self.nodes[(i + 1) % len];
+
+            // the fillet may use at most half of each adjacent edge,
+            // unless the neighbour is the (unrounded) end of an open chain
+            let max_a = if !self.is_closed && i == 1 { a.dist(b) } else { a.dist(b) / 2.0 };
+            let max_c = if !self.is_closed && i + 2 == len { b.dist(c) } else { b.dist(c) / 2.0 };
+
+            fillet(a, b, c, radius, max_a.min(max_c), arc_tolerance, &mut nodes);
+        }
+
+        if !self.is_closed {
+            nodes.push(self.nodes[len - 1]);
+        }
+
+        self.with_nodes(nodes)
+    }
+}
+
+/// Number of line segments needed to approximate an arc with the given
+/// `radius` and `sweep_angle` (in radians), so that the segments do not deviate
+/// more than `tolerance` from the arc
+pub(crate) fn arc_segment_count(radius: fsize, sweep_angle: fsize, tolerance: fsize) -> usize {
+    let sweep_angle = sweep_angle.abs();
+    if !(tolerance > 0.0) || tolerance >= radius {
+        // one segment per quarter circle at most
+        return ((sweep_angle / (::std::f64::consts::FRAC_PI_2 as fsize)).ceil() as usize).max(1);
+    }
+    let max_step = 2.0 * (1.0 - tolerance / radius).acos();
+    ((sweep_angle / max_step).ceil() as usize).max(1)
+}
+
+/// Pushes the rounded corner `b` (between the edges (a, b) and (b, c)) onto `out`
+///
+/// `max_tangent_len` is the maximum distance from `b` to the points where
+/// the arc touches the edges.
+fn fillet(a: &Point2D, b: &Point2D, c: &Point2D, radius: fsize, max_tangent_len: fsize,
+          arc_tolerance: fsize, out: &mut Vec<Point2D>)
+{
+    if a == b || b == c {
+        out.push(*b);
+        return;
+    }
+
+    let ua = (*a - *b).normalize();
+    let uc = (*c - *b).normalize();
+
+    // interior angle at b
+    let theta = ua.dot(&uc).max(-1.0).min(1.0).acos();
+    let pi = ::std::f64::consts::PI as fsize;
+
+    // straight or fully folded corner, nothing to round
+    if theta < 1e-6 || pi - theta < 1e-6 {
+        out.push(*b);
+        return;
+    }
+
+    let half = theta / 2.0;
+    let mut tangent_len = radius / half.tan();
+    let mut radius = radius;
+
+    if tangent_len > max_tangent_len {
+        tangent_len = max_tangent_len;
+        radius = tangent_len * half.tan();
+    }
+
+    if !(tangent_len > 0.0) {
+        out.push(*b);
+        return;
+    }
+
+    // the center lies on the bisector of the corner
+    let bisector = (ua + uc).normalize();
+    let center = *b + bisector * (radius / half.sin());
+
+    let start = *b + ua * tangent_len;
+    let end = *b + uc * tangent_len;
+
+    let start_angle = (start.y - center.y).atan2(start.x - center.x);
+    let end_angle = (end.y - center.y).atan2(end.x - center.x);
+    let mut sweep = end_angle - start_angle;
+    if sweep > pi {
+        sweep -= 2.0 * pi;
+    } else if sweep < -pi {
+        sweep += 2.0 * pi;
+    }
+
+    let segments = arc_segment_count(radius, sweep, arc_tolerance);
+
+    out.push(start);
+    for s in 1..segments {
+        let angle = start_angle + sweep * (s as fsize / segments as fsize);
+        out.push(center + Point2D { x: angle.cos(), y: angle.sin() } * radius);
+    }
+    out.push(end);
+}
+
+#[test]
+fn test_round_corners_square() {
+    let poly = Polygon {
+        nodes: vec![
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 10.0, y: 0.0 },
+            Point2D { x: 10.0, y: 10.0 },
+            Point2D { x: 0.0, y: 10.0 },
+        ],
+        .. Default::default()
+    };
+    let rounded = poly.round_corners(2.0, 0.01);
+    assert!(rounded.nodes.len() > 8);
+    // no point may lie outside the square or in the cut-off corner
+    for p in &rounded.nodes {
+        assert!(p.x >= -0.001 && p.x <= 10.001 && p.y >= -0.001 && p.y <= 10.001);
+        assert!(p.dist(&Point2D { x: 10.0, y: 10.0 }) > 0.8);
+    }
+}
@@ -0,0 +1,166 @@
+//! Flattening of curves into line segments
+
+use fillet::arc_segment_count;
+use polygon::Polygon;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Closed polygon from a contour of quadratic bezier curves, for example
+    /// a glyph outline
+    ///
+    /// `points` alternates between on-curve points and control points:
+    /// `[p0, c0, p1, c1, ...]`, the last curve ends at `p0` again. The curves are
+    /// flattened so that the segments do not deviate more than `tolerance` from
+    /// them. If the number of points is odd, the last point is connected to
+    /// `p0` with a straight line.
+    pub fn from_quadratic_contour(points: &[Point2D], tolerance: fsize) -> Self {
+        flatten_contour(points, 2, tolerance)
+    }
+
+    /// Closed polygon from a contour of cubic bezier curves, for example an
+    /// SVG path or a PostScript font outline
+    ///
+    /// `points` is a list of on-curve points, each followed by the two control
+    /// points of the next curve: `[p0, c0, d0, p1, c1, d1, ...]`, the last curve
+    /// ends at `p0` again. See `from_quadratic_contour` for the `tolerance`.
+    /// Points of an incomplete last curve are connected with straight lines.
+    pub fn from_cubic_contour(points: &[Point2D], tolerance: fsize) -> Self {
+        flatten_contour(points, 3, tolerance)
+    }
+}
+
+/// Flattens a closed contour of bezier curves of the given `degree` (2 or 3)
+fn flatten_contour(points: &[Point2D], degree: usize, tolerance: fsize) -> Polygon {
+
+    let len = points.len();
+    let mut nodes = Vec::<Point2D>::with_capacity(len * 4);
+    nodes.extend(points.first());
+
+    let mut idx = 0;
+    while idx < len {
+        let start = *nodes.last().unwrap();
+        if idx + degree > len {
+            // incomplete last curve, its points are connected with lines
+            nodes.extend_from_slice(&points[(idx + 1)..]);
+            break;
+        }
+
+        let end = points[(idx + degree) % len];
+        if degree == 2 {
+            flatten_quadratic(&start, &points[idx + 1], &end, tolerance, &mut nodes);
+        } else {
+            flatten_cubic(&start, &points[idx + 1], &points[idx + 2], &end, tolerance, &mut nodes);
+        }
+        idx += degree;
+    }
+
+    // the last curve ends at the first point
+    if nodes.len() > 1 && nodes.first() == nodes.last() {
+        nodes.pop();
+    }
+
+    Polygon::from_parts(nodes, false, true)
+}
+
+/// Appends a flattened quadratic bezier curve from `p0` (exclusive) over the
+/// control point `p1` to `p2` (inclusive) to `out`, so that the line segments
+/// do not deviate more than `tolerance` from the curve
+pub(crate) fn flatten_quadratic(p0: &Point2D, p1: &Point2D, p2: &Point2D, tolerance: fsize, out: &mut Vec<Point2D>) {
+    // the deviation of n segments is bounded by |B''| / (8 * n^2), |B''| = 2 * |p0 - 2 p1 + p2|
+    let dd = ((p0.x - 2.0 * p1.x + p2.x).powi(2) + (p0.y - 2.0 * p1.y + p2.y).powi(2)).sqrt();
+    let segments = segment_count(dd / 4.0, tolerance);
+
+    for s in 1..(segments + 1) {
+        let t = s as fsize / segments as fsize;
+        let mt = 1.0 - t;
+        out.push(Point2D {
+            x: mt * mt * p0.x + 2.0 * mt * t * p1.x + t * t * p2.x,
+            y: mt * mt * p0.y + 2.0 * mt * t * p1.y + t * t * p2.y,
+        });
+    }
+}
+
+/// Appends a flattened cubic bezier curve from `p0` (exclusive) over the
+/// control points `p1` and `p2` to `p3` (inclusive) to `out`
+pub(crate) fn flatten_cubic(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D, tolerance: fsize, out: &mut Vec<Point2D>) {
+    // |B''| <= 6 * max(|p0 - 2 p1 + p2|, |p1 - 2 p2 + p3|)
+    let dd1 = ((p0.x - 2.0 * p1.x + p2.x).powi(2) + (p0.y - 2.0 * p1.y + p2.y).powi(2)).sqrt();
+    let dd2 = ((p1.x - 2.0 * p2.x + p3.x).powi(2) + (p1.y - 2.0 * p2.y + p3.y).powi(2)).sqrt();
+    let segments = segment_count(dd1.max(dd2) * 0.75, tolerance);
+
+    for s in 1..(segments + 1) {
+        let t = s as fsize / segments as fsize;
+        let mt = 1.0 - t;
+        let a = mt * mt * mt;
+        let b = 3.0 * mt * mt * t;
+        let c = 3.0 * mt * t * t;
+        let d = t * t * t;
+        out.push(Point2D {
+            x: a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+            y: a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+        });
+    }
+}
+
+/// Appends a flattened elliptical arc, given in center parameterization, to `out`.
+/// The start point (at `start_angle`) is not included, the end point is.
+///
+/// `rotation` is the rotation of the x axis of the ellipse, `sweep` is the
+/// (signed) angle that the arc covers, all angles are in radians.
+pub(crate) fn flatten_arc(center: &Point2D, rx: fsize, ry: fsize, rotation: fsize,
+                          start_angle: fsize, sweep: fsize, tolerance: fsize, out: &mut Vec<Point2D>)
+{
+    let segments = arc_segment_count(rx.abs().max(ry.abs()), sweep, tolerance);
+    let (sin_rot, cos_rot) = rotation.sin_cos();
+
+    for s in 1..(segments + 1) {
+        let angle = start_angle + sweep * (s as fsize / segments as fsize);
+        let (sin, cos) = angle.sin_cos();
+        let x = rx * cos;
+        let y = ry * sin;
+        out.push(Point2D {
+            x: center.x + cos_rot * x - sin_rot * y,
+            y: center.y + sin_rot * x + cos_rot * y,
+        });
+    }
+}
+
+/// Number of segments `n` so that `curvature / n^2 <= tolerance`
+#[inline]
+fn segment_count(curvature: fsize, tolerance: fsize) -> usize {
+    if !(tolerance > 0.0) {
+        return 16;
+    }
+    ((curvature / tolerance).sqrt().ceil() as usize).max(1)
+}
+
+#[test]
+fn test_flatten_contours() {
+    let p = |x, y| Point2D { x: x, y: y };
+
+    // circle with radius 1.0 out of four cubic curves
+    let k = 0.5523;
+    let circle = vec![
+        p(1.0, 0.0), p(1.0, k), p(k, 1.0),
+        p(0.0, 1.0), p(-k, 1.0), p(-1.0, k),
+        p(-1.0, 0.0), p(-1.0, -k), p(-k, -1.0),
+        p(0.0, -1.0), p(k, -1.0), p(1.0, -k),
+    ];
+    let fine = Polygon::from_cubic_contour(&circle, 0.0001);
+    let coarse = Polygon::from_cubic_contour(&circle, 0.01);
+    assert!(coarse.nodes.len() < fine.nodes.len());
+    assert_eq!(fine.nodes[0], p(1.0, 0.0));
+    assert!(fine.nodes.iter().all(|n| (n.length() - 1.0).abs() < 0.001));
+    assert!((fine.area() - ::std::f64::consts::PI as fsize).abs() < 0.01);
+
+    // a quadratic contour whose control points lie on the edges is a triangle
+    let triangle = Polygon::from_quadratic_contour(&[
+        p(0.0, 0.0), p(1.0, 0.0), p(2.0, 0.0), p(1.5, 0.5), p(1.0, 1.0), p(0.5, 0.5),
+    ], 0.01);
+    assert_eq!(triangle.clean(0.0).nodes, vec![p(0.0, 0.0), p(2.0, 0.0), p(1.0, 1.0)]);
+
+    // an odd number of points closes with a straight line
+    let open_end = Polygon::from_quadratic_contour(&[p(0.0, 0.0), p(1.0, -1.0), p(2.0, 0.0), p(2.0, 2.0), p(0.0, 2.0)], 0.01);
+    assert_eq!(*open_end.nodes.last().unwrap(), p(0.0, 2.0));
+}
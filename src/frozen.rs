@@ -0,0 +1,203 @@
+//! Immutable polygons with precomputed derived data
+//!
+//! The fields of `Polygon` are public, so its cached bounding box and winding
+//! order can be reset or go stale, and the boolean operations recompute the
+//! bounding box whenever it is missing. A `FrozenPolygon` is created once,
+//! with `PolygonBuilder` or from a `Polygon`, computes the bounding box,
+//! winding order, area and validity at that time and can not be modified
+//! afterwards, so the cached values never have to be invalidated. The nodes
+//! are reference counted: clones are cheap and can be sent to other threads.
+
+use std::ops::Deref;
+use std::sync::Arc;
+use polygon::{Polygon, WindingOrder};
+use {Point2D, Bbox, fsize};
+
+/// Builder of a `FrozenPolygon`
+///
+/// ```rust
+/// # use polyclip::PolygonBuilder;
+/// let triangle = PolygonBuilder::new()
+///     .point(0.0, 0.0)
+///     .point(4.0, 0.0)
+///     .point(0.0, 3.0)
+///     .build();
+/// assert_eq!(triangle.area(), 6.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PolygonBuilder {
+    nodes: Vec<Point2D>,
+    is_hole: bool,
+    is_closed: bool,
+    measures: Option<Vec<fsize>>,
+}
+
+impl Default for PolygonBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PolygonBuilder {
+
+    /// Starts an empty, closed polygon that is not a hole
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Same as `new`, with space for `capacity` nodes
+    pub fn with_capacity(capacity: usize) -> Self {
+        PolygonBuilder {
+            nodes: Vec::with_capacity(capacity),
+            is_hole: false,
+            is_closed: true,
+            measures: None,
+        }
+    }
+
+    /// Appends a node
+    pub fn point(mut self, x: fsize, y: fsize) -> Self {
+        self.nodes.push(Point2D { x: x, y: y });
+        self
+    }
+
+    /// Appends several nodes
+    pub fn points<I: IntoIterator<Item = Point2D>>(mut self, points: I) -> Self {
+        self.nodes.extend(points);
+        self
+    }
+
+    /// Sets whether the polygon is a hole, see `Polygon::is_hole`
+    pub fn hole(mut self, is_hole: bool) -> Self {
+        self.is_hole = is_hole;
+        self
+    }
+
+    /// Sets whether the polygon is closed, see `Polygon::is_closed`
+    pub fn closed(mut self, is_closed: bool) -> Self {
+        self.is_closed = is_closed;
+        self
+    }
+
+    /// Sets one extra value per node, see `Polygon::measures`
+    pub fn measures(mut self, measures: Vec<fsize>) -> Self {
+        self.measures = Some(measures);
+        self
+    }
+
+    /// Computes the derived data and returns the immutable polygon
+    pub fn build(self) -> FrozenPolygon {
+        let mut polygon = Polygon::from_parts(self.nodes, self.is_hole, self.is_closed);
+        polygon.measures = self.measures;
+        FrozenPolygon::from(polygon)
+    }
+}
+
+/// Immutable polygon whose bounding box, winding order, area and validity
+/// are computed once, see the module documentation
+///
+/// All read-only methods of `Polygon` are available through `Deref`, so a
+/// `&FrozenPolygon` can be passed wherever a `&Polygon` is expected, for
+/// example to `Polygon::try_clip`. The bounding box is always cached there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrozenPolygon {
+    polygon: Arc<Polygon>,
+    area: fsize,
+    is_valid: bool,
+}
+
+impl From<Polygon> for FrozenPolygon {
+    fn from(mut polygon: Polygon) -> Self {
+        polygon.bbox = Bbox::from_points(&polygon.nodes);
+        polygon.winding = ::utils::try_calculate_winding_order(&polygon.nodes);
+
+        let min_nodes = if polygon.is_closed { 3 } else { 2 };
+        let is_valid = polygon.nodes.len() >= min_nodes &&
+            ::utils::check_finite(polygon.nodes.iter()).is_ok() &&
+            polygon.measures.as_ref().map_or(true, |m| m.len() == polygon.nodes.len()) &&
+            ::verify::find_crossing(Some(&polygon.nodes[..]), polygon.is_closed).is_none();
+
+        FrozenPolygon {
+            area: polygon.area(),
+            is_valid: is_valid,
+            polygon: Arc::new(polygon),
+        }
+    }
+}
+
+impl Deref for FrozenPolygon {
+    type Target = Polygon;
+
+    fn deref(&self) -> &Polygon {
+        &self.polygon
+    }
+}
+
+impl FrozenPolygon {
+
+    /// Bounding box of the nodes, None if there are none
+    pub fn bbox(&self) -> Option<Bbox> {
+        self.polygon.bbox
+    }
+
+    /// Winding order of the nodes, None if there are less than three
+    pub fn winding(&self) -> Option<WindingOrder> {
+        self.polygon.winding
+    }
+
+    /// Absolute area of the polygon, as if it was closed (see `Polygon::area`)
+    pub fn area(&self) -> fsize {
+        self.area
+    }
+
+    /// Whether the polygon has enough nodes (three if closed, two if open),
+    /// only finite coordinates, one measure per node (if any) and no edges
+    /// that cross each other
+    pub fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+
+    /// Returns a mutable copy of the polygon, which is only cloned if the
+    /// nodes are still shared with other clones
+    pub fn into_polygon(self) -> Polygon {
+        Arc::try_unwrap(self.polygon).unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
+#[test]
+fn test_frozen_polygon() {
+    use std::thread;
+    use polygon::BoolOpType;
+    use options::ClipOptions;
+
+    let square = PolygonBuilder::new()
+        .points(vec![Point2D { x: 0.0, y: 0.0 }, Point2D { x: 0.0, y: 2.0 }])
+        .point(2.0, 2.0)
+        .point(2.0, 0.0)
+        .build();
+    assert_eq!(square.bbox(), Some(Bbox::new(0.0, 0.0, 2.0, 2.0)));
+    assert_eq!(square.winding(), Some(WindingOrder::Clockwise));
+    assert_eq!(square.area(), 4.0);
+    assert!(square.is_valid());
+
+    let bowtie = FrozenPolygon::from(Polygon::from(vec![(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]));
+    assert!(!bowtie.is_valid());
+    assert!(!PolygonBuilder::new().point(0.0, 0.0).point(1.0, 1.0).build().is_valid());
+    assert!(PolygonBuilder::new().point(0.0, 0.0).point(1.0, 1.0).closed(false).build().is_valid());
+
+    // clones share the nodes and can be clipped on other threads
+    let options = ClipOptions::default();
+    let other = FrozenPolygon::from(Polygon::rect(1.0, 1.0, 2.0, 2.0));
+    let handles: Vec<_> = (0..2).map(|_| {
+        let (square, other, options) = (square.clone(), other.clone(), options.clone());
+        thread::spawn(move || square.try_clip(&other, BoolOpType::Intersection, &options).unwrap())
+    }).collect();
+    for handle in handles {
+        let result = handle.join().unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].area(), 1.0);
+    }
+
+    let polygon = square.into_polygon();
+    assert_eq!(polygon.nodes.len(), 4);
+}
@@ -0,0 +1,146 @@
+//! Reproducible random polygons for benchmarks and load tests
+//!
+//! A `PolygonGenerator` is seeded, so the same seed produces the same
+//! polygons on every run and platform. All generated polygons are simple and
+//! counter-clockwise. Holes are clockwise and lie strictly inside of their
+//! exterior, without touching it or each other.
+
+use multi_polygon::MultiPolygon;
+use polygon::Polygon;
+use utils::distance_point_segment;
+use edges::Edges;
+use {Point2D, fsize};
+
+const TWO_PI: fsize = 2.0 * ::std::f64::consts::PI as fsize;
+
+/// Seeded source of random polygons with a given number of vertices
+#[derive(Debug, Clone)]
+pub struct PolygonGenerator {
+    state: u64,
+}
+
+impl PolygonGenerator {
+
+    /// Creates a generator with the given seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Star-shaped polygon with `n` vertices (at least 3) around `center`: the
+    /// vertices are placed at random, increasing angles, at random distances
+    /// between `radius / 2` and `radius` from the center
+    pub fn star(&mut self, n: usize, center: Point2D, radius: fsize) -> Polygon {
+        Polygon::from_parts(self.star_nodes(n, center, radius, 0.5), false, true)
+    }
+
+    /// Same as `star`, but the distances vary between `radius / 10` and
+    /// `radius`, so the polygon has deep notches and long spikes
+    pub fn jagged(&mut self, n: usize, center: Point2D, radius: fsize) -> Polygon {
+        Polygon::from_parts(self.star_nodes(n, center, radius, 0.1), false, true)
+    }
+
+    /// Polygon with `n` vertices on its exterior (see `star`) and `holes`
+    /// star-shaped holes with `n` vertices each. The holes are placed on a
+    /// circle around the center, inside of the largest circle that fits into
+    /// the exterior.
+    pub fn with_holes(&mut self, n: usize, holes: usize, center: Point2D, radius: fsize) -> MultiPolygon {
+        let exterior = self.star(n, center, radius);
+        let inner_radius = Edges::new(&exterior.nodes, true)
+            .map(|(a, b)| distance_point_segment(&center, &a, &b))
+            .fold(radius, fsize::min);
+
+        // every hole gets a circle of its own
+        let (distance, hole_radius) = match holes {
+            0 => (0.0, 0.0),
+            1 => (0.0, inner_radius * 0.5),
+            _ => {
+                let distance = inner_radius * 0.5;
+                (distance, (distance * (TWO_PI / (2 * holes) as fsize).sin()).min(distance) * 0.8)
+            },
+        };
+
+        let mut polygons = vec![exterior];
+        for i in 0..holes {
+            let angle = TWO_PI * i as fsize / holes as fsize;
+            let hole_center = Point2D { x: center.x + distance * angle.cos(), y: center.y + distance * angle.sin() };
+            let mut nodes = self.star_nodes(n, hole_center, hole_radius, 0.5);
+            nodes.reverse();
+            polygons.push(Polygon::from_parts(nodes, true, true));
+        }
+        MultiPolygon::new(polygons)
+    }
+
+    /// Vertices of a star-shaped polygon, at distances between
+    /// `min_ratio * radius` and `radius` from the center
+    fn star_nodes(&mut self, n: usize, center: Point2D, radius: fsize, min_ratio: fsize) -> Vec<Point2D> {
+        let corners: Vec<(u32, fsize)> = (0..n.max(3))
+            .map(|_| (50 + (self.next_u64() % 50) as u32, radius * (min_ratio + (1.0 - min_ratio) * self.next_unit())))
+            .collect();
+        star_nodes(center, &corners)
+    }
+
+    /// Next number of the SplitMix64 sequence
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in [0, 1)
+    fn next_unit(&mut self) -> fsize {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) as fsize
+    }
+}
+
+/// Vertices of a star-shaped polygon around `center`, one per corner. A corner
+/// is given by the gap to the angle of the next corner (relative to the sum
+/// of all gaps, which makes up a full turn) and its distance to the center.
+///
+/// Every gap has to be less than half of the turn, otherwise the center does
+/// not see the whole polygon, which can then be clockwise or self-intersecting.
+/// Gaps between 50 and 99 ensure this for any number of corners.
+pub(crate) fn star_nodes(center: Point2D, corners: &[(u32, fsize)]) -> Vec<Point2D> {
+    let turn = corners.iter().map(|&(gap, _)| gap).sum::<u32>() as fsize;
+    let mut angle: fsize = 0.0;
+    corners.iter().map(|&(gap, distance)| {
+        let p = Point2D { x: center.x + distance * angle.cos(), y: center.y + distance * angle.sin() };
+        angle += TWO_PI * gap as fsize / turn;
+        p
+    }).collect()
+}
+
+#[test]
+fn test_polygon_generator() {
+    use intersections::find_intersections;
+    use segment::Segment;
+
+    let center = Point2D { x: 10.0, y: -5.0 };
+    let star = PolygonGenerator::new(7).star(50, center, 100.0);
+    assert_eq!(star.nodes, PolygonGenerator::new(7).star(50, center, 100.0).nodes);
+    assert!(star.nodes != PolygonGenerator::new(8).star(50, center, 100.0).nodes);
+
+    let mut generator = PolygonGenerator::new(42);
+    for &n in [3, 10, 200].iter() {
+        for polygon in [generator.star(n, center, 100.0), generator.jagged(n, center, 100.0)].iter() {
+            assert_eq!(polygon.nodes.len(), n);
+            assert!(::utils::calculate_signed_area(&polygon.nodes) > 0.0);
+
+            // the edges only meet at the vertices they share
+            let segments: Vec<Segment> = polygon.edges().map(|(a, b)| Segment::new(a, b)).collect();
+            assert!(find_intersections(&segments).iter().all(|event| event.segments.len() == 2));
+        }
+
+        let holes = generator.with_holes(n, 5, center, 100.0);
+        assert_eq!(holes.polygons.len(), 6);
+        for (i, hole) in holes.polygons[1..].iter().enumerate() {
+            assert!(hole.is_hole && ::utils::calculate_signed_area(&hole.nodes) < 0.0);
+            assert!(holes.polygons[0].contains_polygon(hole));
+            for other in holes.polygons[i + 2..].iter() {
+                let overlap = MultiPolygon::new(vec![hole.clone()]).intersection(&MultiPolygon::new(vec![other.clone()]));
+                assert_eq!(overlap.total_area(), 0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! GeoJSON import and export (requires the `geojson` feature)
+
+use geojson::{Geometry, Value, PolygonType, Position};
+use polygon::{Polygon, WindingOrder};
+use multi_polygon::MultiPolygon;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Reads a GeoJSON `Polygon` geometry
+    ///
+    /// Returns the exterior ring, followed by all holes (with `is_hole` set).
+    /// Returns `None` if the geometry is not a `Polygon`.
+    pub fn from_geojson(geometry: &Geometry) -> Option<Vec<Self>> {
+        match geometry.value {
+            Value::Polygon(ref rings) => Some(polygon_from_rings(rings)),
+            _ => None,
+        }
+    }
+
+    /// Converts the polygon into a GeoJSON geometry
+    ///
+    /// Closed polygons are exported as a `Polygon` with a single ring, open
+    /// polygons as a `LineString`. The ring is exported in the order of the
+    /// nodes, use `MultiPolygon::to_geojson` to export with normalized winding.
+    pub fn to_geojson(&self) -> Geometry {
+        if self.is_closed {
+            Geometry::new(Value::Polygon(vec![ring_to_positions(&self.nodes)]))
+        } else {
+            Geometry::new(Value::LineString(self.nodes.iter().map(point_to_position).collect()))
+        }
+    }
+}
+
+impl MultiPolygon {
+
+    /// Reads a GeoJSON `Polygon` or `MultiPolygon` geometry
+    ///
+    /// Holes are stored as polygons with `is_hole` set, directly after their
+    /// exterior ring. Returns `None` for all other geometry types.
+    pub fn from_geojson(geometry: &Geometry) -> Option<Self> {
+        match geometry.value {
+            Value::Polygon(ref rings) => Some(MultiPolygon::new(polygon_from_rings(rings))),
+            Value::MultiPolygon(ref polygons) => Some(MultiPolygon::new(
+                polygons.iter().flat_map(|rings| polygon_from_rings(rings).into_iter()).collect()
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Converts the set into a GeoJSON `MultiPolygon` geometry
+    ///
+    /// The rings are re-oriented according to RFC 7946 (exteriors counter-clockwise,
+    /// holes clockwise, see `orient`) and every hole is attached to the exterior
+    /// that contains it.
+    pub fn to_geojson(&self) -> Geometry {
+        let mut oriented = MultiPolygon::new(self.polygons.clone());
+        ::orient::orient(&mut oriented.polygons, WindingOrder::CounterClockwise);
+
+        let polygons: Vec<PolygonType> = oriented.exteriors_with_holes().into_iter().map(|(exterior, holes)| {
+            let mut rings = vec![ring_to_positions(&exterior.nodes)];
+            rings.extend(holes.iter().map(|h| ring_to_positions(&h.nodes)));
+            rings
+        }).collect();
+
+        Geometry::new(Value::MultiPolygon(polygons))
+    }
+}
+
+/// Converts the rings of a GeoJSON polygon into an exterior and holes
+fn polygon_from_rings(rings: &PolygonType) -> Vec<Polygon> {
+    rings.iter().enumerate().map(|(idx, ring)| {
+        let mut nodes: Vec<Point2D> = ring.iter().filter(|p| p.len() >= 2).map(|p| Point2D {
+            x: p[0] as fsize,
+            y: p[1] as fsize,
+        }).collect();
+
+        // GeoJSON rings repeat the first position at the end
+        if nodes.len() > 1 && nodes.first() == nodes.last() {
+            nodes.pop();
+        }
+
+        Polygon::from_parts(nodes, idx > 0, true)
+    }).collect()
+}
+
+fn ring_to_positions(nodes: &[Point2D]) -> Vec<Position> {
+    let mut positions: Vec<Position> = nodes.iter().map(point_to_position).collect();
+    if let Some(first) = positions.first().cloned() {
+        positions.push(first);
+    }
+    positions
+}
+
+#[inline]
+fn point_to_position(p: &Point2D) -> Position {
+    vec![p.x as f64, p.y as f64]
+}
+
+#[test]
+fn test_geojson_roundtrip_with_hole() {
+    let json = r#"{"type": "Polygon", "coordinates": [
+        [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+        [[2.0, 2.0], [2.0, 8.0], [8.0, 8.0], [8.0, 2.0], [2.0, 2.0]]
+    ]}"#;
+    let geometry: Geometry = json.parse().unwrap();
+    let multi = MultiPolygon::from_geojson(&geometry).unwrap();
+    assert_eq!(multi.polygons.len(), 2);
+    assert!(multi.polygons[1].is_hole);
+    assert_eq!(multi.polygons[0].nodes.len(), 4);
+
+    match multi.to_geojson().value {
+        Value::MultiPolygon(ref polygons) => {
+            assert_eq!(polygons.len(), 1);
+            assert_eq!(polygons[0].len(), 2);
+        },
+        _ => panic!("expected a MultiPolygon"),
+    }
+}
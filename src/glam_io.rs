@@ -0,0 +1,33 @@
+//! Conversions from and to `glam` (requires the `glam` feature)
+//!
+//! Slices of vectors are converted with `Point2D::from_slice` and `Point2D::to_vec`.
+
+use glam::Vec2;
+use {Point2D, fsize};
+
+impl From<Vec2> for Point2D {
+    fn from(v: Vec2) -> Self {
+        Point2D { x: v.x as fsize, y: v.y as fsize }
+    }
+}
+
+impl From<Point2D> for Vec2 {
+    fn from(p: Point2D) -> Self {
+        Vec2::new(p.x as f32, p.y as f32)
+    }
+}
+
+#[test]
+fn test_glam_conversions() {
+    use polygon::Polygon;
+
+    let v = Vec2::new(1.5, -2.0);
+    let p = Point2D::from(v);
+    assert_eq!(p, Point2D { x: 1.5, y: -2.0 });
+    assert_eq!(Vec2::from(p), v);
+
+    let vertices = [Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(2.0, 2.0), Vec2::new(0.0, 2.0)];
+    let polygon = Polygon::new(Point2D::from_slice(&vertices));
+    assert_eq!(polygon.area(), 4.0);
+    assert_eq!(Point2D::to_vec::<Vec2>(&polygon.nodes), vertices.to_vec());
+}
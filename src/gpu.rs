@@ -0,0 +1,396 @@
+//! Experimental GPU backend for clipping many small polygons (requires the `gpu` feature)
+//!
+//! The GPU filters the subjects by their bounding box and clips them against
+//! a convex clipping polygon with the Sutherland-Hodgman algorithm, one
+//! subject per invocation. Everything else (other operations, concave clip
+//! polygons, subjects that overflow their output buffer) runs on the CPU, with
+//! the same arrangement as `MultiPolygon::clip`.
+
+use std::borrow::Cow;
+use wgpu;
+use wgpu::util::DeviceExt;
+use pollster;
+use polygon::{Polygon, BoolOpType, WindingOrder};
+use multi_polygon::MultiPolygon;
+use options::ClipOptions;
+use error::ClipError;
+use {Point2D, fsize};
+
+const WORKGROUP_SIZE: usize = 64;
+
+/// Invocations report this count if the result did not fit into the buffer
+const OVERFLOW: u32 = 0xffff_ffff;
+
+const SHADER: &str = r#"
+struct Range {
+    offset: u32,
+    len: u32,
+    out_offset: u32,
+    capacity: u32,
+    // output: number of vertices of the result
+    count: u32,
+}
+
+@group(0) @binding(0) var<storage, read> points: array<vec2<f32>>;
+@group(0) @binding(1) var<storage, read> clip: array<vec2<f32>>;
+@group(0) @binding(2) var<storage, read_write> ranges: array<Range>;
+@group(0) @binding(3) var<storage, read_write> scratch: array<vec2<f32>>;
+
+// positive if p is left of a -> b, the clip polygon is counter-clockwise
+fn side(a: vec2<f32>, b: vec2<f32>, p: vec2<f32>) -> f32 {
+    return (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= arrayLength(&ranges)) {
+        return;
+    }
+    let r = ranges[i];
+    let m = arrayLength(&clip);
+
+    // bounding box test
+    var lo = points[r.offset];
+    var hi = lo;
+    for (var k = 1u; k < r.len; k = k + 1u) {
+        lo = min(lo, points[r.offset + k]);
+        hi = max(hi, points[r.offset + k]);
+    }
+    var clip_lo = clip[0];
+    var clip_hi = clip_lo;
+    for (var k = 1u; k < m; k = k + 1u) {
+        clip_lo = min(clip_lo, clip[k]);
+        clip_hi = max(clip_hi, clip[k]);
+    }
+    if (any(hi < clip_lo) || any(lo > clip_hi)) {
+        ranges[i].count = 0u;
+        return;
+    }
+
+    // Sutherland-Hodgman, ping-ponging between two halves of the scratch space
+    let first = r.out_offset;
+    for (var k = 0u; k < r.len; k = k + 1u) {
+        scratch[first + k] = points[r.offset + k];
+    }
+    var src = first;
+    var dst = first + r.capacity;
+    var n = r.len;
+
+    for (var e = 0u; e < m && n > 0u; e = e + 1u) {
+        let c0 = clip[e];
+        let c1 = clip[(e + 1u) % m];
+        var out = 0u;
+        for (var k = 0u; k < n; k = k + 1u) {
+            let p = scratch[src + k];
+            let q = scratch[src + (k + 1u) % n];
+            let dp = side(c0, c1, p);
+            let dq = side(c0, c1, q);
+            if (out + 2u > r.capacity) {
+                ranges[i].count = 0xffffffffu;
+                return;
+            }
+            if (dp >= 0.0) {
+                scratch[dst + out] = p;
+                out = out + 1u;
+            }
+            if ((dp >= 0.0) != (dq >= 0.0) && dp != dq) {
+                scratch[dst + out] = mix(p, q, dp / (dp - dq));
+                out = out + 1u;
+            }
+        }
+        n = out;
+        let tmp = src;
+        src = dst;
+        dst = tmp;
+    }
+
+    if (src != first) {
+        for (var k = 0u; k < n; k = k + 1u) {
+            scratch[first + k] = scratch[src + k];
+        }
+    }
+    ranges[i].count = n;
+}
+"#;
+
+/// Clips batches of polygons on the GPU, see `clip_many`
+pub struct GpuClipper {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuClipper {
+
+    /// Connects to the default GPU adapter, returns None if there is none
+    pub fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("polyclip"),
+            required_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::downlevel_defaults(),
+        }, None)).ok()?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("polyclip sutherland-hodgman"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("polyclip sutherland-hodgman"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+
+        Some(Self {
+            device: device,
+            queue: queue,
+            pipeline: pipeline,
+        })
+    }
+
+    /// Same as `polyclip::clip_many`, but intersections with a convex clipping
+    /// polygon are computed on the GPU
+    ///
+    /// The GPU computes in single precision. Concave subjects are clipped on
+    /// the GPU as well, the zero-width connections that Sutherland-Hodgman
+    /// leaves along the clip boundary are then removed on the CPU.
+    pub fn clip_many(&self, subjects: &[Polygon], clip: &Polygon, operation_type: BoolOpType)
+                     -> Vec<Result<Vec<Polygon>, ClipError>>
+    {
+        let clip_nodes = ::clean::clean_nodes(&clip.nodes, true, 0.0);
+        if operation_type != BoolOpType::Intersection || clip_nodes.len() < 3 || !::utils::is_convex(&clip_nodes) ||
+           ::utils::check_finite(clip_nodes.iter()).is_err() {
+            return subjects.iter().map(|subject| clip_on_cpu(subject, clip, operation_type)).collect();
+        }
+
+        let mut clip_nodes = clip_nodes;
+        if ::utils::calculate_winding_order(&clip_nodes) == WindingOrder::Clockwise {
+            clip_nodes.reverse();
+        }
+
+        // subjects with non-finite coordinates fail like on the CPU
+        let mut results: Vec<Result<Vec<Polygon>, ClipError>> = subjects.iter()
+            .map(|subject| ::utils::check_finite(subject.nodes.iter()).map(|_| Vec::new()))
+            .collect();
+        let gpu_subjects: Vec<usize> = (0..subjects.len()).filter(|i| subjects[*i].nodes.len() > 2 && results[*i].is_ok()).collect();
+
+        // split the batch, so that every buffer fits into the limits of the device
+        let limits = self.device.limits();
+        let max_bytes = limits.max_storage_buffer_binding_size as usize;
+        let max_subjects = limits.max_compute_workgroups_per_dimension as usize * WORKGROUP_SIZE;
+        let mut chunk = Vec::new();
+        let mut chunk_bytes = 0;
+
+        for idx in gpu_subjects {
+            let bytes = scratch_capacity(subjects[idx].nodes.len(), clip_nodes.len()) * 2 * 8;
+            if !chunk.is_empty() && (chunk_bytes + bytes > max_bytes || chunk.len() == max_subjects) {
+                self.clip_chunk(subjects, &chunk, &clip_nodes, &mut results);
+                chunk.clear();
+                chunk_bytes = 0;
+            }
+            chunk.push(idx);
+            chunk_bytes += bytes;
+        }
+
+        if !chunk.is_empty() {
+            self.clip_chunk(subjects, &chunk, &clip_nodes, &mut results);
+        }
+
+        results
+    }
+
+    fn clip_chunk(&self, subjects: &[Polygon], chunk: &[usize], clip_nodes: &[Point2D],
+                  results: &mut [Result<Vec<Polygon>, ClipError>]) {
+
+        let mut points = Vec::new();
+        let mut ranges = Vec::new();
+        let mut scratch_len = 0;
+
+        for &idx in chunk {
+            let nodes = &subjects[idx].nodes;
+            let capacity = scratch_capacity(nodes.len(), clip_nodes.len());
+            ranges.extend_from_slice(&[points.len() as u32, nodes.len() as u32, scratch_len as u32, capacity as u32, 0]);
+            points.extend(nodes.iter().cloned());
+            scratch_len += 2 * capacity;
+        }
+
+        let storage = |label, contents: &[u8], usage| self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: contents,
+            usage: wgpu::BufferUsages::STORAGE | usage,
+        });
+        let points_buffer = storage("points", &points_to_bytes(&points), wgpu::BufferUsages::empty());
+        let clip_buffer = storage("clip", &points_to_bytes(clip_nodes), wgpu::BufferUsages::empty());
+        let ranges_buffer = storage("ranges", &u32_to_bytes(&ranges), wgpu::BufferUsages::COPY_SRC);
+
+        let scratch_size = (scratch_len * 8) as wgpu::BufferAddress;
+        let ranges_size = (ranges.len() * 4) as wgpu::BufferAddress;
+        let output = |label, size| self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = |label, size| self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let scratch_buffer = output("scratch", scratch_size);
+        let scratch_readback = readback("scratch readback", scratch_size);
+        let ranges_readback = readback("ranges readback", ranges_size);
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: points_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: clip_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: ranges_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: scratch_buffer.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(((chunk.len() + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&scratch_buffer, 0, &scratch_readback, 0, scratch_size);
+        encoder.copy_buffer_to_buffer(&ranges_buffer, 0, &ranges_readback, 0, ranges_size);
+        self.queue.submit(Some(encoder.finish()));
+
+        scratch_readback.slice(..).map_async(wgpu::MapMode::Read, |_| ());
+        ranges_readback.slice(..).map_async(wgpu::MapMode::Read, |_| ());
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let scratch = bytes_to_f32(&scratch_readback.slice(..).get_mapped_range());
+        let ranges = bytes_to_u32(&ranges_readback.slice(..).get_mapped_range());
+        read_results(subjects, chunk, clip_nodes, &ranges, &scratch, results);
+    }
+}
+
+/// Turns the output of the shader for `chunk` into polygons, subjects that
+/// overflowed their part of the scratch space are clipped on the CPU
+fn read_results(subjects: &[Polygon], chunk: &[usize], clip_nodes: &[Point2D],
+                ranges: &[u32], scratch: &[f32], results: &mut [Result<Vec<Polygon>, ClipError>]) {
+
+    for (n, &idx) in chunk.iter().enumerate() {
+        let subject = &subjects[idx];
+        let count = ranges[5 * n + 4];
+        if count == OVERFLOW {
+            let clip = Polygon::new(clip_nodes.to_vec());
+            results[idx] = clip_on_cpu(subject, &clip, BoolOpType::Intersection);
+            continue;
+        }
+
+        let offset = ranges[5 * n + 2] as usize;
+        let ring: Vec<Point2D> = (offset..offset + count as usize)
+            .map(|k| Point2D { x: scratch[2 * k] as fsize, y: scratch[2 * k + 1] as fsize })
+            .collect();
+        let mut ring = ::clean::clean_nodes(&ring, true, 0.0);
+        if ring.len() < 3 {
+            continue;
+        }
+
+        results[idx] = Ok(if ::utils::is_convex(&subject.nodes) {
+            if ::utils::calculate_winding_order(&ring) != ::utils::calculate_winding_order(&subject.nodes) {
+                ring.reverse();
+            }
+            vec![Polygon { is_hole: subject.is_hole, .. Polygon::new(ring) }]
+        } else {
+            ::grid::resolve_rings(vec![ring])
+        });
+    }
+}
+
+/// Clips one subject on the CPU
+fn clip_on_cpu(subject: &Polygon, clip: &Polygon, operation_type: BoolOpType) -> Result<Vec<Polygon>, ClipError> {
+    MultiPolygon::new(vec![subject.clone()])
+        .try_clip(&MultiPolygon::new(vec![clip.clone()]), operation_type, &ClipOptions::default())
+        .map(|result| result.polygons)
+}
+
+/// Room for the vertices of one subject in one half of the scratch space. Every
+/// clip edge can add vertices to a concave subject, subjects that need more
+/// room are clipped on the CPU.
+fn scratch_capacity(subject_len: usize, clip_len: usize) -> usize {
+    2 * (subject_len + clip_len) + 2
+}
+
+fn points_to_bytes(points: &[Point2D]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(points.len() * 8);
+    for p in points {
+        bytes.extend_from_slice(&(p.x as f32).to_le_bytes());
+        bytes.extend_from_slice(&(p.y as f32).to_le_bytes());
+    }
+    bytes
+}
+
+fn u32_to_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes().to_vec()).collect()
+}
+
+fn bytes_to_f32(bytes: &[u8]) -> Vec<f32> {
+    bytes.chunks(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect()
+}
+
+fn bytes_to_u32(bytes: &[u8]) -> Vec<u32> {
+    bytes.chunks(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect()
+}
+
+#[test]
+fn test_gpu_clip_many() {
+    // there is no GPU on most CI machines
+    let clipper = match GpuClipper::new() {
+        Some(clipper) => clipper,
+        None => return,
+    };
+
+    let viewport = Polygon::rect(0.0, 0.0, 10.0, 10.0);
+    let subjects = vec![
+        Polygon::rect(-5.0, -5.0, 10.0, 10.0),
+        Polygon::rect(20.0, 20.0, 1.0, 1.0),
+        Polygon::rect(2.0, 2.0, 1.0, 1.0),
+        // concave, the two arms are connected outside of the viewport
+        Polygon::from(vec![(2.0, 5.0), (4.0, 5.0), (4.0, 12.0), (6.0, 12.0),
+                           (6.0, 5.0), (8.0, 5.0), (8.0, 14.0), (2.0, 14.0)]),
+    ];
+
+    let result: Vec<Vec<Polygon>> = clipper.clip_many(&subjects, &viewport, BoolOpType::Intersection)
+        .into_iter().map(Result::unwrap).collect();
+    assert_eq!(result.len(), 4);
+    assert_eq!(result[0][0].area(), 25.0);
+    assert!(result[1].is_empty());
+    assert_eq!(result[2][0].area(), 1.0);
+    assert_eq!(result[3].len(), 2);
+    assert!(result[3].iter().all(|p| p.area() == 10.0));
+}
+
+#[test]
+fn test_gpu_overflow() {
+    // the shader reports an overflow for the second subject, which is then
+    // clipped on the CPU
+    let clip_nodes = Polygon::rect(0.0, 0.0, 10.0, 10.0).nodes;
+    let subjects = vec![
+        Polygon::rect(2.0, 2.0, 1.0, 1.0),
+        Polygon::from(vec![(-5.0, 2.0), (5.0, 2.0), (5.0, 4.0), (-3.0, 4.0), (-3.0, 6.0),
+                           (5.0, 6.0), (5.0, 8.0), (-5.0, 8.0)]),
+    ];
+    let scratch = vec![2.0, 2.0, 3.0, 2.0, 3.0, 3.0, 2.0, 3.0];
+    let ranges = vec![0, 4, 0, 4, 4, 4, 8, 8, 8, OVERFLOW];
+
+    let mut results = vec![Ok(Vec::new()), Ok(Vec::new())];
+    read_results(&subjects, &[0, 1], &clip_nodes, &ranges, &scratch, &mut results);
+    let results: Vec<Vec<Polygon>> = results.into_iter().map(Result::unwrap).collect();
+    assert_eq!(results[0].len(), 1);
+    assert_eq!(results[0][0].area(), 1.0);
+    assert_eq!(results[1].len(), 2);
+    assert!(results[1].iter().all(|p| p.area() == 10.0));
+}
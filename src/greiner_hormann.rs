@@ -0,0 +1,476 @@
+//! Greiner–Hormann clipping, with the extensions of Foster, Hormann and Popa
+//! for degenerate intersections
+//!
+//! This is an alternative to the arrangement, see `ClipOptions::algorithm`.
+//! The two share no code, so the tests compare their results: the
+//! differential tests of this module run against the arrangement (the crate
+//! has no Martinez sweep to test against).
+//!
+//! The boundaries of both polygons are intersected pairwise, every
+//! intersection is labelled as an entry into or an exit out of the other
+//! polygon and the result is traced by switching between the two boundaries
+//! at every crossing.
+//!
+//! Vertices that lie on the other boundary and edges that overlap it are not
+//! perturbed. Instead, every shared point is classified as a crossing or a
+//! bounce by looking at its neighbours, and chains of overlapping edges are
+//! labelled as a whole ("Clipping simple polygons with degenerate
+//! intersections", Foster, Hormann and Popa, 2019). Bounces are treated as
+//! regular vertices. Both inputs have to be simple rings.
+
+use error::ClipError;
+use options::OperationGuard;
+use polygon::{Polygon, BoolOpType, WindingOrder};
+use utils::{calculate_sign, is_on_segment, locate_point_in_ring, PointLocation, Sign};
+use {Point2D, fsize};
+
+/// Position of an intersection on one of the input rings
+#[derive(Debug, Copy, Clone)]
+enum Location {
+    /// At an existing vertex
+    Vertex(usize),
+    /// Inside of the edge that starts at the vertex, at the parameter `alpha`
+    Edge(usize, fsize),
+}
+
+/// Vertex of the boundary of one input, including the intersections
+#[derive(Debug, Copy, Clone)]
+struct Vertex {
+    p: Point2D,
+    /// Index of the same point in the list of the other input
+    neighbour: Option<usize>,
+    /// Does the other boundary cross the boundary here?
+    crossing: bool,
+    /// Does the boundary enter the result region here?
+    entry: bool,
+    visited: bool,
+}
+
+/// Position of a neighbour of an intersection, relative to the other boundary
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+    /// The edge to the neighbour overlaps the other boundary
+    On,
+}
+
+/// Computes the boolean operation of two simple polygons
+pub(crate) fn clip(subject: &Polygon, clip: &Polygon, operation_type: BoolOpType, guard: &mut OperationGuard)
+                   -> Result<Vec<Polygon>, ClipError>
+{
+    let p = ::clean::clean_nodes(&subject.nodes, true, 0.0);
+    let q = ::clean::clean_nodes(&clip.nodes, true, 0.0);
+
+    let mut polygons: Vec<Polygon> = clip_rings(&p, &q, operation_type, guard)?.into_iter()
+        .map(|ring| ::clean::clean_nodes(&ring, true, 0.0))
+        .filter(|ring| ring.len() > 2)
+        .map(|ring| Polygon::from_parts(ring, false, true))
+        .collect();
+    ::orient::orient(&mut polygons, WindingOrder::CounterClockwise);
+    ::multi_polygon::sort_canonical(&mut polygons);
+    Ok(polygons)
+}
+
+/// Result rings of the boolean operation of the rings `p` and `q`
+fn clip_rings(p: &[Point2D], q: &[Point2D], operation_type: BoolOpType, guard: &mut OperationGuard)
+              -> Result<Vec<Vec<Point2D>>, ClipError>
+{
+    if p.len() < 3 || q.len() < 3 {
+        return Ok(match operation_type {
+            BoolOpType::Intersection => Vec::new(),
+            BoolOpType::Difference => vec![p.to_vec()],
+            BoolOpType::Union | BoolOpType::Xor => vec![p.to_vec(), q.to_vec()],
+        });
+    }
+
+    // phase 1: find the intersections and insert them into both boundaries
+    let mut hits = Vec::new();
+    for i in 0..p.len() {
+        guard.tick()?;
+        for j in 0..q.len() {
+            intersect_edges(p, i, q, j, &mut hits);
+        }
+    }
+
+    let mut points: Vec<Point2D> = hits.iter().map(|h| h.0).collect();
+    points.sort();
+    points.dedup();
+    let is_edge = |location: &Location| match *location { Location::Edge(..) => true, Location::Vertex(_) => false };
+    guard.record(points.len(), hits.iter().map(|h| is_edge(&h.1) as usize + is_edge(&h.2) as usize).sum());
+
+    let (mut p_list, p_index) = build_list(p, hits.iter().map(|h| (h.0, h.1)));
+    let (mut q_list, q_index) = build_list(q, hits.iter().map(|h| (h.0, h.2)));
+    for (&ip, &iq) in p_index.iter().zip(q_index.iter()) {
+        if p_list[ip].neighbour.is_none() && q_list[iq].neighbour.is_none() {
+            p_list[ip].neighbour = Some(iq);
+            q_list[iq].neighbour = Some(ip);
+        }
+    }
+
+    // phase 2: crossings and bounces, then entries and exits
+    label_crossings(&mut p_list, &mut q_list);
+    let p_inside = mark_entries(&mut p_list, &q_list, q);
+    let q_inside = mark_entries(&mut q_list, &p_list, p);
+
+    if !p_list.iter().any(|v| v.crossing) {
+        return Ok(without_crossings(p, q, p_inside, q_inside, operation_type));
+    }
+
+    // phase 3: the union traces both boundaries outside of the other polygon,
+    // the difference traces `p` outside of `q` and `q` inside of `p`. Both
+    // differences of the exclusive-or are traced on the same intersections,
+    // so that their rings share the same vertices.
+    let lists = [p_list, q_list];
+    match operation_type {
+        BoolOpType::Intersection => trace(lists, [false, false], guard),
+        BoolOpType::Union => trace(lists, [true, true], guard),
+        BoolOpType::Difference => trace(lists, [true, false], guard),
+        BoolOpType::Xor => {
+            let mut rings = trace(lists.clone(), [true, false], guard)?;
+            rings.extend(trace(lists, [false, true], guard)?);
+            Ok(rings)
+        },
+    }
+}
+
+/// Traces the result rings, switching the boundary at every crossing. The
+/// entries and exits of the lists are inverted where `flip` is set.
+fn trace(mut lists: [Vec<Vertex>; 2], flip: [bool; 2], guard: &mut OperationGuard) -> Result<Vec<Vec<Point2D>>, ClipError> {
+
+    for side in 0..2 {
+        for v in lists[side].iter_mut().filter(|v| flip[side] && v.crossing) {
+            v.entry = !v.entry;
+        }
+    }
+
+    let max_steps = lists[0].len() + lists[1].len();
+    let mut rings = Vec::new();
+
+    while let Some(start) = lists[0].iter().position(|v| v.crossing && !v.visited) {
+        let mut ring = vec![lists[0][start].p];
+        let (mut side, mut idx) = (0, start);
+
+        for _ in 0..max_steps {
+            guard.tick()?;
+            lists[side][idx].visited = true;
+            let neighbour = lists[side][idx].neighbour.unwrap();
+            lists[1 - side][neighbour].visited = true;
+
+            let len = lists[side].len();
+            let forward = lists[side][idx].entry;
+            loop {
+                idx = if forward { (idx + 1) % len } else { (idx + len - 1) % len };
+                ring.push(lists[side][idx].p);
+                if lists[side][idx].crossing {
+                    break;
+                }
+            }
+
+            idx = lists[side][idx].neighbour.unwrap();
+            side = 1 - side;
+            if lists[side][idx].visited {
+                break;
+            }
+        }
+
+        rings.push(ring);
+    }
+
+    Ok(rings)
+}
+
+/// Finds the intersections of the edge `i` of `p` with the edge `j` of `q`
+///
+/// Only the start points of the edges are reported if they lie on the other
+/// edge, the end points are the start points of the following edges. This
+/// way, every shared point is found exactly once.
+fn intersect_edges(p: &[Point2D], i: usize, q: &[Point2D], j: usize, hits: &mut Vec<(Point2D, Location, Location)>) {
+
+    let (p1, p2) = (p[i], p[(i + 1) % p.len()]);
+    let (q1, q2) = (q[j], q[(j + 1) % q.len()]);
+
+    if p1.x.max(p2.x) < q1.x.min(q2.x) || q1.x.max(q2.x) < p1.x.min(p2.x) ||
+       p1.y.max(p2.y) < q1.y.min(q2.y) || q1.y.max(q2.y) < p1.y.min(p2.y) {
+        return;
+    }
+
+    let sp1 = calculate_sign(&q1, &q2, &p1);
+    let sp2 = calculate_sign(&q1, &q2, &p2);
+    let sq1 = calculate_sign(&p1, &p2, &q1);
+    let sq2 = calculate_sign(&p1, &p2, &q2);
+
+    if sp1 == Sign::Equal && sp2 == Sign::Equal {
+        // collinear edges
+        if p1 == q1 {
+            hits.push((p1, Location::Vertex(i), Location::Vertex(j)));
+            return;
+        }
+        if p1 != q2 && is_on_segment(&q1, &q2, &p1) {
+            hits.push((p1, Location::Vertex(i), Location::Edge(j, parameter(&q1, &q2, &p1))));
+        }
+        if q1 != p2 && is_on_segment(&p1, &p2, &q1) {
+            hits.push((q1, Location::Edge(i, parameter(&p1, &p2, &q1)), Location::Vertex(j)));
+        }
+        return;
+    }
+
+    if (sp1 == sp2 && sp1 != Sign::Equal) || (sq1 == sq2 && sq1 != Sign::Equal) {
+        return;
+    }
+
+    // the edges meet in exactly one point
+    if sp2 == Sign::Equal || sq2 == Sign::Equal {
+        // at an end point, which is found with the following edge
+    } else if sp1 == Sign::Equal {
+        if p1 == q1 {
+            hits.push((p1, Location::Vertex(i), Location::Vertex(j)));
+        } else {
+            hits.push((p1, Location::Vertex(i), Location::Edge(j, parameter(&q1, &q2, &p1))));
+        }
+    } else if sq1 == Sign::Equal {
+        hits.push((q1, Location::Edge(i, parameter(&p1, &p2, &q1)), Location::Vertex(j)));
+    } else {
+        let dp = p2 - p1;
+        let dq = q2 - q1;
+        let denom = dp.cross(&dq);
+        let alpha = ((q1 - p1).cross(&dq) / denom).max(0.0).min(1.0);
+        let beta = ((q1 - p1).cross(&dp) / denom).max(0.0).min(1.0);
+        hits.push((p1.lerp(&p2, alpha), Location::Edge(i, alpha), Location::Edge(j, beta)));
+    }
+}
+
+/// Parameter of the point `p` on the segment (a, b)
+#[inline]
+fn parameter(a: &Point2D, b: &Point2D, p: &Point2D) -> fsize {
+    let ab = *b - *a;
+    (*p - *a).dot(&ab) / ab.dot(&ab)
+}
+
+/// Builds the vertex list of a ring with the intersections inserted. Returns
+/// the list and the index of every intersection in it.
+fn build_list<I>(nodes: &[Point2D], hits: I) -> (Vec<Vertex>, Vec<usize>) where I: Iterator<Item = (Point2D, Location)> {
+
+    let vertex = |p: Point2D| Vertex { p: p, neighbour: None, crossing: false, entry: false, visited: false };
+
+    let mut inserted = vec![Vec::new(); nodes.len()];
+    let hits: Vec<(Point2D, Location)> = hits.collect();
+    for (hit, &(_, location)) in hits.iter().enumerate() {
+        if let Location::Edge(edge, alpha) = location {
+            inserted[edge].push((alpha, hit));
+        }
+    }
+
+    let mut list = Vec::with_capacity(nodes.len() + hits.len());
+    let mut vertex_index = Vec::with_capacity(nodes.len());
+    let mut hit_index = vec![0; hits.len()];
+
+    for (edge, points) in inserted.iter_mut().enumerate() {
+        vertex_index.push(list.len());
+        list.push(vertex(nodes[edge]));
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal));
+        for &(_, hit) in points.iter() {
+            hit_index[hit] = list.len();
+            list.push(vertex(hits[hit].0));
+        }
+    }
+
+    for (hit, &(_, location)) in hits.iter().enumerate() {
+        if let Location::Vertex(v) = location {
+            hit_index[hit] = vertex_index[v];
+        }
+    }
+
+    (list, hit_index)
+}
+
+/// Decides for every intersection whether the boundaries cross or only touch
+///
+/// The neighbours of an intersection on `p` are classified as left or right
+/// of the two edges of `q` at that point. Chains of overlapping edges are
+/// classified as a whole: they are a (delayed) crossing if the neighbours
+/// before and after the chain lie on different sides. The first point of
+/// such a chain is marked as the crossing. The result is copied to `q`.
+fn label_crossings(p: &mut [Vertex], q: &mut [Vertex]) {
+
+    let n = p.len();
+    let m = q.len();
+
+    let sides: Vec<Option<(Side, Side)>> = (0..n).map(|i| {
+        let qi = p[i].neighbour?;
+        let q_prev = q[(qi + m - 1) % m].p;
+        let q_next = q[(qi + 1) % m].p;
+        let classify = |r: Point2D| if r == q_prev || r == q_next {
+            Side::On
+        } else {
+            side_of_chain(&r, &q_prev, &p[i].p, &q_next)
+        };
+        Some((classify(p[(i + n - 1) % n].p), classify(p[(i + 1) % n].p)))
+    }).collect();
+
+    for i in 0..n {
+        p[i].crossing = match sides[i] {
+            Some((Side::Left, Side::Right)) | Some((Side::Right, Side::Left)) => true,
+            Some((before, Side::On)) if before != Side::On => {
+                // start of an overlapping chain, find its end
+                let end = (1..n).map(|k| (i + k) % n)
+                    .take_while(|k| sides[*k].is_some())
+                    .filter_map(|k| sides[k])
+                    .find(|&(_, after)| after != Side::On);
+                match end {
+                    Some((_, after)) => after != before,
+                    None => false,
+                }
+            },
+            _ => false,
+        };
+    }
+
+    for v in p.iter() {
+        if let Some(qi) = v.neighbour {
+            q[qi].crossing = v.crossing;
+        }
+    }
+}
+
+/// Is `r` to the left or the right of the chain (a, b, c)?
+fn side_of_chain(r: &Point2D, a: &Point2D, b: &Point2D, c: &Point2D) -> Side {
+    let s1 = calculate_sign(a, b, r) == Sign::Positive;
+    let s2 = calculate_sign(b, c, r) == Sign::Positive;
+    let is_left = if calculate_sign(a, b, c) == Sign::Negative { s1 || s2 } else { s1 && s2 };
+    if is_left { Side::Left } else { Side::Right }
+}
+
+/// Marks the crossings of `list` as entries into or exits out of the ring
+/// `other`, whose vertex list is `other_list`. Returns whether the boundary
+/// of `list` lies inside of `other` before its first crossing, or None if it
+/// lies completely on the boundary of `other`.
+fn mark_entries(list: &mut [Vertex], other_list: &[Vertex], other: &[Point2D]) -> Option<bool> {
+
+    let len = list.len();
+    let m = other_list.len();
+
+    // the location is determined at a vertex that is not shared with the other
+    // boundary or, if there is none, at the middle of an edge that does not
+    // overlap it
+    let overlaps = |k: usize| match (list[k].neighbour, list[(k + 1) % len].neighbour) {
+        (Some(a), Some(b)) => (a + 1) % m == b || (b + 1) % m == a,
+        _ => false,
+    };
+    let vertices = (0..len).filter(|k| list[*k].neighbour.is_none()).map(|k| (k, list[k].p));
+    let midpoints = (0..len).filter(|k| !overlaps(*k)).map(|k| (k, list[k].p.lerp(&list[(k + 1) % len].p, 0.5)));
+
+    let (start, inside) = vertices.chain(midpoints).filter_map(|(k, point)| {
+        match locate_point_in_ring(&point, other) {
+            PointLocation::OnBoundary => None,
+            location => Some((k, location == PointLocation::Inside)),
+        }
+    }).next()?;
+
+    let mut is_inside = inside;
+    for step in 1..(len + 1) {
+        let v = &mut list[(start + step) % len];
+        if v.crossing {
+            v.entry = !is_inside;
+            is_inside = !is_inside;
+        }
+    }
+
+    Some(inside)
+}
+
+/// Result of an operation where the boundaries do not cross, so that the
+/// polygons are either nested, disjoint or equal
+fn without_crossings(p: &[Point2D], q: &[Point2D], p_inside: Option<bool>, q_inside: Option<bool>,
+                     operation_type: BoolOpType) -> Vec<Vec<Point2D>>
+{
+    use self::BoolOpType::*;
+
+    let (p, q) = (p.to_vec(), q.to_vec());
+    match (p_inside, q_inside, operation_type) {
+        // the boundaries coincide
+        (None, _, Difference) | (_, None, Difference) | (None, _, Xor) | (_, None, Xor) => Vec::new(),
+        (None, _, _) | (_, None, _) => vec![p],
+        (Some(true), _, Intersection) => vec![p],
+        (Some(true), _, Difference) => Vec::new(),
+        (Some(true), _, Xor) => vec![q, p],
+        (Some(true), _, _) => vec![q],
+        (_, Some(true), Intersection) => vec![q],
+        (_, Some(true), Difference) | (_, Some(true), Xor) => vec![p, q],
+        (_, Some(true), _) => vec![p],
+        (_, _, Intersection) => Vec::new(),
+        (_, _, Difference) => vec![p],
+        (_, _, _) => vec![p, q],
+    }
+}
+
+#[test]
+fn test_greiner_hormann() {
+    use options::ClipOptions;
+
+    let gh = |a: &Polygon, b: &Polygon, op: BoolOpType| -> (usize, fsize) {
+        let result = clip(a, b, op, &mut ClipOptions::default().guard()).unwrap();
+        (result.len(), result.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum())
+    };
+
+    // regular crossings
+    let a = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    let b = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+    assert_eq!(gh(&a, &b, BoolOpType::Intersection), (1, 1.0));
+    assert_eq!(gh(&a, &b, BoolOpType::Union), (1, 7.0));
+    assert_eq!(gh(&a, &b, BoolOpType::Difference), (1, 3.0));
+    assert_eq!(gh(&a, &b, BoolOpType::Xor), (2, 6.0));
+
+    // overlapping edges (delayed crossing) and a vertex on an edge
+    let c = Polygon::rect(1.0, 0.0, 2.0, 1.0);
+    assert_eq!(gh(&a, &c, BoolOpType::Intersection), (1, 1.0));
+    assert_eq!(gh(&a, &c, BoolOpType::Union), (1, 5.0));
+    assert_eq!(gh(&a, &c, BoolOpType::Difference), (1, 3.0));
+
+    // touching in a single vertex (bounce)
+    let d = Polygon::rect(2.0, 2.0, 1.0, 1.0);
+    assert_eq!(gh(&a, &d, BoolOpType::Intersection), (0, 0.0));
+    assert_eq!(gh(&a, &d, BoolOpType::Union), (2, 5.0));
+
+    // equal and nested polygons
+    assert_eq!(gh(&a, &a, BoolOpType::Intersection), (1, 4.0));
+    assert_eq!(gh(&a, &a, BoolOpType::Difference), (0, 0.0));
+    let inner = Polygon::rect(0.5, 0.5, 1.0, 1.0);
+    assert_eq!(gh(&a, &inner, BoolOpType::Difference), (2, 3.0));
+    assert_eq!(gh(&inner, &a, BoolOpType::Union), (1, 4.0));
+}
+
+#[test]
+fn test_greiner_hormann_matches_arrangement() {
+    // differential test against the arrangement, the default algorithm
+    use multi_polygon::MultiPolygon;
+    use options::ClipOptions;
+    use std::f64::consts::PI;
+
+    // a star and a rotated square share some vertices and edges
+    let star = Polygon::from_parts((0..10).map(|i| {
+        let radius = if i % 2 == 0 { 4.0 } else { 2.0 };
+        let angle = i as fsize * PI as fsize / 5.0;
+        Point2D { x: radius * angle.cos(), y: radius * angle.sin() }
+    }).collect(), false, true);
+    let shapes = [
+        star,
+        Polygon::rect(-1.0, -3.0, 5.0, 3.0),
+        Polygon::from(vec![(4.0, 0.0), (0.0, 4.0), (-4.0, 0.0), (0.0, -4.0)]),
+        Polygon::from(vec![(0.0, 0.0), (4.0, 0.0), (2.0, 1.0), (4.0, 2.0), (0.0, 2.0)]),
+    ];
+
+    for a in shapes.iter() {
+        for b in shapes.iter() {
+            for &op in [BoolOpType::Intersection, BoolOpType::Union, BoolOpType::Difference, BoolOpType::Xor].iter() {
+                let signed_area = |polygons: &[Polygon]| -> fsize {
+                    polygons.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum()
+                };
+                let expected = MultiPolygon::new(vec![a.clone()]).clip(&MultiPolygon::new(vec![b.clone()]), op, &ClipOptions::default());
+                let result = clip(a, b, op, &mut ClipOptions::default().guard()).unwrap();
+                assert!((signed_area(&result) - signed_area(&expected.polygons)).abs() < 0.001,
+                        "{:?}: {} != {}", op, signed_area(&result), signed_area(&expected.polygons));
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Splitting polygons along a regular grid
+
+use arrangement::Arrangement;
+use multi_polygon::{MultiPolygon, sort_canonical};
+use polygon::Polygon;
+use {Point2D, Bbox, fsize};
+
+/// Index of a grid cell: cell `(column, row)` covers the area
+/// `[column * cell_width, (column + 1) * cell_width] x [row * cell_height, (row + 1) * cell_height]`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CellIndex {
+    pub column: i64,
+    pub row: i64,
+}
+
+impl Polygon {
+
+    /// Clips the polygon against every grid cell it overlaps
+    ///
+    /// The grid starts at the origin. The polygon is first cut into columns
+    /// and every column is cut into rows, so every vertex is only processed
+    /// by the cells of its own column. Cells that end up empty are omitted,
+    /// the cells are returned column by column.
+    pub fn split_to_grid(&self, cell_width: fsize, cell_height: fsize) -> Vec<(CellIndex, Vec<Polygon>)> {
+        split_rings_to_grid(&[&self.nodes[..]], cell_width, cell_height)
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Clips all polygons against every grid cell they overlap, holes are cut
+    /// out of the pieces (even-odd rule), see `Polygon::split_to_grid`
+    pub fn split_to_grid(&self, cell_width: fsize, cell_height: fsize) -> Vec<(CellIndex, Vec<Polygon>)> {
+        let rings: Vec<&[Point2D]> = self.polygons.iter().map(|p| &p.nodes[..]).collect();
+        split_rings_to_grid(&rings, cell_width, cell_height)
+    }
+}
+
+fn split_rings_to_grid(rings: &[&[Point2D]], cell_width: fsize, cell_height: fsize)
+                       -> Vec<(CellIndex, Vec<Polygon>)>
+{
+    if !(cell_width > 0.0) || !(cell_height > 0.0) {
+        return Vec::new();
+    }
+
+    let bbox = match bbox_of_rings(rings) {
+        Some(b) => b,
+        None => return Vec::new(),
+    };
+
+    let mut result = Vec::new();
+
+    for column in cell_range(bbox.min().x, bbox.max().x, cell_width) {
+        let x0 = column as fsize * cell_width;
+        let strip: Vec<Vec<Point2D>> = rings.iter()
+            .map(|r| clip_axis(r, true, x0, x0 + cell_width))
+            .filter(|r| r.len() > 2)
+            .collect();
+
+        let strip_refs: Vec<&[Point2D]> = strip.iter().map(|r| &r[..]).collect();
+        let strip_bbox = match bbox_of_rings(&strip_refs) {
+            Some(b) => b,
+            None => continue,
+        };
+
+        for row in cell_range(strip_bbox.min().y, strip_bbox.max().y, cell_height) {
+            let y0 = row as fsize * cell_height;
+            let cell: Vec<Vec<Point2D>> = strip.iter()
+                .map(|r| clip_axis(r, false, y0, y0 + cell_height))
+                .filter(|r| r.len() > 2)
+                .collect();
+
+            let polygons = resolve_rings(cell);
+            if !polygons.is_empty() {
+                result.push((CellIndex { column: column, row: row }, polygons));
+            }
+        }
+    }
+
+    result
+}
+
+/// Clips the rings to a rectangle, holes are cut out (even-odd rule)
+pub(crate) fn clip_rings_to_rect(rings: &[&[Point2D]], rect: &Bbox) -> Vec<Polygon> {
+    let (min, max) = (rect.min(), rect.max());
+    resolve_rings(rings.iter()
+        .map(|r| clip_axis(&clip_axis(r, true, min.x, max.x), false, min.y, max.y))
+        .filter(|r| r.len() > 2)
+        .collect())
+}
+
+/// Indices of all cells of size `cell_size` that overlap the interval [min, max]
+fn cell_range(min: fsize, max: fsize, cell_size: fsize) -> ::std::ops::Range<i64> {
+    let first = (min / cell_size).floor() as i64;
+    let last = ((max / cell_size).ceil() as i64).max(first + 1);
+    first..last
+}
+
+/// Bounding box of all rings, None if all rings are empty
+pub(crate) fn bbox_of_rings(rings: &[&[Point2D]]) -> Option<Bbox> {
+    rings.iter().filter_map(|r| Bbox::from_points(r))
+         .fold(None, |acc, b| Some(acc.map(|a: Bbox| a.union(&b)).unwrap_or(b)))
+}
+
+/// Clips a ring to the slab `min <= x <= max` (or y, if `along_x` is false)
+/// with the Sutherland-Hodgman algorithm
+///
+/// Concave rings can result in zero-width connections along the slab border,
+/// these are removed by `resolve_rings`.
+fn clip_axis(nodes: &[Point2D], along_x: bool, min: fsize, max: fsize) -> Vec<Point2D> {
+    let coord = |p: &Point2D| if along_x { p.x } else { p.y };
+    let lower = clip_half_plane(nodes, |p| coord(p) - min);
+    clip_half_plane(&lower, |p| max - coord(p))
+}
+
+/// Keeps the part of the ring where `distance(p) >= 0`
+fn clip_half_plane<F>(nodes: &[Point2D], distance: F) -> Vec<Point2D> where F: Fn(&Point2D) -> fsize {
+    let mut out = Vec::with_capacity(nodes.len() + 4);
+    for (a, b) in ::edges::Edges::new(nodes, true) {
+        let (da, db) = (distance(&a), distance(&b));
+        if da >= 0.0 {
+            out.push(a);
+        }
+        if (da >= 0.0) != (db >= 0.0) && da != db {
+            let crossing = a.lerp(&b, da / (da - db));
+            if out.last() != Some(&crossing) {
+                out.push(crossing);
+            }
+        }
+    }
+    out.dedup();
+    while out.len() > 1 && out.first() == out.last() {
+        out.pop();
+    }
+    out
+}
+
+/// Turns the clipped rings into proper polygons and holes
+pub(crate) fn resolve_rings(rings: Vec<Vec<Point2D>>) -> Vec<Polygon> {
+    if rings.is_empty() {
+        return Vec::new();
+    }
+    let polygons: Vec<Polygon> = rings.into_iter().map(Polygon::new).collect();
+    let mut result = Arrangement::new(&polygons, &[]).extract(|a, _| a);
+    sort_canonical(&mut result);
+    result
+}
+
+#[test]
+fn test_split_to_grid() {
+    let square = Polygon::rect(0.5, 0.5, 2.0, 1.0);
+    let cells = square.split_to_grid(1.0, 1.0);
+
+    let indices: Vec<CellIndex> = cells.iter().map(|c| c.0).collect();
+    assert_eq!(indices.len(), 6);
+    assert_eq!(indices[0], CellIndex { column: 0, row: 0 });
+    assert_eq!(indices[5], CellIndex { column: 2, row: 1 });
+
+    let total: fsize = cells.iter().flat_map(|c| c.1.iter()).map(|p| p.area()).sum();
+    assert!((total - 2.0).abs() < 1e-5);
+
+    // a U shape is split into two pieces in the top cell
+    let u_shape = Polygon::from(vec![(0.0, 0.0), (3.0, 0.0), (3.0, 2.0), (2.0, 2.0),
+                                     (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0)]);
+    let cells = u_shape.split_to_grid(4.0, 1.0);
+    assert_eq!(cells.len(), 2);
+    assert_eq!(cells[0].1.len(), 1);
+    assert_eq!(cells[1].1.len(), 2);
+}
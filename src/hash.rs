@@ -0,0 +1,112 @@
+//! Canonical hashing of polygons
+
+use polygon::Polygon;
+use fsize;
+
+impl Polygon {
+
+    /// Hashes the geometry of the polygon, independent of the start vertex
+    /// and the orientation of the ring
+    ///
+    /// All coordinates are snapped to a grid with the cell size `quantization`
+    /// before hashing, so polygons whose vertices differ by less than the grid size
+    /// usually hash to the same value (vertices close to a cell border can still
+    /// snap to different cells). Consecutive vertices that snap to the same
+    /// cell are merged.
+    ///
+    /// The hash is computed with 64-bit FNV-1a and does not depend on the platform
+    /// or the Rust version, so it can be used as a key for persistent caches.
+    pub fn canonical_hash(&self, quantization: fsize) -> u64 {
+
+        let quantization = if quantization > 0.0 { quantization } else { 1.0 };
+
+        let mut ring: Vec<(i64, i64)> = Vec::with_capacity(self.nodes.len());
+        for p in &self.nodes {
+            let q = ((p.x / quantization).round() as i64, (p.y / quantization).round() as i64);
+            if ring.last() != Some(&q) {
+                ring.push(q);
+            }
+        }
+
+        if self.is_closed {
+            while ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
+            }
+        }
+
+        let len = ring.len();
+        let canonical: Vec<(i64, i64)> = if len == 0 {
+            ring
+        } else if self.is_closed {
+            // start at the smallest vertex, walk in the direction of the smaller neighbour
+            let start = (0..len).min_by_key(|i| ring[*i]).unwrap();
+            let forward: Vec<(i64, i64)> = (0..len).map(|i| ring[(start + i) % len]).collect();
+            let backward: Vec<(i64, i64)> = (0..len).map(|i| ring[(start + len - i) % len]).collect();
+            if backward < forward { backward } else { forward }
+        } else {
+            let mut backward = ring.clone();
+            backward.reverse();
+            if backward < ring { backward } else { ring }
+        };
+
+        let mut hasher = Fnv1a::new();
+        hasher.write_u64(self.is_closed as u64);
+        hasher.write_u64(canonical.len() as u64);
+        for (x, y) in canonical {
+            hasher.write_u64(x as u64);
+            hasher.write_u64(y as u64);
+        }
+        hasher.finish()
+    }
+}
+
+/// 64-bit FNV-1a, which (unlike the `DefaultHasher` of the standard library)
+/// is stable across Rust versions
+struct Fnv1a(u64);
+
+impl Fnv1a {
+
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    fn new() -> Self {
+        Fnv1a(Self::OFFSET_BASIS)
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        for i in 0..8 {
+            self.0 ^= (value >> (i * 8)) & 0xff;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[test]
+fn test_canonical_hash_rotation_and_orientation() {
+    use Point2D;
+
+    let a = Polygon {
+        nodes: vec![
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 10.0, y: 0.0 },
+            Point2D { x: 10.0, y: 10.0 },
+            Point2D { x: 0.0, y: 10.0 },
+        ],
+        .. Default::default()
+    };
+    let b = Polygon {
+        nodes: vec![
+            Point2D { x: 10.0, y: 10.0 },
+            Point2D { x: 10.001, y: 0.0 },
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 0.0, y: 10.0 },
+        ],
+        .. Default::default()
+    };
+    assert_eq!(a.canonical_hash(0.1), b.canonical_hash(0.1));
+    assert!(a.canonical_hash(0.1) != a.translate(1.0, 0.0).canonical_hash(0.1));
+}
@@ -0,0 +1,84 @@
+//! Generation of parallel infill lines (hatching)
+
+use multi_polygon::MultiPolygon;
+use polygon::{Polygon, FillRule};
+use raster::scanline_spans;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Fills the polygon with parallel lines, `spacing` apart, running at
+    /// `angle` (in radians, counter-clockwise from the x axis)
+    ///
+    /// The lines lie on a global grid (all multiples of `spacing` from the
+    /// origin), so that hatching adjacent polygons with the same parameters
+    /// produces continuous lines. Lines are returned in the order of their offset.
+    pub fn hatch(&self, angle: fsize, spacing: fsize) -> Vec<(Point2D, Point2D)> {
+        hatch_rings(&[&self.nodes[..]], angle, spacing)
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Fills all polygons with parallel lines, leaving out the holes
+    /// (even-odd rule), see `Polygon::hatch`
+    pub fn hatch(&self, angle: fsize, spacing: fsize) -> Vec<(Point2D, Point2D)> {
+        let rings: Vec<&[Point2D]> = self.polygons.iter().map(|p| &p.nodes[..]).collect();
+        hatch_rings(&rings, angle, spacing)
+    }
+}
+
+fn hatch_rings(rings: &[&[Point2D]], angle: fsize, spacing: fsize) -> Vec<(Point2D, Point2D)> {
+
+    if !(spacing > 0.0) {
+        return Vec::new();
+    }
+
+    // rotate the rings so that the hatch lines become horizontal scanlines
+    let (sin, cos) = angle.sin_cos();
+    let rotated: Vec<Vec<Point2D>> = rings.iter().map(|r| {
+        r.iter().map(|p| Point2D { x: p.x * cos + p.y * sin, y: -p.x * sin + p.y * cos }).collect()
+    }).collect();
+    let rotated_refs: Vec<&[Point2D]> = rotated.iter().map(|r| &r[..]).collect();
+
+    let all_points: Vec<Point2D> = rotated.iter().flat_map(|r| r.iter().cloned()).collect();
+    let bbox = match ::Bbox::from_points(&all_points) {
+        Some(b) => b,
+        None => return Vec::new(),
+    };
+
+    let unrotate = |x: fsize, y: fsize| Point2D { x: x * cos - y * sin, y: x * sin + y * cos };
+    let mut lines = Vec::new();
+    let mut line = (bbox.min().y / spacing).ceil() as i64;
+
+    loop {
+        let y = line as fsize * spacing;
+        if y > bbox.max().y {
+            break;
+        }
+        for (start, end) in scanline_spans(&rotated_refs, y, FillRule::EvenOdd) {
+            lines.push((unrotate(start, y), unrotate(end, y)));
+        }
+        line += 1;
+    }
+
+    lines
+}
+
+#[test]
+fn test_hatch() {
+    let square = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+
+    // horizontal lines at y = 0, 1, 2, 3 (the line at y = 4 is on the edge)
+    let lines = square.hatch(0.0, 1.0);
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[1], (Point2D { x: 0.0, y: 1.0 }, Point2D { x: 4.0, y: 1.0 }));
+
+    // vertical lines with a hole in the middle
+    let hole = Polygon::rect(1.5, 1.5, 1.0, 1.0);
+    let lines = MultiPolygon::new(vec![square, hole]).hatch(::std::f64::consts::FRAC_PI_2 as fsize, 1.0);
+    assert_eq!(lines.len(), 5);
+    for &(a, b) in &lines {
+        assert!((a.x - b.x).abs() < 1e-4);
+    }
+}
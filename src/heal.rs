@@ -0,0 +1,150 @@
+//! Healing of small gaps and overlaps between adjacent polygons
+
+use std::collections::{HashMap, HashSet};
+use multi_polygon::MultiPolygon;
+use utils::distance_point_segment;
+use {Point2D, fsize};
+
+impl<T: Clone> MultiPolygon<T> {
+
+    /// Snaps the polygons of the set together, so that boundaries which are
+    /// meant to be shared become identical
+    ///
+    /// - vertices of all polygons that are closer than `tolerance` are merged
+    ///   into one shared vertex
+    /// - vertices that are closer than `tolerance` to an edge of another polygon
+    ///   are inserted into that edge
+    ///
+    /// This closes hairline gaps and removes hairline overlaps narrower than
+    /// `tolerance`, so that a following union produces a clean coverage. Rings
+    /// that collapse to less than three vertices are removed.
+    pub fn heal(&self, tolerance: fsize) -> Self {
+
+        if !(tolerance > 0.0) {
+            return self.clone();
+        }
+
+        let rings: Vec<&[Point2D]> = self.polygons.iter().map(|p| &p.nodes[..]).collect();
+        let snapped = snap_vertices(&rings, tolerance);
+
+        let bboxes: Vec<Option<::Bbox>> = snapped.iter()
+            .map(|nodes| ::Bbox::from_points(nodes).map(|b| b.expand(tolerance)))
+            .collect();
+
+        let polygons = self.polygons.iter().enumerate().filter_map(|(idx, polygon)| {
+
+            let own_bbox = match bboxes[idx] {
+                Some(b) => b,
+                None => return None,
+            };
+
+            // vertices of other rings that could lie close to this ring
+            let mut candidates = HashSet::<Point2D>::new();
+            for (other, nodes) in snapped.iter().enumerate() {
+                let overlaps = bboxes[other].map(|b| b.overlaps(&own_bbox)).unwrap_or(false);
+                if other != idx && overlaps {
+                    candidates.extend(nodes.iter().cloned());
+                }
+            }
+
+            let nodes = &snapped[idx];
+            let len = nodes.len();
+            let edge_count = if polygon.is_closed { len } else { len.saturating_sub(1) };
+            let mut out = Vec::with_capacity(len);
+
+            for i in 0..len {
+                out.push(nodes[i]);
+                if i >= edge_count {
+                    continue;
+                }
+
+                let (a, b) = (nodes[i], nodes[(i + 1) % len]);
+                let ab = b - a;
+                let len_sq = ab.dot(&ab);
+                if len_sq == 0.0 {
+                    continue;
+                }
+
+                let mut inserted: Vec<(fsize, Point2D)> = candidates.iter()
+                    .filter(|v| **v != a && **v != b)
+                    .filter(|v| distance_point_segment(v, &a, &b) <= tolerance)
+                    .map(|v| ((*v - a).dot(&ab) / len_sq, *v))
+                    .filter(|&(t, _)| t > 0.0 && t < 1.0)
+                    .collect();
+                inserted.sort_by(|x, y| x.partial_cmp(y).unwrap_or(::std::cmp::Ordering::Equal));
+                out.extend(inserted.into_iter().map(|(_, v)| v));
+            }
+
+            out.dedup();
+            while polygon.is_closed && out.len() > 1 && out.first() == out.last() {
+                out.pop();
+            }
+
+            if polygon.is_closed && out.len() < 3 {
+                None
+            } else {
+                Some(polygon.with_nodes(out))
+            }
+        }).collect();
+
+        MultiPolygon::with_data(polygons, self.data.clone())
+    }
+}
+
+/// Merges all vertices closer than `tolerance` into shared vertices
+///
+/// The vertices are processed in sorted order, every vertex is replaced by
+/// the first vertex within `tolerance`, so the result does not depend on
+/// the order of the rings.
+fn snap_vertices(rings: &[&[Point2D]], tolerance: fsize) -> Vec<Vec<Point2D>> {
+
+    let cell = |p: &Point2D| ((p.x / tolerance).floor() as i64, (p.y / tolerance).floor() as i64);
+
+    let mut all: Vec<Point2D> = rings.iter().flat_map(|r| r.iter().cloned()).collect();
+    all.sort();
+    all.dedup();
+
+    let mut grid = HashMap::<(i64, i64), Vec<Point2D>>::new();
+    let mut snapped_to = HashMap::<Point2D, Point2D>::new();
+
+    for p in all {
+        let (cx, cy) = cell(&p);
+        let existing = (cx - 1..cx + 2)
+            .flat_map(|x| (cy - 1..cy + 2).map(move |y| (x, y)))
+            .filter_map(|key| grid.get(&key))
+            .flat_map(|points| points.iter())
+            .filter(|q| q.dist(&p) <= tolerance)
+            .min()
+            .cloned();
+
+        match existing {
+            Some(q) => { snapped_to.insert(p, q); },
+            None => {
+                grid.entry((cx, cy)).or_insert_with(Vec::new).push(p);
+                snapped_to.insert(p, p);
+            },
+        }
+    }
+
+    rings.iter().map(|r| r.iter().map(|p| snapped_to[p]).collect()).collect()
+}
+
+#[test]
+fn test_heal_closes_gap() {
+    use polygon::Polygon;
+
+    // two squares with a hairline gap and a vertex that is slightly off
+    let left = Polygon::from(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+    let right = Polygon::from(vec![(1.001, 0.0), (2.0, 0.0), (2.0, 2.0), (1.001, 2.0), (1.002, 0.5)]);
+
+    let healed = MultiPolygon::new(vec![left, right]).heal(0.01);
+    let left = &healed.polygons[0].nodes;
+    let right = &healed.polygons[1].nodes;
+
+    // the corners of the left square are shared with the right square
+    assert!(right.contains(&Point2D { x: 1.0, y: 0.0 }));
+    assert!(right.contains(&Point2D { x: 1.0, y: 1.0 }));
+    // the off vertex of the right square is inserted into the left square
+    assert!(left.contains(&Point2D { x: 1.002, y: 0.5 }));
+    assert_eq!(left.len(), 5);
+}
@@ -0,0 +1,119 @@
+//! Re-clipping when one input moves
+//!
+//! Interactive editors drag one shape (the clip) over a large, static set (the
+//! subject). Only the parts of the subject near the clip can change, so the
+//! subject is split into its exteriors (with their holes) once, and an update
+//! only runs the boolean operation on the exteriors whose bounding box
+//! overlaps the clip. All other exteriors are taken over unchanged.
+
+use error::ClipError;
+use multi_polygon::MultiPolygon;
+use options::ClipOptions;
+use polygon::{Polygon, BoolOpType};
+use {Bbox, fsize};
+
+/// Cached result of `subject <op> clip`, which is updated when the clip moves
+#[derive(Debug, Clone)]
+pub struct IncrementalClip<T = ()> {
+    /// Exteriors of the subject together with their holes and the bounding box of the exterior
+    groups: Vec<(Vec<Polygon>, Option<Bbox>)>,
+    clip: MultiPolygon,
+    operation_type: BoolOpType,
+    options: ClipOptions,
+    result: MultiPolygon<T>,
+}
+
+impl<T: Clone> IncrementalClip<T> {
+
+    /// Computes `subject <operation_type> clip`
+    ///
+    /// The subject is dissolved first (overlapping members are merged), so
+    /// that its exteriors can be clipped independently of each other.
+    pub fn new(subject: &MultiPolygon<T>, clip: MultiPolygon, operation_type: BoolOpType, options: ClipOptions)
+               -> Result<Self, ClipError>
+    {
+        let dissolved = subject.try_clip(&MultiPolygon::new(Vec::new()), BoolOpType::Union, &options)?;
+        let groups = dissolved.exteriors_with_holes().into_iter()
+            .map(|(exterior, holes)| {
+                let bbox = exterior.bbox;
+                let rings = Some(exterior).into_iter().chain(holes).cloned().collect();
+                (rings, bbox)
+            })
+            .collect();
+
+        let mut incremental = Self {
+            groups: groups,
+            clip: MultiPolygon::new(Vec::new()),
+            operation_type: operation_type,
+            options: options,
+            result: MultiPolygon::with_data(Vec::new(), dissolved.data),
+        };
+        incremental.set_clip(clip)?;
+        Ok(incremental)
+    }
+
+    /// The result of the operation with the current clip
+    pub fn result(&self) -> &MultiPolygon<T> {
+        &self.result
+    }
+
+    /// The current clip
+    pub fn clip(&self) -> &MultiPolygon {
+        &self.clip
+    }
+
+    /// Replaces the clip and updates the result. Only the exteriors of the
+    /// subject that overlap the new clip go through the boolean operation.
+    /// If the operation fails, the previous clip and result are kept.
+    pub fn set_clip(&mut self, clip: MultiPolygon) -> Result<(), ClipError> {
+        let clip_bbox = clip.combined_bbox();
+        let touches = |bbox: &Option<Bbox>| match (*bbox, clip_bbox) {
+            (Some(a), Some(b)) => a.overlaps(&b),
+            _ => false,
+        };
+
+        let (near, far): (Vec<_>, Vec<_>) = self.groups.iter().partition(|&&(_, ref bbox)| touches(bbox));
+        let near = MultiPolygon::new(near.into_iter().flat_map(|&(ref rings, _)| rings.iter().cloned()).collect());
+        let mut polygons = near.try_clip(&clip, self.operation_type, &self.options)?.polygons;
+
+        // the parts of the subject that are far away from the clip are not
+        // changed by the operation, except that the intersection drops them
+        if self.operation_type != BoolOpType::Intersection {
+            polygons.extend(far.into_iter().flat_map(|&(ref rings, _)| rings.iter().cloned()));
+            ::multi_polygon::sort_canonical(&mut polygons);
+        }
+
+        self.clip = clip;
+        self.result.polygons = polygons;
+        Ok(())
+    }
+
+    /// Moves the clip by (dx, dy) and updates the result
+    pub fn translate_clip(&mut self, dx: fsize, dy: fsize) -> Result<(), ClipError> {
+        self.transform_clip(&[1.0, 0.0, dx, 0.0, 1.0, dy])
+    }
+
+    /// Applies the affine transformation `m` to the clip and updates the
+    /// result, see `Polygon::transform`
+    pub fn transform_clip(&mut self, m: &[fsize; 6]) -> Result<(), ClipError> {
+        let clip = MultiPolygon::new(self.clip.polygons.iter().map(|p| p.transform(m)).collect());
+        self.set_clip(clip)
+    }
+}
+
+#[test]
+fn test_incremental_clip() {
+    let subject = MultiPolygon::new((0..5).map(|i| Polygon::rect(i as fsize * 3.0, 0.0, 2.0, 2.0)).collect());
+    let clip = MultiPolygon::new(vec![Polygon::rect(-1.5, 0.5, 1.0, 1.0)]);
+
+    for &op in [BoolOpType::Intersection, BoolOpType::Union, BoolOpType::Difference, BoolOpType::Xor].iter() {
+        let mut incremental = IncrementalClip::new(&subject, clip.clone(), op, ClipOptions::default()).unwrap();
+
+        for step in 0..12 {
+            let expected = subject.clip(incremental.clip(), op, &ClipOptions::default());
+            let nodes = |m: &MultiPolygon| m.polygons.iter().map(|p| (p.nodes.clone(), p.is_hole)).collect::<Vec<_>>();
+            assert_eq!(nodes(incremental.result()), nodes(&expected), "{:?} at step {}", op, step);
+            incremental.translate_clip(1.25, 0.0).unwrap();
+        }
+    }
+}
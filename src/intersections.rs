@@ -0,0 +1,303 @@
+//! Bentley-Ottmann sweep for finding all intersections in a set of segments
+
+use std::cmp::Ordering;
+use error::ClipError;
+use event_queue::EventQueue;
+use options::OperationGuard;
+use sweep_debug::SnapshotRecorder;
+use {Point2D, Segment, fsize};
+
+/// A point where two or more of the input segments meet
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntersectionEvent {
+    /// Location of the intersection
+    pub point: Point2D,
+    /// Sorted indices (into the input slice) of all segments touching `point`
+    pub segments: Vec<usize>,
+}
+
+/// Point of an event and the index of the segment that starts there, if any.
+/// The crossings of segments and the lower endpoints start no segment.
+///
+/// Events are processed in the order of `Point2D` (by x, then by y). The
+/// events at one point are taken from the queue together, so the order among
+/// them (first those that start no segment, then the starting segments by
+/// their index) only decides the order of the segments in `upper`.
+type Event = (Point2D, Option<usize>);
+
+/// Segment with its endpoints sorted in sweep order (`upper < lower`)
+#[derive(Debug, Copy, Clone)]
+struct SweepSegment {
+    upper: Point2D,
+    lower: Point2D,
+}
+
+impl SweepSegment {
+    /// y-coordinate of the segment at the sweep position `p`
+    fn y_at(&self, p: &Point2D) -> fsize {
+        if self.upper.x == self.lower.x {
+            return p.y.max(self.upper.y).min(self.lower.y);
+        }
+        let t = (p.x - self.upper.x) / (self.lower.x - self.upper.x);
+        self.upper.y + t * (self.lower.y - self.upper.y)
+    }
+
+    /// Slope used to order segments leaving the same point, vertical segments last
+    fn slope(&self) -> fsize {
+        if self.upper.x == self.lower.x {
+            fsize::INFINITY
+        } else {
+            (self.lower.y - self.upper.y) / (self.lower.x - self.upper.x)
+        }
+    }
+}
+
+/// Reports every point where two or more of the `segments` cross or touch,
+/// including shared endpoints and T-junctions. Zero-length and non-finite
+/// segments are ignored. Runs in O((n + k) log n) for n segments with k
+/// intersections, the results are sorted by their point.
+pub fn find_intersections(segments: &[Segment]) -> Vec<IntersectionEvent> {
+    match find_intersections_guarded(segments, &mut OperationGuard::unlimited()) {
+        Ok(result) => result,
+        Err(_) => unreachable!("an unlimited guard never aborts"),
+    }
+}
+
+/// Same as `find_intersections`, but can be aborted by the `guard`
+pub(crate) fn find_intersections_guarded(segments: &[Segment], guard: &mut OperationGuard)
+                                         -> Result<Vec<IntersectionEvent>, ClipError>
+{
+    find_intersections_observed(segments, guard, None)
+}
+
+/// Same as `find_intersections_guarded`, but records the state of the sweep
+/// after every event if `recorder` is set
+pub(crate) fn find_intersections_observed(segments: &[Segment], guard: &mut OperationGuard,
+                                          mut recorder: Option<&mut SnapshotRecorder>)
+                                          -> Result<Vec<IntersectionEvent>, ClipError>
+{
+
+    let mut sweep_segments = Vec::with_capacity(segments.len());
+    let mut events = Vec::with_capacity(2 * segments.len());
+    let mut scale: fsize = 1.0;
+
+    for (idx, s) in segments.iter().enumerate() {
+        let finite = s.a.x.is_finite() && s.a.y.is_finite() &&
+                     s.b.x.is_finite() && s.b.y.is_finite();
+        let (upper, lower) = if s.a < s.b { (s.a, s.b) } else { (s.b, s.a) };
+        sweep_segments.push(SweepSegment { upper: upper, lower: lower });
+
+        if !finite || upper == lower {
+            continue;
+        }
+
+        scale = scale.max(upper.x.abs()).max(upper.y.abs())
+                     .max(lower.x.abs()).max(lower.y.abs());
+        events.push((upper, Some(idx)));
+        events.push((lower, None));
+    }
+
+    // the initial events are sorted at once, which counts as one insertion
+    let mut queue = profile!(event_queue_push, EventQueue::new(events));
+
+    let eps = fsize::EPSILON * 8.0 * scale;
+    let mut status = Vec::<usize>::new();
+    let mut result = Vec::new();
+
+    while let Some(&(p, _)) = queue.peek() {
+        guard.tick()?;
+        let upper = profile!(event_queue_pop, pop_events_at(&mut queue, &p));
+        sweep_trace!("event popped: p={:?} starting={:?}", p, upper);
+
+        // status is sorted by y at the sweep line, so all segments passing
+        // through p (the ones ending at p and the ones containing p) are contiguous
+        let lo = lower_bound(&status, &sweep_segments, &p, p.y - eps);
+        let mut hi = lo;
+        while hi < status.len() && sweep_segments[status[hi]].y_at(&p) <= p.y + eps {
+            hi += 1;
+        }
+
+        let mut involved = upper.clone();
+        involved.extend_from_slice(&status[lo..hi]);
+
+        // segments that continue past p are re-inserted, ordered by their
+        // direction after p. Collinear segments have the same slope and are
+        // ordered by their index, so the status does not depend on the order
+        // in which the segments reached p.
+        let mut continuing: Vec<usize> = profile!(sweep_line_remove, status.drain(lo..hi)
+            .filter(|idx| !points_close(&sweep_segments[*idx].lower, &p, eps))
+            .collect());
+        continuing.extend(upper);
+        continuing.sort_by(|a, b| {
+            sweep_segments[*a].slope().partial_cmp(&sweep_segments[*b].slope())
+                .unwrap_or(Ordering::Equal)
+                .then(a.cmp(b))
+        });
+
+        sweep_trace!("segments removed at {:?}: {}, inserted at {}: {:?}", p, hi - lo, lo, continuing);
+        let inserted = continuing.len();
+        profile!(sweep_line_insert, for (offset, idx) in continuing.into_iter().enumerate() {
+            status.insert(lo + offset, idx);
+        });
+
+        if inserted == 0 {
+            if lo > 0 && lo < status.len() {
+                find_new_event(&sweep_segments, status[lo - 1], status[lo], &p, eps, &mut queue);
+            }
+        } else {
+            let last = lo + inserted - 1;
+            if lo > 0 {
+                find_new_event(&sweep_segments, status[lo - 1], status[lo], &p, eps, &mut queue);
+            }
+            if last + 1 < status.len() {
+                find_new_event(&sweep_segments, status[last], status[last + 1], &p, eps, &mut queue);
+            }
+        }
+
+        if let Some(ref mut recorder) = recorder {
+            recorder.record(p, &involved, &status, segments);
+        }
+
+        if involved.len() > 1 {
+            involved.sort();
+            involved.dedup();
+            result.push(IntersectionEvent { point: p, segments: involved });
+        }
+    }
+
+    Ok(result)
+}
+
+/// Removes all events at `p` from the queue, returns the segments starting there
+fn pop_events_at(queue: &mut EventQueue<Event>, p: &Point2D) -> Vec<usize> {
+    let mut upper = Vec::new();
+    while queue.peek().map_or(false, |&(q, _)| q == *p) {
+        upper.extend(queue.pop().and_then(|(_, idx)| idx));
+    }
+    upper
+}
+
+/// First position in `status` whose segment is at or above `y` at the sweep position `p`
+fn lower_bound(status: &[usize], segments: &[SweepSegment], p: &Point2D, y: fsize) -> usize {
+    let mut lo = 0;
+    let mut hi = status.len();
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if segments[status[mid]].y_at(p) < y {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+#[inline]
+fn points_close(a: &Point2D, b: &Point2D, eps: fsize) -> bool {
+    (a.x - b.x).abs() <= eps && (a.y - b.y).abs() <= eps
+}
+
+/// Schedules the crossing of two neighbouring segments if it lies after `p`
+///
+/// Crossings within `eps` of an endpoint are snapped to the endpoint, so that
+/// rounding errors do not create a second event right next to it.
+fn find_new_event(segments: &[SweepSegment], first: usize, second: usize,
+                  p: &Point2D, eps: fsize, queue: &mut EventQueue<Event>)
+{
+    profile!(possible_intersection, schedule_crossing(segments, first, second, p, eps, queue))
+}
+
+/// Body of `find_new_event`, which measures it
+fn schedule_crossing(segments: &[SweepSegment], first: usize, second: usize,
+                     p: &Point2D, eps: fsize, queue: &mut EventQueue<Event>)
+{
+    let s1 = &segments[first];
+    let s2 = &segments[second];
+
+    // segments that share an endpoint do not meet anywhere else, and the
+    // endpoint already is an event
+    if s1.upper == s2.upper || s1.upper == s2.lower || s1.lower == s2.upper || s1.lower == s2.lower {
+        return;
+    }
+
+    let d1 = s1.lower - s1.upper;
+    let d2 = s2.lower - s2.upper;
+    let denom = d1.cross(&d2);
+
+    // parallel segments never cross properly, overlaps are found through
+    // their endpoints lying on the other segment
+    if denom == 0.0 {
+        return;
+    }
+
+    let diff = s2.upper - s1.upper;
+    let t = diff.cross(&d2) / denom;
+    let u = diff.cross(&d1) / denom;
+
+    if t < 0.0 || t > 1.0 || u < 0.0 || u > 1.0 {
+        return;
+    }
+
+    let computed = s1.upper + d1 * t;
+    let q = [s1.upper, s1.lower, s2.upper, s2.lower].iter().cloned()
+        .find(|endpoint| points_close(endpoint, &computed, eps))
+        .unwrap_or(computed);
+    if q > *p {
+        profile!(event_queue_push, queue.push((q, None)));
+    }
+}
+
+#[test]
+fn test_find_intersections() {
+    let pt = |x, y| Point2D { x: x, y: y };
+    let segments = [
+        Segment::new(pt(0.0, 0.0), pt(4.0, 4.0)),
+        Segment::new(pt(0.0, 4.0), pt(4.0, 0.0)),
+        Segment::new(pt(2.0, 0.0), pt(2.0, 4.0)),
+        Segment::new(pt(5.0, 0.0), pt(6.0, 0.0)),
+        Segment::new(pt(6.0, 0.0), pt(6.0, 1.0)),
+        Segment::new(pt(0.0, 1.0), pt(1.0, 1.0)),
+    ];
+
+    let result = find_intersections(&segments);
+    assert_eq!(result, vec![
+        IntersectionEvent { point: pt(1.0, 1.0), segments: vec![0, 5] },
+        IntersectionEvent { point: pt(2.0, 2.0), segments: vec![0, 1, 2] },
+        IntersectionEvent { point: pt(6.0, 0.0), segments: vec![3, 4] },
+    ]);
+
+    // T-junction: endpoint of one segment in the interior of another
+    let t_junction = [
+        Segment::new(pt(0.0, 0.0), pt(4.0, 0.0)),
+        Segment::new(pt(1.0, 0.0), pt(1.0, 3.0)),
+    ];
+    assert_eq!(find_intersections(&t_junction), vec![
+        IntersectionEvent { point: pt(1.0, 0.0), segments: vec![0, 1] },
+    ]);
+}
+
+#[test]
+fn test_find_intersections_ignores_input_order() {
+    let pt = |x, y| Point2D { x: x, y: y };
+    // overlapping collinear segments, which have the same slope, and
+    // segments that cross them at their shared part
+    let segments = [
+        Segment::new(pt(0.0, 0.0), pt(4.0, 0.0)),
+        Segment::new(pt(1.0, 0.0), pt(5.0, 0.0)),
+        Segment::new(pt(5.0, 0.0), pt(2.0, 0.0)),
+        Segment::new(pt(2.0, -1.0), pt(3.0, 1.0)),
+        Segment::new(pt(3.0, -1.0), pt(2.0, 1.0)),
+    ];
+    let expected = find_intersections(&segments);
+
+    let n = segments.len();
+    let reversed: Vec<Segment> = segments.iter().rev().cloned().collect();
+    let mut result = find_intersections(&reversed);
+    for event in result.iter_mut() {
+        for idx in event.segments.iter_mut() {
+            *idx = n - 1 - *idx;
+        }
+        event.segments.sort();
+    }
+    assert_eq!(result, expected);
+}
@@ -0,0 +1,291 @@
+//! Geometric predicates and constructions
+//!
+//! All decisions of the algorithms (on which side of an edge a point lies,
+//! whether and where two edges intersect, in which order points are swept)
+//! go through a `Kernel`. The kernel that the crate uses is `DefaultKernel`,
+//! which is selected with cargo features:
+//!
+//! - no feature: `FloatKernel`, plain `fsize` arithmetic
+//! - `adaptive_precision`: `RobustKernel`, recomputes uncertain results in `f64`
+//! - `exact_predicates`: `ExactKernel`, exact orientation tests
+//!
+//! The kernels can also be used directly, for example to check results.
+
+use std::cmp::Ordering;
+use {Point2D, fsize};
+
+/// Predicates and constructions of the algorithms
+pub trait Kernel {
+    /// Orientation of the triangle (a, b, c): `Greater` if it turns
+    /// counter-clockwise, `Less` if it turns clockwise and `Equal` if the
+    /// points are collinear
+    fn orient2d(a: &Point2D, b: &Point2D, c: &Point2D) -> Ordering;
+
+    /// Intersection of the segments (p0, p1) and (p2, p3), see `line_intersect`
+    fn intersect(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D) -> Option<(Point2D, Option<Point2D>)>;
+
+    /// Order in which points are swept, lexicographic by default (see `Point2D::cmp`)
+    #[inline]
+    fn compare_points(a: &Point2D, b: &Point2D) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// Kernel that computes everything in `fsize`
+///
+/// This is the fastest kernel, but the orientation of nearly collinear points
+/// can be wrong, which can make the sweep inconsistent on degenerate input.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct FloatKernel;
+
+/// Kernel that computes the orientation in `fsize` and recomputes it in `f64`
+/// if rounding errors could have flipped its sign (Shewchuk's error bound)
+///
+/// Nearly all calls take the fast path, but for `f32` coordinates the result
+/// is exact. Intersections are classified with these orientation tests and
+/// computed in `f64`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RobustKernel;
+
+/// Kernel with exact orientation tests for any finite coordinates, using
+/// floating point expansions (Shewchuk, "Adaptive Precision Floating-Point
+/// Arithmetic and Fast Robust Geometric Predicates", 1997)
+///
+/// The slowest kernel. Intersection points are still rounded to `fsize`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ExactKernel;
+
+/// Kernel used by the algorithms of this crate
+#[cfg(not(any(feature = "adaptive_precision", feature = "exact_predicates")))]
+pub type DefaultKernel = FloatKernel;
+/// Kernel used by the algorithms of this crate
+#[cfg(all(feature = "adaptive_precision", not(feature = "exact_predicates")))]
+pub type DefaultKernel = RobustKernel;
+/// Kernel used by the algorithms of this crate
+#[cfg(feature = "exact_predicates")]
+pub type DefaultKernel = ExactKernel;
+
+impl Kernel for FloatKernel {
+
+    #[inline]
+    fn orient2d(a: &Point2D, b: &Point2D, c: &Point2D) -> Ordering {
+        let det = (a.x - c.x) * (b.y - c.y) - (b.x - c.x) * (a.y - c.y);
+        det.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+    }
+
+    #[inline]
+    fn intersect(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D) -> Option<(Point2D, Option<Point2D>)> {
+        let s1_x = p1.x - p0.x;
+        let s1_y = p1.y - p0.y;
+        let s2_x = p3.x - p2.x;
+        let s2_y = p3.y - p2.y;
+
+        let coef_div = -s2_x * s1_y + s1_x * s2_y;
+
+        if coef_div == 0.0 {
+            return ::point::collinear_overlap(p0, p1, p2, p3);
+        }
+
+        let s = (-s1_y * (p0.x - p2.x) + s1_x * (p0.y - p2.y)) / coef_div;
+        let t = ( s2_x * (p0.y - p2.y) - s2_y * (p0.x - p2.x)) / coef_div;
+
+        if t >= 0.0 && t <= 1.0 && s >= 0.0 && s <= 1.0 {
+            let first_point = Point2D {
+                x: p0.x + (t * s1_x),
+                y: p0.y + (t * s1_y)
+            };
+            Some((first_point, None))
+        } else {
+            None
+        }
+    }
+}
+
+impl Kernel for RobustKernel {
+
+    #[inline]
+    fn orient2d(a: &Point2D, b: &Point2D, c: &Point2D) -> Ordering {
+        let left = (a.x - c.x) * (b.y - c.y);
+        let right = (b.x - c.x) * (a.y - c.y);
+        let det = left - right;
+
+        if det.abs() <= orientation_error_bound(left, right) {
+            orient2d_f64(a, b, c)
+        } else {
+            det.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+        }
+    }
+
+    fn intersect(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D) -> Option<(Point2D, Option<Point2D>)> {
+        intersect_with_predicates::<Self>(p0, p1, p2, p3)
+    }
+}
+
+impl Kernel for ExactKernel {
+
+    fn orient2d(a: &Point2D, b: &Point2D, c: &Point2D) -> Ordering {
+        // (a - c) x (b - c), expanded so that only products of the input
+        // coordinates remain (the c.x * c.y terms cancel)
+        let (ax, ay, bx, by, cx, cy) = (a.x as f64, a.y as f64, b.x as f64, b.y as f64, c.x as f64, c.y as f64);
+        let products = [(ax, by), (-ax, cy), (-cx, by), (-bx, ay), (bx, cy), (cx, ay)];
+
+        let mut expansion = [0.0; 12];
+        let mut len = 0;
+        for &(x, y) in products.iter() {
+            let (product, error) = two_product(x, y);
+            len = grow_expansion(&mut expansion, len, error);
+            len = grow_expansion(&mut expansion, len, product);
+        }
+
+        // the components are ordered by magnitude and do not overlap, so the
+        // largest non-zero component has the sign of the sum
+        expansion[..len].iter().rev()
+            .find(|c| **c != 0.0)
+            .map(|c| c.partial_cmp(&0.0).unwrap_or(Ordering::Equal))
+            .unwrap_or(Ordering::Equal)
+    }
+
+    fn intersect(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D) -> Option<(Point2D, Option<Point2D>)> {
+        intersect_with_predicates::<Self>(p0, p1, p2, p3)
+    }
+}
+
+/// Maximum absolute error of `left - right` in `RobustKernel::orient2d`
+#[inline]
+fn orientation_error_bound(left: fsize, right: fsize) -> fsize {
+    let half_epsilon = fsize::EPSILON / 2.0;
+    (3.0 + 16.0 * half_epsilon) * half_epsilon * (left.abs() + right.abs())
+}
+
+/// Slow path of `RobustKernel::orient2d`. The differences and products of
+/// `f32` coordinates of similar magnitude are exact in `f64`.
+#[inline(never)]
+fn orient2d_f64(a: &Point2D, b: &Point2D, c: &Point2D) -> Ordering {
+    let det = (a.x as f64 - c.x as f64) * (b.y as f64 - c.y as f64) -
+              (b.x as f64 - c.x as f64) * (a.y as f64 - c.y as f64);
+    det.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+}
+
+/// `a * b` as the rounded product and its rounding error
+#[inline]
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    (product, a.mul_add(b, -product))
+}
+
+/// `a + b` as the rounded sum and its rounding error
+#[inline]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    (sum, (a - a_virtual) + (b - b_virtual))
+}
+
+/// Adds `value` to the first `len` components of the expansion and returns
+/// its new length (Shewchuk's Grow-Expansion)
+#[inline]
+fn grow_expansion(expansion: &mut [f64], len: usize, value: f64) -> usize {
+    let mut sum = value;
+    for component in expansion[..len].iter_mut() {
+        let (new_sum, error) = two_sum(sum, *component);
+        *component = error;
+        sum = new_sum;
+    }
+    expansion[len] = sum;
+    len + 1
+}
+
+/// Intersection of two segments that is classified with the orientation
+/// tests of `K`. Touching endpoints are returned exactly, proper crossings
+/// are computed in `f64` and clamped to the bounding boxes of both segments.
+fn intersect_with_predicates<K: Kernel>(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D)
+                                        -> Option<(Point2D, Option<Point2D>)>
+{
+    let d0 = K::orient2d(p2, p3, p0);
+    let d1 = K::orient2d(p2, p3, p1);
+    let d2 = K::orient2d(p0, p1, p2);
+    let d3 = K::orient2d(p0, p1, p3);
+
+    if (d0 == Ordering::Equal && d1 == Ordering::Equal) || (d2 == Ordering::Equal && d3 == Ordering::Equal) {
+        return collinear_overlap::<K>(p0, p1, p2, p3);
+    }
+
+    if (d0 == d1 && d0 != Ordering::Equal) || (d2 == d3 && d2 != Ordering::Equal) {
+        return None;
+    }
+
+    for &(d, p) in [(d0, p0), (d1, p1), (d2, p2), (d3, p3)].iter() {
+        if d == Ordering::Equal {
+            return Some((*p, None));
+        }
+    }
+
+    let (s1_x, s1_y) = (p1.x as f64 - p0.x as f64, p1.y as f64 - p0.y as f64);
+    let (s2_x, s2_y) = (p3.x as f64 - p2.x as f64, p3.y as f64 - p2.y as f64);
+    let (d_x, d_y) = (p2.x as f64 - p0.x as f64, p2.y as f64 - p0.y as f64);
+    let t = (d_x * s2_y - d_y * s2_x) / (s1_x * s2_y - s1_y * s2_x);
+
+    let clamp = |value: f64, a0: fsize, a1: fsize, b0: fsize, b1: fsize| {
+        let min = a0.min(a1).max(b0.min(b1));
+        let max = a0.max(a1).min(b0.max(b1));
+        (value as fsize).max(min).min(max)
+    };
+
+    Some((Point2D {
+        x: clamp(p0.x as f64 + t * s1_x, p0.x, p1.x, p2.x, p3.x),
+        y: clamp(p0.y as f64 + t * s1_y, p0.y, p1.y, p2.y, p3.y),
+    }, None))
+}
+
+/// Shared sub-segment of two collinear segments, in the point order of `K`
+fn collinear_overlap<K: Kernel>(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D)
+                                -> Option<(Point2D, Option<Point2D>)>
+{
+    let min = |a: &Point2D, b: &Point2D| if K::compare_points(a, b) == Ordering::Greater { *b } else { *a };
+    let max = |a: &Point2D, b: &Point2D| if K::compare_points(a, b) == Ordering::Greater { *a } else { *b };
+
+    let start = max(&min(p0, p1), &min(p2, p3));
+    let end = min(&max(p0, p1), &max(p2, p3));
+
+    match K::compare_points(&start, &end) {
+        Ordering::Less => Some((start, Some(end))),
+        Ordering::Equal => Some((start, None)),
+        Ordering::Greater => None,
+    }
+}
+
+#[test]
+fn test_kernels() {
+    let p = |x: fsize, y: fsize| Point2D { x: x, y: y };
+
+    fn check<K: Kernel>(p: &dyn Fn(fsize, fsize) -> Point2D) {
+        assert_eq!(K::orient2d(&p(0.0, 0.0), &p(1.0, 0.0), &p(0.0, 1.0)), Ordering::Greater);
+        assert_eq!(K::orient2d(&p(0.0, 0.0), &p(0.0, 1.0), &p(1.0, 0.0)), Ordering::Less);
+        assert_eq!(K::orient2d(&p(0.0, 0.0), &p(1.0, 1.0), &p(3.0, 3.0)), Ordering::Equal);
+
+        // crossing, touching, collinear overlap and parallel
+        assert_eq!(K::intersect(&p(0.0, 0.0), &p(2.0, 2.0), &p(0.0, 2.0), &p(2.0, 0.0)), Some((p(1.0, 1.0), None)));
+        assert_eq!(K::intersect(&p(0.0, 0.0), &p(2.0, 0.0), &p(1.0, 0.0), &p(1.0, 1.0)), Some((p(1.0, 0.0), None)));
+        assert_eq!(K::intersect(&p(0.0, 0.0), &p(2.0, 0.0), &p(3.0, 0.0), &p(1.0, 0.0)), Some((p(1.0, 0.0), Some(p(2.0, 0.0)))));
+        assert_eq!(K::intersect(&p(0.0, 0.0), &p(2.0, 0.0), &p(0.0, 1.0), &p(2.0, 1.0)), None);
+        assert_eq!(K::intersect(&p(0.0, 0.0), &p(1.0, 0.0), &p(2.0, -1.0), &p(2.0, 1.0)), None);
+    }
+
+    check::<FloatKernel>(&p);
+    check::<RobustKernel>(&p);
+    check::<ExactKernel>(&p);
+
+    // the classic near-collinear grid from Shewchuk's and Kettner's papers:
+    // the robust kernels agree on all of these points, and with the perturbation
+    let p0 = p(12.0, 12.0);
+    let p1 = p(24.0, 24.0);
+    let ulp = fsize::EPSILON / 2.0;
+    for i in 0..32 {
+        for j in 0..32 {
+            let o = p(0.5 + i as fsize * ulp, 0.5 + j as fsize * ulp);
+            assert_eq!(RobustKernel::orient2d(&p0, &p1, &o), ExactKernel::orient2d(&p0, &p1, &o));
+            assert_eq!(ExactKernel::orient2d(&p0, &p1, &o), j.cmp(&i));
+        }
+    }
+}
@@ -7,6 +7,9 @@
 //! - Subtract (A_NOT_B): Resulting polygon(s) contains A, except for the Intersection of A and B
 //! - Cut (CUT): Resulting polygon(s) contains all possible intersections of A and B,
 //!   however the nodes don't have to be a valid polygon
+//!
+//! All public types are `Send + Sync`, so the operations can run on
+//! several threads at once.
 
 
 #![allow(dead_code)]
@@ -15,59 +18,219 @@
 #![warn(unused_features)]
 #![allow(unused_unsafe)]
 
-// NOTE: These features are only because the `std::collections::BTreeSet`
-// does not allow to immediately construct an iterator to the last inserted element,
-// which is crucial for performance. For now, this repository contains a fork of the
-// `std::collections::BTreeSet`, licensed under the Apache/MIT license
-// (see the main Rust repository) for details.
-
-// Tracking RFC: https://github.com/rust-lang/rfcs/pull/1194
-
-// However, I need this NOW and not in two years, so this should explain the fork.
-// A BTreeSet is just a BTreeMap with the value set to `()`, so an implementation
-// is fairly trivial.
-
-// Collections crate for Cursor-like behaviour for RBTrees
+#[cfg(feature = "geojson")]
+extern crate geojson;
+#[cfg(feature = "log")]
+#[cfg_attr(feature = "trace", macro_use)]
+extern crate log;
+#[cfg(feature = "lyon")]
+extern crate lyon_path;
+#[cfg(feature = "serde")]
 #[macro_use]
-extern crate intrusive_collections;
+extern crate serde;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+#[cfg(feature = "gpu")]
+extern crate wgpu;
+#[cfg(feature = "gpu")]
+extern crate pollster;
+#[cfg(feature = "testing")]
+extern crate proptest;
+#[cfg(feature = "approx")]
+extern crate approx;
+#[cfg(feature = "euclid")]
+extern crate euclid;
+#[cfg(feature = "glam")]
+extern crate glam;
+#[cfg(feature = "nalgebra")]
+extern crate nalgebra;
 
 #[cfg(not(use_double_precision))]
 pub type fsize = f32;
 #[cfg(use_double_precision)]
 pub type fsize = f64;
 
-macro_rules! inner {
-    ($e:expr) => ((unsafe { &*$e.inner.get() }))
+// Emits a trace event with the target `polyclip::sweep` if the `trace`
+// feature is enabled, the arguments are not evaluated otherwise. Used by the
+// intersection sweep and the arrangement.
+#[cfg(feature = "trace")]
+macro_rules! sweep_trace {
+    ($($arg:tt)+) => (trace!(target: "polyclip::sweep", $($arg)+))
 }
 
-macro_rules! inner_mut {
-    ($e:expr) => ((unsafe { &mut *$e.inner.get() }))
+#[cfg(not(feature = "trace"))]
+macro_rules! sweep_trace {
+    ($($arg:tt)+) => (())
 }
 
-macro_rules! other {
-    ($e:expr) => (unsafe { &(*(*inner!($e).other).inner.get()) })
+// Counts the call of a step of an operation and the time spent in it if the
+// `profiling` feature is enabled, see `profile::ProfileReport`
+#[cfg(feature = "profiling")]
+macro_rules! profile {
+    ($timing:ident, $e:expr) => (::profile::measure(|report| &mut report.$timing, || $e))
 }
 
-macro_rules! other_mut {
-    ($e:expr) => (unsafe { &mut (*(*inner!($e).other).inner.get()) })
+#[cfg(not(feature = "profiling"))]
+macro_rules! profile {
+    ($timing:ident, $e:expr) => ($e)
 }
 
+#[cfg(feature = "approx")]
+mod approx_eq;
+mod arrangement;
+mod backend;
+mod batch;
 mod bbox;
-mod connector;
+mod circle;
+mod clean;
+mod coverage;
+mod densify;
+#[cfg(feature = "dxf")]
+mod dxf;
+mod edges;
+mod error;
+#[cfg(feature = "euclid")]
+mod euclid_io;
+mod event_queue;
+mod fill;
+mod fillet;
+mod flatten;
+mod frozen;
+mod generators;
+#[cfg(feature = "geojson")]
+mod geojson_io;
+#[cfg(feature = "glam")]
+mod glam_io;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod greiner_hormann;
+mod grid;
+mod hash;
+mod hatch;
+mod heal;
+mod incremental;
+mod intersections;
+mod kernel;
+#[cfg(feature = "lyon")]
+mod lyon_io;
+mod multi_polygon;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_io;
+mod ops;
+mod options;
+mod orient;
+mod overlay;
 mod point;
-mod sweep_event;
-mod point_chain;
+mod precision;
 mod polygon;
+#[cfg(feature = "profiling")]
+mod profile;
+mod provenance;
+mod query;
+mod raster;
+mod raycast;
+mod reference;
+mod relate;
+mod rects;
+mod scanbeam;
 mod segment;
+mod shapes;
+mod simplify;
+mod smooth;
+mod stream;
+mod svg;
+mod sweep_debug;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod tiles;
+mod transform;
+mod triangulate;
 mod utils;
+mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod wkt;
 
-pub use point::{Point2D, line_intersect};
-pub use polygon::{Polygon, WindingOrder};
+pub use point::{Point2D, PointKey, line_intersect, line_intersect_eps};
+pub use kernel::{Kernel, DefaultKernel, FloatKernel, RobustKernel, ExactKernel};
+pub use polygon::{Polygon, WindingOrder, BoolOpType, FillRule};
+pub use precision::{TypedPolygon, Polygon32, Polygon64, PolygonI64};
+pub use frozen::{FrozenPolygon, PolygonBuilder};
+pub use options::{ClipOptions, ClipAlgorithm, ClipStats, IntersectionCallback};
+pub use backend::ClipBackend;
+pub use error::{ClipError, ClipWarning, VerificationError};
+pub use batch::{clip_many, xor_all};
+pub use rects::union_rects;
+pub use scanbeam::clip_i64;
+pub use reference::reference_clip;
+pub use verify::{verify_result, check_partition, PartitionDeltas};
+pub use multi_polygon::MultiPolygon;
+pub use grid::CellIndex;
+pub use generators::PolygonGenerator;
+pub use tiles::TileId;
+pub use coverage::{CoverageReport, CoverageOverlap};
 pub use bbox::Bbox;
+pub use edges::Edges;
+pub use segment::Segment;
+pub use arrangement::{Arrangement, HalfEdge, Face};
+#[cfg(feature = "profiling")]
+pub use profile::{ProfileReport, Timing, take_profile_report};
+pub use provenance::{EdgeRef, EdgeSource, VertexSource, TracedPolygon};
+pub use intersections::{IntersectionEvent, find_intersections};
+pub use orient::{orient, fix_winding};
+pub use overlay::overlay_identity;
+pub use incremental::IncrementalClip;
+pub use triangulate::{TessellationOptions, earcut};
+pub use svg::{svg_debug_document, write_svg_debug};
+pub use sweep_debug::SweepSnapshot;
+#[cfg(feature = "gpu")]
+pub use gpu::GpuClipper;
+pub use transform::transform_point;
 pub use utils::{calculate_signed_area2,
                 calculate_signed_area3,
                 calculate_bounding_box,
-                calculate_winding_order};
+                calculate_winding_order,
+                try_calculate_winding_order,
+                distance_point_segment,
+                distance_segment_segment,
+                quantize,
+                dequantize};
 
 // TODO: Replace all (*thing.other_vec)[thing.other_idx]
 // with (*thing.other_vec).get_unchecked(thing.other_idx)
+
+#[test]
+fn test_public_types_are_send_and_sync() {
+    // results and options can be shared between threads, for example when
+    // the operations run in a thread pool
+    fn assert_send_sync<T: Send + Sync>() { }
+
+    assert_send_sync::<Point2D>();
+    assert_send_sync::<Polygon>();
+    assert_send_sync::<FrozenPolygon>();
+    assert_send_sync::<MultiPolygon>();
+    assert_send_sync::<Bbox>();
+    assert_send_sync::<Segment>();
+    assert_send_sync::<ClipOptions>();
+    assert_send_sync::<ClipError>();
+    assert_send_sync::<ClipWarning>();
+    assert_send_sync::<Arrangement>();
+    assert_send_sync::<TracedPolygon>();
+    assert_send_sync::<IntersectionEvent>();
+    assert_send_sync::<SweepSnapshot>();
+    assert_send_sync::<CoverageReport>();
+    assert_send_sync::<Edges>();
+    #[cfg(feature = "gpu")]
+    assert_send_sync::<GpuClipper>();
+
+    let clip = ::std::sync::Arc::new(MultiPolygon::new(vec![Polygon::rect(1.0, 1.0, 2.0, 2.0)]));
+    let threads: Vec<_> = (0..4).map(|i| {
+        let clip = clip.clone();
+        ::std::thread::spawn(move || {
+            let subject = MultiPolygon::new(vec![Polygon::rect(i as fsize, 0.0, 2.0, 2.0)]);
+            subject.intersection(&*clip).polygons.iter().map(|p| p.area()).sum::<fsize>()
+        })
+    }).collect();
+    let areas: Vec<fsize> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+    assert_eq!(areas, vec![1.0, 2.0, 1.0, 0.0]);
+}
@@ -0,0 +1,126 @@
+//! Conversions from and to `lyon_path` (requires the `lyon` feature)
+
+use lyon_path::{Path, PathEvent};
+use lyon_path::iterator::PathIterator;
+#[cfg(not(feature = "euclid"))]
+use lyon_path::math::Point;
+use polygon::Polygon;
+use multi_polygon::MultiPolygon;
+use Point2D;
+
+// `Point` is `euclid::Point2D<f32, UnknownUnit>`, which the generic
+// conversions of the `euclid` feature already cover
+#[cfg(not(feature = "euclid"))]
+impl From<Point> for Point2D {
+    fn from(p: Point) -> Self {
+        Point2D { x: p.x as ::fsize, y: p.y as ::fsize }
+    }
+}
+
+#[cfg(not(feature = "euclid"))]
+impl From<Point2D> for Point {
+    fn from(p: Point2D) -> Self {
+        ::lyon_path::math::point(p.x as f32, p.y as f32)
+    }
+}
+
+impl Polygon {
+
+    /// Flattens a lyon path with the given tolerance and returns one polygon per subpath
+    ///
+    /// Subpaths that are closed in the path are closed polygons, all other subpaths
+    /// have `is_closed` set to false. Like in SVG, holes are determined by the fill rule,
+    /// so `is_hole` is never set - use `orient` to classify the contours.
+    pub fn from_lyon_path(path: &Path, tolerance: f32) -> Vec<Self> {
+        let mut polygons = Vec::new();
+        let mut nodes = Vec::<Point2D>::new();
+
+        for event in path.iter().flattened(tolerance) {
+            match event {
+                PathEvent::Begin { at } => {
+                    nodes.clear();
+                    nodes.push(at.into());
+                },
+                PathEvent::Line { to, .. } => {
+                    nodes.push(to.into());
+                },
+                PathEvent::End { close, .. } => {
+                    let mut subpath = ::std::mem::replace(&mut nodes, Vec::new());
+                    if subpath.len() > 1 && subpath.first() == subpath.last() {
+                        subpath.pop();
+                    }
+                    if subpath.len() < 2 {
+                        continue;
+                    }
+                    polygons.push(Polygon::from_parts(subpath, false, close));
+                },
+                // a flattened path only contains line segments
+                _ => { },
+            }
+        }
+
+        polygons
+    }
+
+    /// Converts the polygon into a lyon path with a single subpath
+    pub fn to_lyon_path(&self) -> Path {
+        let mut builder = Path::builder();
+        add_to_builder(&mut builder, self);
+        builder.build()
+    }
+}
+
+impl MultiPolygon {
+
+    /// Flattens a lyon path, see `Polygon::from_lyon_path`
+    pub fn from_lyon_path(path: &Path, tolerance: f32) -> Self {
+        MultiPolygon::new(Polygon::from_lyon_path(path, tolerance))
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Converts all polygons into one lyon path, with one subpath per polygon
+    ///
+    /// Holes are separate subpaths, so the path should be tessellated with the
+    /// even-odd fill rule (or oriented with `orient` and filled with non-zero).
+    pub fn to_lyon_path(&self) -> Path {
+        let mut builder = Path::builder();
+        for polygon in &self.polygons {
+            add_to_builder(&mut builder, polygon);
+        }
+        builder.build()
+    }
+}
+
+fn add_to_builder(builder: &mut ::lyon_path::path::Builder, polygon: &Polygon) {
+    let mut nodes = polygon.nodes.iter();
+    let first = match nodes.next() {
+        Some(p) => p,
+        None => return,
+    };
+    builder.begin((*first).into());
+    for p in nodes {
+        builder.line_to((*p).into());
+    }
+    builder.end(polygon.is_closed);
+}
+
+#[test]
+fn test_lyon_roundtrip() {
+    use lyon_path::math::point;
+
+    let mut builder = Path::builder();
+    builder.begin(point(0.0, 0.0));
+    builder.quadratic_bezier_to(point(5.0, 10.0), point(10.0, 0.0));
+    builder.close();
+    let path = builder.build();
+
+    let polygons = Polygon::from_lyon_path(&path, 0.01);
+    assert_eq!(polygons.len(), 1);
+    assert!(polygons[0].nodes.len() > 3);
+    assert!(polygons[0].is_closed);
+
+    let back = MultiPolygon::new(polygons.clone()).to_lyon_path();
+    assert_eq!(Polygon::from_lyon_path(&back, 0.01)[0].nodes, polygons[0].nodes);
+}
@@ -0,0 +1,336 @@
+use arrangement::Arrangement;
+use error::{ClipError, ClipWarning};
+use std::borrow::Cow;
+use options::{ClipOptions, ClipAlgorithm, ClipStats, OperationGuard};
+use polygon::{Polygon, BoolOpType};
+use edges::Edges;
+use precision::PolygonI64;
+use {Point2D, Bbox, fsize};
+
+/// A set of polygons, for example the result of a boolean operation
+///
+/// Holes are stored as regular polygons with `is_hole` set to true,
+/// the same way the boolean operations return them.
+///
+/// The set can carry an attribute of type `T` (for example the feature
+/// properties of a GIS layer), which is passed through by operations like
+/// `overlay_identity`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiPolygon<T = ()> {
+    /// The polygons (outer rings and holes) of this set
+    pub polygons: Vec<Polygon>,
+    /// User-defined attribute of the set
+    pub data: T,
+}
+
+impl MultiPolygon {
+
+    /// Creates a new MultiPolygon from a list of polygons
+    pub fn new(polygons: Vec<Polygon>) -> Self {
+        Self {
+            polygons: polygons,
+            data: (),
+        }
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Creates a new MultiPolygon from a list of polygons and an attribute
+    pub fn with_data(polygons: Vec<Polygon>, data: T) -> Self {
+        Self {
+            polygons: polygons,
+            data: data,
+        }
+    }
+
+    /// Brings the polygons into a canonical order and removes duplicates, see
+    /// `sort_canonical`. The boolean operations already return their results
+    /// in this order.
+    pub fn sort_canonical(&mut self) {
+        sort_canonical(&mut self.polygons);
+    }
+
+    /// Number of polygons (outer rings and holes) in this set
+    pub fn count(&self) -> usize {
+        self.polygons.len()
+    }
+
+    /// Iterates over the polygons together with their cached bounding boxes
+    /// (`None` for empty polygons)
+    pub fn members(&self) -> impl Iterator<Item = (&Polygon, Option<Bbox>)> {
+        self.polygons.iter().map(|p| (p, p.bbox))
+    }
+
+    /// Area covered by the set: the area of the outer rings minus the area of
+    /// the holes. Overlapping outer rings are counted multiple times.
+    pub fn total_area(&self) -> fsize {
+        self.polygons.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum()
+    }
+
+    /// Bounding box of all polygons, `None` if the set has no vertices
+    pub fn combined_bbox(&self) -> Option<Bbox> {
+        self.polygons.iter().filter_map(|p| p.bbox).fold(None, |acc, bbox| match acc {
+            Some(acc) => Some(bbox.union(&acc)),
+            None => Some(bbox),
+        })
+    }
+
+    /// Center of mass of the covered area, with the holes subtracted. Returns
+    /// `None` if the set has no area.
+    pub fn centroid(&self) -> Option<Point2D> {
+        let mut area = 0.0;
+        let mut moment = Point2D { x: 0.0, y: 0.0 };
+
+        for polygon in self.polygons.iter() {
+            let (ring_area, ring_centroid) = match ring_centroid(&polygon.nodes) {
+                Some(c) => c,
+                None => continue,
+            };
+            let weight = if polygon.is_hole { -ring_area } else { ring_area };
+            area += weight;
+            moment = moment + ring_centroid * weight;
+        }
+
+        if area == 0.0 { None } else { Some(moment / area) }
+    }
+
+    /// Groups the polygons into exteriors and the holes that lie inside of them,
+    /// based on the `is_hole` flag. Holes that are not contained in any exterior
+    /// are returned as exteriors, so that no geometry is lost.
+    pub(crate) fn exteriors_with_holes(&self) -> Vec<(&Polygon, Vec<&Polygon>)> {
+
+        let mut groups: Vec<(&Polygon, Vec<&Polygon>)> = self.polygons.iter()
+            .filter(|p| !p.is_hole)
+            .map(|p| (p, Vec::new()))
+            .collect();
+
+        for hole in self.polygons.iter().filter(|p| p.is_hole) {
+            match groups.iter().position(|&(exterior, _)| ::utils::is_ring_inside_ring(&hole.nodes, &exterior.nodes)) {
+                Some(idx) => groups[idx].1.push(hole),
+                None => groups.push((hole, Vec::new())),
+            }
+        }
+
+        groups
+    }
+}
+
+impl<T: Clone> MultiPolygon<T> {
+
+    // The results of the boolean operations carry the data of `self`, so that
+    // for example clipping an attributed feature keeps its attributes. To keep
+    // the data of both sets, use `overlay_identity`.
+
+    /// Calculates the area that is covered by either set
+    pub fn union<U>(&self, other: &MultiPolygon<U>) -> Self {
+        self.clip(other, BoolOpType::Union, &ClipOptions::default())
+    }
+
+    /// Calculates the area that is covered by both sets
+    pub fn intersection<U>(&self, other: &MultiPolygon<U>) -> Self {
+        self.clip(other, BoolOpType::Intersection, &ClipOptions::default())
+    }
+
+    /// Calculates the area that is covered by this set, but not by `other`
+    pub fn difference<U>(&self, other: &MultiPolygon<U>) -> Self {
+        self.clip(other, BoolOpType::Difference, &ClipOptions::default())
+    }
+
+    /// Calculates the area that is covered by exactly one of the sets
+    pub fn xor<U>(&self, other: &MultiPolygon<U>) -> Self {
+        self.clip(other, BoolOpType::Xor, &ClipOptions::default())
+    }
+
+    /// Runs the boolean operation `operation_type` with the given options
+    ///
+    /// The operation is computed on the arrangement of both sets, so holes and
+    /// overlapping members are supported. If the operation is cancelled, the
+    /// result is empty - use `try_clip` to tell this apart from an empty result.
+    pub fn clip<U>(&self, other: &MultiPolygon<U>, operation_type: BoolOpType, options: &ClipOptions)
+                   -> Self
+    {
+        self.try_clip(other, operation_type, options)
+            .unwrap_or_else(|_| MultiPolygon::with_data(Vec::new(), self.data.clone()))
+    }
+
+    /// Same as `clip`, but returns an error if the operation was aborted
+    pub fn try_clip<U>(&self, other: &MultiPolygon<U>, operation_type: BoolOpType, options: &ClipOptions)
+                       -> Result<Self, ClipError>
+    {
+        self.clip_with_warnings(other, operation_type, options).map(|(result, _)| result)
+    }
+
+    /// Same as `try_clip`, but also returns the warnings about degenerate input
+    /// rings, which are dropped before the operation (see `Polygon::is_degenerate`),
+    /// and about the result if `ClipOptions::verify` is set
+    pub fn clip_with_warnings<U>(&self, other: &MultiPolygon<U>, operation_type: BoolOpType, options: &ClipOptions)
+                                 -> Result<(Self, Vec<ClipWarning>), ClipError>
+    {
+        self.clip_guarded(other, operation_type, options, &mut options.guard())
+    }
+
+    /// Same as `try_clip`, but also returns what the operation did: the number
+    /// of events, intersections and subdivisions, the size of the result and
+    /// the time it took
+    pub fn clip_with_stats<U>(&self, other: &MultiPolygon<U>, operation_type: BoolOpType, options: &ClipOptions)
+                              -> Result<(Self, ClipStats), ClipError>
+    {
+        let mut guard = options.timed_guard();
+        let (result, _) = self.clip_guarded(other, operation_type, options, &mut guard)?;
+        let stats = guard.stats(&result.polygons);
+        Ok((result, stats))
+    }
+
+    /// Runs `clip_with_warnings` with the `guard`
+    fn clip_guarded<U>(&self, other: &MultiPolygon<U>, operation_type: BoolOpType, options: &ClipOptions,
+                       guard: &mut OperationGuard) -> Result<(Self, Vec<ClipWarning>), ClipError>
+    {
+        let rings = self.polygons.iter().chain(other.polygons.iter());
+        ::utils::check_finite(rings.flat_map(|p| p.nodes.iter()))?;
+
+        let mut warnings = Vec::new();
+        let subject = drop_degenerate(&self.polygons, 0, &mut warnings);
+        let clip = drop_degenerate(&other.polygons, self.polygons.len(), &mut warnings);
+
+        let rectilinear = options.algorithm == ClipAlgorithm::Rectilinear &&
+                          subject.iter().chain(clip.iter()).all(Polygon::is_rectilinear);
+        let scanbeam = options.algorithm == ClipAlgorithm::Scanbeam &&
+                       subject.iter().chain(clip.iter()).all(Polygon::has_integer_coordinates);
+        let mut polygons = options.filter_output(if rectilinear {
+            let subject_rings: Vec<&[Point2D]> = subject.iter().map(|p| &p.nodes[..]).collect();
+            let clip_rings: Vec<&[Point2D]> = clip.iter().map(|p| &p.nodes[..]).collect();
+            ::rects::clip_rectilinear(&subject_rings, &clip_rings, operation_type, guard)?
+        } else if scanbeam {
+            let subject: Vec<PolygonI64> = subject.iter().map(PolygonI64::from).collect();
+            let clip: Vec<PolygonI64> = clip.iter().map(PolygonI64::from).collect();
+            ::scanbeam::clip_scanbeam(&subject, &clip, operation_type, guard)?.into_iter().map(Polygon::from).collect()
+        } else {
+            clip_arrangement(&subject, &clip, operation_type, options, guard)?
+        });
+        options.check_output_size(&polygons)?;
+        sort_canonical(&mut polygons);
+        if options.verify {
+            warnings.extend(::reference::verify(&subject, &clip, operation_type, &polygons));
+        }
+        Ok((MultiPolygon::with_data(polygons, self.data.clone()), warnings))
+    }
+}
+
+/// Runs the boolean operation on two sets of rings by building their
+/// arrangement, without the output filters
+pub(crate) fn clip_arrangement(subject: &[Polygon], clip: &[Polygon], operation_type: BoolOpType,
+                               options: &ClipOptions, guard: &mut OperationGuard) -> Result<Vec<Polygon>, ClipError>
+{
+    let arrangement = Arrangement::with_options(subject, clip, options, guard)?;
+    Ok(match operation_type {
+        BoolOpType::Union => arrangement.extract(|a, b| a || b),
+        BoolOpType::Intersection => arrangement.extract(|a, b| a && b),
+        BoolOpType::Difference => arrangement.extract(|a, b| a && !b),
+        BoolOpType::Xor => arrangement.extract(|a, b| a != b),
+    })
+}
+
+/// Absolute area and centroid of a ring, `None` if it has no area
+fn ring_centroid(nodes: &[Point2D]) -> Option<(fsize, Point2D)> {
+    let mut signed_area = 0.0;
+    let mut sum = Point2D { x: 0.0, y: 0.0 };
+
+    for (p0, p1) in Edges::new(nodes, true) {
+        let cross = p0.cross(&p1);
+        signed_area += cross;
+        sum = sum + (p0 + p1) * cross;
+    }
+
+    if signed_area == 0.0 {
+        None
+    } else {
+        // the signs of the sum and the area cancel out
+        Some(((signed_area / 2.0).abs(), sum / (3.0 * signed_area)))
+    }
+}
+
+/// Removes the degenerate rings and records a warning for each of them. The
+/// rings are numbered starting at `first_ring`. Only copies the rings if one
+/// of them has to be removed.
+fn drop_degenerate<'a>(polygons: &'a [Polygon], first_ring: usize, warnings: &mut Vec<ClipWarning>) -> Cow<'a, [Polygon]> {
+    let old_len = warnings.len();
+    warnings.extend(polygons.iter().enumerate().filter_map(|(idx, p)| p.degenerate_warning(first_ring + idx)));
+    if warnings.len() == old_len {
+        Cow::Borrowed(polygons)
+    } else {
+        Cow::Owned(polygons.iter().filter(|p| !p.is_degenerate()).cloned().collect())
+    }
+}
+
+/// Rotates every closed ring so that it starts at its smallest vertex (keeping
+/// its orientation), sorts the rings by their nodes and removes duplicate rings
+///
+/// This makes the output of an operation independent of the order in which the
+/// contours were discovered, so results are stable across runs and platforms.
+pub(crate) fn sort_canonical(polygons: &mut Vec<Polygon>) {
+    for polygon in polygons.iter_mut().filter(|p| p.is_closed) {
+        let start = (0..polygon.nodes.len()).min_by_key(|i| polygon.nodes[*i]).unwrap_or(0);
+        polygon.nodes.rotate_left(start);
+        if let Some(ref mut measures) = polygon.measures {
+            if measures.len() == polygon.nodes.len() {
+                measures.rotate_left(start);
+            }
+        }
+    }
+
+    polygons.sort_by(|a, b| {
+        a.nodes.cmp(&b.nodes)
+            .then_with(|| a.is_hole.cmp(&b.is_hole))
+            .then_with(|| a.is_closed.cmp(&b.is_closed))
+    });
+    polygons.dedup_by(|a, b| a.nodes == b.nodes && a.is_hole == b.is_hole && a.is_closed == b.is_closed);
+}
+
+#[test]
+fn test_clip_with_warnings() {
+    let a = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 4.0, 4.0), Polygon::from(vec![(1.0, 1.0), (3.0, 3.0)])]);
+    let b = MultiPolygon::new(vec![Polygon::from(vec![(0.0, 2.0), (2.0, 2.0), (4.0, 2.0)]), Polygon::rect(2.0, 2.0, 4.0, 4.0)]);
+
+    let (result, warnings) = a.clip_with_warnings(&b, BoolOpType::Intersection, &ClipOptions::default()).unwrap();
+    assert_eq!(warnings, vec![ClipWarning::TooFewVertices { ring: 1 }, ClipWarning::ZeroArea { ring: 2 }]);
+    assert_eq!(result.polygons.len(), 1);
+    assert_eq!(result.polygons[0].area(), 4.0);
+}
+
+#[test]
+fn test_aggregate_measures() {
+    let multi = MultiPolygon::new(vec![
+        Polygon::rect(0.0, 0.0, 4.0, 4.0),
+        Polygon { is_hole: true, .. Polygon::rect(0.0, 0.0, 2.0, 2.0) },
+        Polygon::rect(6.0, 0.0, 2.0, 4.0),
+    ]);
+
+    assert_eq!(multi.count(), 3);
+    assert_eq!(multi.total_area(), 20.0);
+    assert_eq!(multi.combined_bbox(), Some(::Bbox::new(0.0, 0.0, 8.0, 4.0)));
+    assert_eq!(multi.members().filter_map(|(_, bbox)| bbox).map(|b| b.area()).sum::<fsize>(), 28.0);
+
+    // (16 * (2, 2) - 4 * (1, 1) + 8 * (7, 2)) / 20
+    let centroid = multi.centroid().unwrap();
+    assert!((centroid.x - 4.2).abs() < 1e-5 && (centroid.y - 2.2).abs() < 1e-5);
+
+    assert_eq!(MultiPolygon::new(Vec::new()).combined_bbox(), None);
+    assert_eq!(MultiPolygon::new(Vec::new()).centroid(), None);
+}
+
+#[test]
+fn test_sort_canonical() {
+    let triangle = Polygon::from(vec![(1.0, 1.0), (0.0, 0.0), (2.0, 0.0)]);
+    let rotated = Polygon::from(vec![(2.0, 0.0), (1.0, 1.0), (0.0, 0.0)]);
+    let square = Polygon::rect(-5.0, -5.0, 1.0, 1.0);
+
+    let mut multi = MultiPolygon::new(vec![triangle, square.clone(), rotated]);
+    multi.sort_canonical();
+
+    assert_eq!(multi.polygons.len(), 2);
+    assert_eq!(multi.polygons[0].nodes, square.nodes);
+    assert_eq!(multi.polygons[1].nodes[0], ::Point2D { x: 0.0, y: 0.0 });
+    assert_eq!(multi.polygons[1].nodes[1], ::Point2D { x: 2.0, y: 0.0 });
+}
@@ -0,0 +1,30 @@
+//! Conversions from and to `nalgebra` (requires the `nalgebra` feature)
+//!
+//! Slices of points are converted with `Point2D::from_slice` and `Point2D::to_vec`.
+
+use nalgebra::Point2;
+use {Point2D, fsize};
+
+impl From<Point2<fsize>> for Point2D {
+    fn from(p: Point2<fsize>) -> Self {
+        Point2D { x: p.x, y: p.y }
+    }
+}
+
+impl From<Point2D> for Point2<fsize> {
+    fn from(p: Point2D) -> Self {
+        Point2::new(p.x, p.y)
+    }
+}
+
+#[test]
+fn test_nalgebra_conversions() {
+    let p = Point2::new(1.5, -2.0);
+    assert_eq!(Point2D::from(p), Point2D { x: 1.5, y: -2.0 });
+    assert_eq!(Point2::from(Point2D::from(p)), p);
+
+    let points = [Point2::new(0.0, 0.0), Point2::new(3.0, 1.0)];
+    let converted = Point2D::from_slice(&points);
+    assert_eq!(converted[1], Point2D { x: 3.0, y: 1.0 });
+    assert_eq!(Point2D::to_vec::<Point2<fsize>>(&converted), points.to_vec());
+}
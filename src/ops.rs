@@ -0,0 +1,84 @@
+//! Operator overloads for the boolean operations
+//!
+//! `&a | &b` is the union, `&a & &b` the intersection, `&a - &b` the
+//! difference and `&a ^ &b` the exclusive-or of `a` and `b`. The operators
+//! run `try_clip` with the default options, so they return an error instead
+//! of an empty set if an input has a non-finite coordinate.
+
+use std::ops::{BitOr, BitAnd, Sub, BitXor};
+use error::ClipError;
+use multi_polygon::MultiPolygon;
+use options::ClipOptions;
+use polygon::{Polygon, BoolOpType};
+
+macro_rules! impl_polygon_op {
+    ($trait_name:ident, $trait_fn:ident, $operation_type:expr) => {
+        impl<'a, 'b> $trait_name<&'b Polygon> for &'a Polygon {
+            type Output = Result<MultiPolygon, ClipError>;
+
+            fn $trait_fn(self, other: &'b Polygon) -> Result<MultiPolygon, ClipError> {
+                self.try_clip(other, $operation_type, &ClipOptions::default()).map(MultiPolygon::new)
+            }
+        }
+    }
+}
+
+macro_rules! impl_multi_polygon_op {
+    ($trait_name:ident, $trait_fn:ident, $operation_type:expr) => {
+        impl<'a, 'b, T: Clone, U> $trait_name<&'b MultiPolygon<U>> for &'a MultiPolygon<T> {
+            type Output = Result<MultiPolygon<T>, ClipError>;
+
+            fn $trait_fn(self, other: &'b MultiPolygon<U>) -> Result<MultiPolygon<T>, ClipError> {
+                self.try_clip(other, $operation_type, &ClipOptions::default())
+            }
+        }
+    }
+}
+
+impl_polygon_op!(BitOr, bitor, BoolOpType::Union);
+impl_polygon_op!(BitAnd, bitand, BoolOpType::Intersection);
+impl_polygon_op!(Sub, sub, BoolOpType::Difference);
+impl_polygon_op!(BitXor, bitxor, BoolOpType::Xor);
+
+impl_multi_polygon_op!(BitOr, bitor, BoolOpType::Union);
+impl_multi_polygon_op!(BitAnd, bitand, BoolOpType::Intersection);
+impl_multi_polygon_op!(Sub, sub, BoolOpType::Difference);
+impl_multi_polygon_op!(BitXor, bitxor, BoolOpType::Xor);
+
+#[test]
+fn test_multi_polygon_operators() {
+    use {Point2D, fsize};
+
+    let square = |x: fsize, y: fsize| MultiPolygon::new(vec![Polygon::from_parts(vec![
+        Point2D { x: x, y: y },
+        Point2D { x: x + 2.0, y: y },
+        Point2D { x: x + 2.0, y: y + 2.0 },
+        Point2D { x: x, y: y + 2.0 },
+    ], false, true)]);
+
+    let a = square(0.0, 0.0);
+    let b = square(1.0, 1.0);
+
+    let and = (&a & &b).unwrap();
+    assert_eq!(and.polygons.len(), 1);
+    assert_eq!(and.polygons[0].bounding_box(), ::Bbox::new(1.0, 1.0, 2.0, 2.0));
+
+    let or = (&a | &b).unwrap();
+    assert_eq!(or.polygons.len(), 1);
+    assert_eq!(or.polygons[0].nodes.len(), 8);
+
+    assert_eq!((&a - &b).unwrap().polygons[0].nodes.len(), 6);
+    assert_eq!((&a ^ &b).unwrap().polygons.len(), 2);
+
+    // the result keeps the data of the left-hand side
+    let feature = MultiPolygon::with_data(a.polygons.clone(), "parcel 12");
+    assert_eq!((&feature - &b).unwrap().data, "parcel 12");
+
+    // the operators of single polygons give the same results
+    let (p, q) = (&a.polygons[0], &b.polygons[0]);
+    assert_eq!((p | q).unwrap().polygons, or.polygons);
+
+    // invalid input is an error, not an empty result
+    let broken = Polygon::from(vec![(0.0, 0.0), (fsize::NAN, 1.0), (1.0, 1.0)]);
+    assert_eq!((&broken & p).err(), Some(ClipError::NonFiniteCoordinate { index: 1 }));
+}
@@ -0,0 +1,477 @@
+//! Options for the boolean operations
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use backend::ClipBackend;
+use error::ClipError;
+use polygon::Polygon;
+use segment::Segment;
+use {Point2D, fsize};
+
+/// Observer for the intersections found during a boolean operation, see
+/// `ClipOptions::on_intersection`
+pub type IntersectionCallback = dyn Fn(Point2D, &Segment, &Segment) + Send + Sync;
+
+/// Algorithm that computes the boolean operations of two polygons, see
+/// `ClipOptions::algorithm`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClipAlgorithm {
+    /// Overlay of both polygons in an `Arrangement`, which is also used by
+    /// the operations of `MultiPolygon`. Handles self-intersecting rings,
+    /// shared vertices and overlapping edges.
+    Arrangement,
+    /// Greiner–Hormann algorithm, with the extensions of Foster, Hormann and
+    /// Popa for shared vertices and overlapping edges. Both polygons have to
+    /// be simple (no self-intersections). Polygons that only touch are not
+    /// merged by the union. Takes O(n * m) time for n and m vertices, which is
+    /// fast for small polygons with few intersections.
+    GreinerHormann,
+    /// Rectilinear sweep for polygons whose edges are all horizontal or
+    /// vertical (see `Polygon::is_rectilinear`), as common in floor plans and
+    /// circuit boards. The result only has axis-aligned edges and only uses
+    /// coordinates of the input. Falls back to `Arrangement` if an input is
+    /// not rectilinear.
+    Rectilinear,
+    /// Scanbeam sweep on integer coordinates in the style of Vatti's algorithm
+    /// and Clipper, which `PolygonI64` always uses (see `clip_i64`). Selected
+    /// if all coordinates of both inputs are integers (see
+    /// `Polygon::has_integer_coordinates`), falls back to `Arrangement`
+    /// otherwise. The points where edges cross are rounded to integers. An
+    /// open subject (`is_closed` is false) is clipped as a path.
+    Scanbeam,
+}
+
+impl Default for ClipAlgorithm {
+    fn default() -> Self {
+        ClipAlgorithm::Arrangement
+    }
+}
+
+/// What a boolean operation did, see `Polygon::clip_with_stats` and
+/// `MultiPolygon::clip_with_stats`
+///
+/// The counters depend on the algorithm: the arrangement (the default, also
+/// for `Polygon`) and the Greiner–Hormann algorithm count intersections and
+/// subdivisions, the rectilinear sweep only counts its events. The scanbeam
+/// sweep counts its scanlines and the edges it checked for crossings as
+/// events. A `ClipOptions::backend` is only timed.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ClipStats {
+    /// Number of events (or loop iterations) that were processed, the same
+    /// number that `ClipOptions::max_events` limits
+    pub events_processed: usize,
+    /// Number of points where input edges cross or touch, not counting the
+    /// vertices that consecutive edges of a ring share
+    pub intersections_found: usize,
+    /// Number of times an input edge was split
+    pub subdivisions: usize,
+    /// Total number of vertices of the result, after the output filters
+    pub output_vertices: usize,
+    /// Time that the operation took. None on `wasm32-unknown-unknown`, which
+    /// has no clock
+    pub duration: Option<Duration>,
+}
+
+/// Options that control how a boolean operation is performed and post-processed
+///
+/// Use struct update syntax to only set some of the options:
+///
+/// ```rust
+/// # use polyclip::ClipOptions;
+/// let options = ClipOptions { min_output_area: 0.01, .. Default::default() };
+/// ```
+#[derive(Clone, Default)]
+pub struct ClipOptions {
+    /// Result contours with an absolute area below this value are removed.
+    /// Useful to drop the slivers that nearly coincident boundaries produce.
+    /// Open contours are not affected. Default: 0.0 (keep everything)
+    pub min_output_area: fsize,
+    /// Edges shorter than this are collapsed by dropping their end vertex,
+    /// contours that are left with less than three vertices are removed.
+    /// Default: 0.0 (keep everything)
+    pub min_edge_length: fsize,
+    /// Called with every intersection point of two input edges and the two
+    /// edges themselves (as they were in the input, before any subdivision).
+    /// Points where more than two edges meet are reported once per pair.
+    /// Default: None
+    pub on_intersection: Option<Arc<IntersectionCallback>>,
+    /// The operation is aborted with `ClipError::Cancelled` once it runs longer
+    /// than this. Not available on `wasm32-unknown-unknown`, which has no clock.
+    /// Default: None
+    pub timeout: Option<Duration>,
+    /// The operation is aborted with `ClipError::Cancelled` once this is set to
+    /// true, for example from another thread. Default: None
+    pub cancel_token: Option<Arc<AtomicBool>>,
+    /// The operation is aborted with `ClipError::TooManyEvents` once the sweep
+    /// has processed this many events, including the events that are created
+    /// when segments are subdivided at intersections. Near-degenerate inputs
+    /// can create a quadratic number of them. Default: None
+    pub max_events: Option<usize>,
+    /// The operation fails with `ClipError::OutputTooLarge` if the result has
+    /// more vertices than this in total. Default: None
+    pub max_output_vertices: Option<usize>,
+    /// Algorithm of `Polygon::clip` and `Polygon::try_clip`. The operations of
+    /// `MultiPolygon` use its arrangement, except for `ClipAlgorithm::Rectilinear`
+    /// and `ClipAlgorithm::Scanbeam`.
+    /// Default: `ClipAlgorithm::Arrangement`
+    pub algorithm: ClipAlgorithm,
+    /// User-defined algorithm of `Polygon::clip` and `Polygon::try_clip`, which
+    /// replaces `algorithm` if set. The output filters of these options are
+    /// still applied to its results. Default: None
+    pub backend: Option<Arc<dyn ClipBackend>>,
+    /// Compares the result of `clip_with_warnings` with the slow `reference_clip`
+    /// and reports a `ClipWarning::ReferenceMismatch` if they differ. Takes
+    /// O(n²) time, so it is meant for tests and debug builds. Default: false
+    pub verify: bool,
+    /// Guarantees bit-identical results on all platforms, for example for
+    /// lockstep simulations. The arrangement of `MultiPolygon` then orders the
+    /// edges around a vertex with exact predicates instead of `atan2` (whose
+    /// last bit depends on the math library), with ties broken by vertex, and
+    /// the results of a `ClipOptions::backend` are sorted canonically like
+    /// those of the built-in algorithms.
+    ///
+    /// Apart from `atan2`, the operations only use correctly rounded
+    /// arithmetic, which Rust never contracts into fused multiply-adds. The
+    /// shape constructors like `Polygon::circle` use `sin` and `cos` and are not
+    /// covered. Default: false
+    pub deterministic: bool,
+}
+
+impl fmt::Debug for ClipOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ClipOptions")
+            .field("min_output_area", &self.min_output_area)
+            .field("min_edge_length", &self.min_edge_length)
+            .field("on_intersection", &self.on_intersection.as_ref().map(|_| "Fn"))
+            .field("timeout", &self.timeout)
+            .field("cancel_token", &self.cancel_token)
+            .field("max_events", &self.max_events)
+            .field("max_output_vertices", &self.max_output_vertices)
+            .field("algorithm", &self.algorithm)
+            .field("backend", &self.backend.as_ref().map(|_| "ClipBackend"))
+            .field("verify", &self.verify)
+            .field("deterministic", &self.deterministic)
+            .finish()
+    }
+}
+
+impl ClipOptions {
+
+    /// Starts the checks for `timeout` and `cancel_token` of one operation
+    pub(crate) fn guard(&self) -> OperationGuard {
+        OperationGuard {
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+            cancel_token: self.cancel_token.clone(),
+            max_events: self.max_events.unwrap_or(usize::max_value()),
+            ticks: 0,
+            intersections: 0,
+            subdivisions: 0,
+            started: None,
+        }
+    }
+
+    /// Same as `guard`, but also measures the duration for `ClipStats`
+    pub(crate) fn timed_guard(&self) -> OperationGuard {
+        let mut guard = self.guard();
+        // there is no clock on wasm32-unknown-unknown
+        #[cfg(not(target_arch = "wasm32"))]
+        { guard.started = Some(Instant::now()); }
+        guard
+    }
+
+    /// Checks the size of a result against `max_output_vertices`
+    pub(crate) fn check_output_size(&self, polygons: &[Polygon]) -> Result<(), ClipError> {
+        match self.max_output_vertices {
+            Some(max) if polygons.iter().map(|p| p.nodes.len()).sum::<usize>() > max => Err(ClipError::OutputTooLarge),
+            _ => Ok(()),
+        }
+    }
+
+    /// Applies the output filters to the result of a boolean operation
+    pub(crate) fn filter_output(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+
+        if !(self.min_output_area > 0.0) && !(self.min_edge_length > 0.0) {
+            return polygons;
+        }
+
+        polygons.into_iter().filter_map(|polygon| {
+            let polygon = if self.min_edge_length > 0.0 {
+                let nodes = collapse_short_edges(&polygon.nodes, polygon.is_closed, self.min_edge_length);
+                if polygon.is_closed && nodes.len() < 3 {
+                    return None;
+                }
+                polygon.with_nodes(nodes)
+            } else {
+                polygon
+            };
+
+            if polygon.is_closed && polygon.area() < self.min_output_area {
+                None
+            } else {
+                Some(polygon)
+            }
+        }).collect()
+    }
+}
+
+/// Aborts a running operation when it is cancelled or runs out of time
+pub(crate) struct OperationGuard {
+    deadline: Option<Instant>,
+    cancel_token: Option<Arc<AtomicBool>>,
+    max_events: usize,
+    ticks: usize,
+    intersections: usize,
+    subdivisions: usize,
+    started: Option<Instant>,
+}
+
+impl OperationGuard {
+
+    /// Guard that never aborts
+    pub(crate) fn unlimited() -> Self {
+        Self {
+            deadline: None,
+            cancel_token: None,
+            max_events: usize::max_value(),
+            ticks: 0,
+            intersections: 0,
+            subdivisions: 0,
+            started: None,
+        }
+    }
+
+    /// Counts intersections of input edges and subdivisions of input edges
+    #[inline]
+    pub(crate) fn record(&mut self, intersections: usize, subdivisions: usize) {
+        self.intersections += intersections;
+        self.subdivisions += subdivisions;
+    }
+
+    /// The counters of the operation so far, with `output` as its result
+    pub(crate) fn stats(&self, output: &[Polygon]) -> ClipStats {
+        ClipStats {
+            events_processed: self.ticks,
+            intersections_found: self.intersections,
+            subdivisions: self.subdivisions,
+            output_vertices: output.iter().map(|p| p.nodes.len()).sum(),
+            duration: self.started.map(|started| started.elapsed()),
+        }
+    }
+
+    /// Called once per processed event. Only every 64th call actually checks
+    /// the clock and the token, to keep the overhead low.
+    #[inline]
+    pub(crate) fn tick(&mut self) -> Result<(), ClipError> {
+        self.ticks += 1;
+        if self.ticks > self.max_events {
+            return Err(ClipError::TooManyEvents);
+        }
+        if self.ticks % 64 != 1 {
+            return Ok(());
+        }
+
+        let cancelled = self.cancel_token.as_ref().map(|token| token.load(Ordering::Relaxed)).unwrap_or(false);
+        let timed_out = self.deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false);
+        if cancelled || timed_out { Err(ClipError::Cancelled) } else { Ok(()) }
+    }
+}
+
+/// Drops every vertex that is closer than `min_length` to the previously kept vertex
+fn collapse_short_edges(nodes: &[::Point2D], is_closed: bool, min_length: fsize) -> Vec<::Point2D> {
+    let mut kept = Vec::with_capacity(nodes.len());
+    for (idx, node) in nodes.iter().enumerate() {
+        let is_last = idx + 1 == nodes.len();
+        match kept.last() {
+            // the end point of an open chain is always kept
+            Some(last) if node.dist(last) < min_length && !(is_last && !is_closed) => { },
+            _ => kept.push(*node),
+        }
+    }
+
+    // the closing edge of a ring
+    if is_closed {
+        while kept.len() > 1 && kept[kept.len() - 1].dist(&kept[0]) < min_length {
+            kept.pop();
+        }
+    }
+
+    kept
+}
+
+#[test]
+fn test_clip_options_on_intersection() {
+    use std::sync::Mutex;
+    use multi_polygon::MultiPolygon;
+    use polygon::BoolOpType;
+
+    let found = Arc::new(Mutex::new(Vec::new()));
+    let sink = found.clone();
+    let options = ClipOptions {
+        on_intersection: Some(Arc::new(move |p: Point2D, _: &Segment, _: &Segment| sink.lock().unwrap().push(p))),
+        .. Default::default()
+    };
+
+    let a = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 2.0, 2.0)]);
+    let b = MultiPolygon::new(vec![Polygon::rect(1.0, 1.0, 2.0, 2.0)]);
+    a.clip(&b, BoolOpType::Intersection, &options);
+
+    let mut found = found.lock().unwrap().clone();
+    found.sort();
+    assert_eq!(found, vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 2.0, y: 1.0 }]);
+}
+
+#[test]
+fn test_clip_options_cancel() {
+    use multi_polygon::MultiPolygon;
+    use polygon::BoolOpType;
+
+    let a = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 2.0, 2.0)]);
+    let b = MultiPolygon::new(vec![Polygon::rect(1.0, 1.0, 2.0, 2.0)]);
+
+    let token = Arc::new(AtomicBool::new(false));
+    let options = ClipOptions { cancel_token: Some(token.clone()), .. Default::default() };
+    assert!(a.try_clip(&b, BoolOpType::Union, &options).is_ok());
+
+    token.store(true, Ordering::Relaxed);
+    assert_eq!(a.try_clip(&b, BoolOpType::Union, &options).err(), Some(ClipError::Cancelled));
+    assert_eq!(a.polygons[0].try_clip(&b.polygons[0], BoolOpType::Union, &options).err(), Some(ClipError::Cancelled));
+
+    let options = ClipOptions { timeout: Some(Duration::from_secs(0)), .. Default::default() };
+    assert_eq!(a.try_clip(&b, BoolOpType::Union, &options).err(), Some(ClipError::Cancelled));
+}
+
+#[test]
+fn test_non_finite_coordinates() {
+    use multi_polygon::MultiPolygon;
+    use polygon::BoolOpType;
+    use std::f32;
+
+    let square = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    let broken = Polygon::from(vec![(1.0, 1.0), (3.0, f32::NAN as fsize), (3.0, 3.0)]);
+    let options = ClipOptions::default();
+
+    assert_eq!(square.try_clip(&broken, BoolOpType::Union, &options).err(),
+               Some(ClipError::NonFiniteCoordinate { index: 5 }));
+    assert!(square.union(&broken).is_none());
+
+    let a = MultiPolygon::new(vec![square]);
+    let b = MultiPolygon::new(vec![broken]);
+    assert_eq!(a.try_clip(&b, BoolOpType::Intersection, &options).err(),
+               Some(ClipError::NonFiniteCoordinate { index: 5 }));
+}
+
+#[test]
+fn test_clip_options_limits() {
+    use multi_polygon::MultiPolygon;
+    use polygon::BoolOpType;
+
+    let a = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 2.0, 2.0)]);
+    let b = MultiPolygon::new(vec![Polygon::rect(1.0, 1.0, 2.0, 2.0)]);
+
+    let options = ClipOptions { max_events: Some(4), .. Default::default() };
+    assert_eq!(a.try_clip(&b, BoolOpType::Union, &options).err(), Some(ClipError::TooManyEvents));
+
+    let options = ClipOptions { max_output_vertices: Some(7), .. Default::default() };
+    assert_eq!(a.try_clip(&b, BoolOpType::Union, &options).err(), Some(ClipError::OutputTooLarge));
+
+    let options = ClipOptions { max_events: Some(100), max_output_vertices: Some(8), .. Default::default() };
+    assert_eq!(a.try_clip(&b, BoolOpType::Union, &options).unwrap().polygons[0].nodes.len(), 8);
+}
+
+#[test]
+fn test_clip_options_filter() {
+    let sliver = Polygon::from(vec![(0.0, 0.0), (10.0, 0.0), (10.0, 0.01)]);
+    let square = Polygon::rect(0.0, 0.0, 1.0, 1.0);
+    let jagged = Polygon::from(vec![(0.0, 0.0), (0.001, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+
+    let options = ClipOptions { min_output_area: 0.5, .. Default::default() };
+    let result = options.filter_output(vec![sliver.clone(), square.clone()]);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes, square.nodes);
+
+    let options = ClipOptions { min_edge_length: 0.1, .. Default::default() };
+    let result = options.filter_output(vec![jagged, sliver]);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes, square.nodes);
+}
+
+#[test]
+fn test_clip_algorithm() {
+    use polygon::BoolOpType;
+
+    // the default algorithm
+    let a = Polygon::rect(-5.0, -5.0, 10.0, 10.0);
+    let b = Polygon::rect(0.0, 0.0, 10.0, 10.0);
+    let area = |op| a.try_clip(&b, op, &ClipOptions::default()).unwrap().iter().map(Polygon::area).sum::<fsize>();
+    assert_eq!(area(BoolOpType::Union), 175.0);
+    assert_eq!(area(BoolOpType::Intersection), 25.0);
+    assert_eq!(area(BoolOpType::Difference), 75.0);
+    assert_eq!(area(BoolOpType::Xor), 150.0);
+    assert_eq!(a.union(&b).unwrap().len(), 1);
+
+    let a = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    let b = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+    let options = ClipOptions { algorithm: ClipAlgorithm::GreinerHormann, .. Default::default() };
+
+    let result = a.try_clip(&b, BoolOpType::Intersection, &options).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes, Polygon::rect(1.0, 1.0, 1.0, 1.0).nodes);
+
+    let result = a.try_clip(&b, BoolOpType::Union, &options).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].area(), 7.0);
+}
+
+#[test]
+fn test_clip_stats() {
+    use multi_polygon::MultiPolygon;
+    use polygon::BoolOpType;
+
+    // the boundaries cross twice, each crossing splits one edge of both squares
+    let a = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 2.0, 2.0)]);
+    let b = MultiPolygon::new(vec![Polygon::rect(1.0, 1.0, 2.0, 2.0)]);
+    let (result, stats) = a.clip_with_stats(&b, BoolOpType::Union, &ClipOptions::default()).unwrap();
+    assert_eq!((stats.intersections_found, stats.subdivisions), (2, 4));
+    assert_eq!(stats.output_vertices, result.polygons[0].nodes.len());
+    assert!(stats.events_processed > 0);
+    #[cfg(not(target_arch = "wasm32"))]
+    assert!(stats.duration.is_some());
+
+    // the same limit as `max_events`
+    let options = ClipOptions { max_events: Some(stats.events_processed), .. Default::default() };
+    assert!(a.try_clip(&b, BoolOpType::Union, &options).is_ok());
+
+    // the same counters for a single polygon, with both of its algorithms
+    for algorithm in &[ClipAlgorithm::Arrangement, ClipAlgorithm::GreinerHormann] {
+        let options = ClipOptions { algorithm: *algorithm, .. Default::default() };
+        let (result, stats) = a.polygons[0].clip_with_stats(&b.polygons[0], BoolOpType::Intersection, &options).unwrap();
+        assert_eq!((stats.intersections_found, stats.subdivisions), (2, 4));
+        assert_eq!(stats.output_vertices, result[0].nodes.len());
+        assert_eq!(result[0].area(), 1.0);
+    }
+}
+
+#[test]
+fn test_deterministic() {
+    use multi_polygon::MultiPolygon;
+    use polygon::BoolOpType;
+
+    let options = ClipOptions { deterministic: true, .. Default::default() };
+    let a = MultiPolygon::new(vec![Polygon::from(vec![(0.0, 0.0), (3.0, 0.3), (2.7, 3.1), (-0.2, 2.9)])]);
+    let b = MultiPolygon::new(vec![Polygon::from(vec![(1.1, -1.3), (4.2, 1.7), (1.9, 4.4), (-1.6, 1.2)])]);
+
+    // the same bits on every platform
+    let result = a.clip(&b, BoolOpType::Union, &options);
+    #[cfg(not(use_double_precision))]
+    {
+        let bits: Vec<(u32, u32)> = result.polygons[0].nodes.iter().map(|p| (p.x.to_bits(), p.y.to_bits())).collect();
+        assert_eq!(bits, vec![
+            (3217870029, 1067030938), (1066192077, 3215353446), (1076782305, 1049330668), (1077936128, 1050253722),
+            (1077838932, 1057237808), (1082549862, 1071225242), (1072902963, 1082969293), (1050148124, 1077660418),
+            (3192704205, 1077516698), (3190875664, 1075859578),
+        ]);
+    }
+    assert_eq!(result.polygons, a.clip(&b, BoolOpType::Union, &ClipOptions::default()).polygons);
+}
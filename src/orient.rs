@@ -0,0 +1,113 @@
+//! Enforcing a winding convention on a set of rings
+
+use polygon::{Polygon, WindingOrder};
+
+/// Orients a set of rings so that exteriors follow the `outer` winding order and
+/// holes follow the opposite winding order
+///
+/// Rings are classified by containment: a ring that lies inside an odd number of
+/// other rings is a hole, all other rings are exteriors. The `is_hole` and `winding`
+/// fields of every ring are updated accordingly and the nodes of rings with the
+/// wrong orientation are reversed.
+///
+/// For the OGC / GeoJSON (RFC 7946) convention, use `WindingOrder::CounterClockwise`.
+///
+/// Rings with less than three nodes are left untouched. This function runs in O(n^2)
+/// time in the number of rings, since every ring is tested against every other ring.
+pub fn orient(polygons: &mut [Polygon], outer: WindingOrder) {
+
+    let depths: Vec<usize> = (0..polygons.len()).map(|i| {
+        if polygons[i].nodes.len() < 3 {
+            return 0;
+        }
+        (0..polygons.len())
+            .filter(|j| *j != i && polygons[*j].nodes.len() > 2)
+            .filter(|j| ::utils::is_ring_inside_ring(&polygons[i].nodes, &polygons[*j].nodes))
+            .count()
+    }).collect();
+
+    let inner = match outer {
+        WindingOrder::Clockwise => WindingOrder::CounterClockwise,
+        WindingOrder::CounterClockwise => WindingOrder::Clockwise,
+    };
+
+    for (polygon, depth) in polygons.iter_mut().zip(depths.into_iter()) {
+        if polygon.nodes.len() < 3 {
+            continue;
+        }
+
+        polygon.is_hole = depth % 2 == 1;
+        let target = if polygon.is_hole { inner } else { outer };
+
+        if ::utils::calculate_winding_order(&polygon.nodes) != target {
+            polygon.nodes.reverse();
+        }
+
+        polygon.winding = Some(target);
+    }
+}
+
+/// Reverses the nodes (and measures) of the polygon if it does not have the
+/// `desired` winding order, and stores the winding order in `winding`
+///
+/// Returns whether the nodes were reversed. Polygons with less than three
+/// nodes have no winding order and are left untouched.
+pub fn fix_winding(polygon: &mut Polygon, desired: WindingOrder) -> bool {
+    let current = match ::utils::try_calculate_winding_order(&polygon.nodes) {
+        Some(winding) => winding,
+        None => return false,
+    };
+
+    let reversed = current != desired;
+    if reversed {
+        polygon.nodes.reverse();
+        if let Some(measures) = polygon.measures.as_mut() {
+            measures.reverse();
+        }
+    }
+    polygon.winding = Some(desired);
+    reversed
+}
+
+#[test]
+fn test_orient_hole() {
+    use {Point2D, fsize};
+
+    let square = |min: fsize, max: fsize| Polygon {
+        nodes: vec![
+            Point2D { x: min, y: min },
+            Point2D { x: max, y: min },
+            Point2D { x: max, y: max },
+            Point2D { x: min, y: max },
+        ],
+        .. Default::default()
+    };
+
+    let mut polygons = vec![square(2.0, 8.0), square(0.0, 10.0)];
+    orient(&mut polygons, WindingOrder::CounterClockwise);
+
+    assert!(polygons[0].is_hole);
+    assert!(!polygons[1].is_hole);
+    assert_eq!(::utils::calculate_winding_order(&polygons[0].nodes), WindingOrder::Clockwise);
+    assert_eq!(::utils::calculate_winding_order(&polygons[1].nodes), WindingOrder::CounterClockwise);
+}
+
+#[test]
+fn test_fix_winding() {
+    use Point2D;
+
+    let mut triangle = Polygon::from(vec![(0.0, 0.0), (0.0, 1.0), (1.0, 0.0)]);
+    triangle.measures = Some(vec![1.0, 2.0, 3.0]);
+    assert_eq!(triangle.winding, Some(WindingOrder::Clockwise));
+
+    assert!(fix_winding(&mut triangle, WindingOrder::CounterClockwise));
+    assert_eq!(triangle.winding, Some(WindingOrder::CounterClockwise));
+    assert_eq!(::utils::try_calculate_winding_order(&triangle.nodes), Some(WindingOrder::CounterClockwise));
+    assert_eq!(triangle.measures, Some(vec![3.0, 2.0, 1.0]));
+    assert!(!fix_winding(&mut triangle, WindingOrder::CounterClockwise));
+
+    let mut line = Polygon::from(vec![(0.0, 0.0), (1.0, 1.0)]);
+    assert!(!fix_winding(&mut line, WindingOrder::Clockwise));
+    assert_eq!(line.winding, None);
+    assert_eq!(::utils::try_calculate_winding_order(&[] as &[Point2D]), None);
+}
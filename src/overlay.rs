@@ -0,0 +1,63 @@
+//! Identity / union overlay of two attributed polygon sets
+
+use arrangement::Arrangement;
+use multi_polygon::MultiPolygon;
+use polygon::Polygon;
+
+/// Splits the area covered by `a` and `b` into non-overlapping pieces and
+/// attaches the attributes of the sets that cover each piece
+///
+/// This is the "union" overlay known from GIS software: every piece lies
+/// either in A only, in B only or in both, which is reflected by the returned
+/// `Option`s. Areas covered by neither set are not returned.
+///
+/// Every piece is returned as its outer ring, followed by its holes (with
+/// `is_hole` set to true) carrying the same attributes.
+pub fn overlay_identity<T: Clone, U: Clone>(a: &MultiPolygon<T>, b: &MultiPolygon<U>)
+                                            -> Vec<(Polygon, Option<T>, Option<U>)>
+{
+    let arrangement = Arrangement::new(&a.polygons, &b.polygons);
+    let mut result = Vec::new();
+
+    // the unbounded face (index 0) is never covered
+    for (idx, face) in arrangement.faces.iter().enumerate().skip(1) {
+        if !face.in_a && !face.in_b {
+            continue;
+        }
+
+        let data_a = if face.in_a { Some(a.data.clone()) } else { None };
+        let data_b = if face.in_b { Some(b.data.clone()) } else { None };
+
+        for ring in arrangement.face_rings(idx) {
+            result.push((ring, data_a.clone(), data_b.clone()));
+        }
+    }
+
+    result
+}
+
+#[test]
+fn test_overlay_identity() {
+    use {Point2D, fsize};
+
+    let square = |x: fsize, y: fsize| Polygon::from_parts(vec![
+        Point2D { x: x, y: y },
+        Point2D { x: x + 2.0, y: y },
+        Point2D { x: x + 2.0, y: y + 2.0 },
+        Point2D { x: x, y: y + 2.0 },
+    ], false, true);
+
+    let landuse = MultiPolygon::with_data(vec![square(0.0, 0.0)], "forest");
+    let parcels = MultiPolygon::with_data(vec![square(1.0, 1.0)], 42);
+
+    let pieces = overlay_identity(&landuse, &parcels);
+    let attributes: Vec<(Option<&str>, Option<i32>)> = pieces.iter().map(|&(_, a, b)| (a, b)).collect();
+
+    assert_eq!(pieces.len(), 3);
+    assert!(attributes.contains(&(Some("forest"), None)));
+    assert!(attributes.contains(&(None, Some(42))));
+    assert!(attributes.contains(&(Some("forest"), Some(42))));
+
+    let both = pieces.iter().find(|&&(_, a, b)| a.is_some() && b.is_some()).unwrap();
+    assert_eq!(both.0.bounding_box(), ::Bbox::new(1.0, 1.0, 2.0, 2.0));
+}
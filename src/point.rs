@@ -1,13 +1,101 @@
 use fsize;
+use kernel::{Kernel, DefaultKernel};
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 
 /// 2D point struct that is generic over the precision (`fsize = f32 | f64`)
+///
+/// Points compare equal if their coordinates are equal as floats, so a point
+/// with a NaN coordinate is not equal to itself. Points with finite
+/// coordinates can be used as keys of a `HashSet` or `BTreeMap` directly, use
+/// `PointKey` for keys that may contain NaN.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Point2D {
   pub x: fsize,
   pub y: fsize,
 }
 
 impl Eq for Point2D { }
+
+impl Hash for Point2D {
+    /// Hashes the bit pattern of the coordinates. `-0.0` is hashed like `0.0`,
+    /// so that points which compare equal also hash equal.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        canonical_bits(self.x).hash(state);
+        canonical_bits(self.y).hash(state);
+    }
+}
+
+impl Ord for Point2D {
+    /// Lexicographic order: first by x, then by y
+    ///
+    /// This is the order in which the intersection sweep processes the points
+    /// (see `find_intersections`). NaN coordinates are sorted after all other values.
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_fsize(self.x, other.x).then_with(|| cmp_fsize(self.y, other.y))
+    }
+}
+
+impl PartialOrd for Point2D {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Point that is compared by the order of `Point2D`, as a key of a `HashSet`
+/// or `BTreeMap` that may contain NaN coordinates
+///
+/// Unlike for `Point2D`, NaN equals NaN (with any payload), so such a point
+/// can be found again. `-0.0` equals `0.0`.
+#[derive(Debug, Copy, Clone)]
+pub struct PointKey(pub Point2D);
+
+impl PartialEq for PointKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PointKey { }
+
+impl Hash for PointKey {
+    /// Hashes like `Point2D`, with all NaNs alike
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        canonical_bits(self.0.x).hash(state);
+        canonical_bits(self.0.y).hash(state);
+    }
+}
+
+impl Ord for PointKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for PointKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[inline]
+fn cmp_fsize(a: fsize, b: fsize) -> Ordering {
+    a.partial_cmp(&b).unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+}
+
+#[inline]
+fn canonical_bits(value: fsize) -> impl Hash {
+    if value.is_nan() {
+        fsize::NAN.to_bits()
+    } else if value == 0.0 {
+        (0.0 as fsize).to_bits()
+    } else {
+        value.to_bits()
+    }
+}
+
 impl Point2D {
     /// Returns the distance to another point via pythagoras
     pub fn dist(&self, other: &Self) -> fsize {
@@ -15,40 +103,216 @@ impl Point2D {
         let dy = self.y - other.y;
         (dx.powi(2) + dy.powi(2)).sqrt()
     }
+
+    /// Dot product, treating both points as vectors
+    #[inline]
+    pub fn dot(&self, other: &Self) -> fsize {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// z component of the cross product, treating both points as vectors
+    ///
+    /// Positive if `other` is counter-clockwise from `self`
+    #[inline]
+    pub fn cross(&self, other: &Self) -> fsize {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Length of the vector from the origin to this point
+    #[inline]
+    pub fn length(&self) -> fsize {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the vector scaled to a length of 1.0
+    ///
+    /// The zero vector has no direction and is returned unchanged.
+    #[inline]
+    pub fn normalize(&self) -> Self {
+        let len = self.length();
+        if len == 0.0 {
+            *self
+        } else {
+            Point2D { x: self.x / len, y: self.y / len }
+        }
+    }
+
+    /// Linear interpolation: returns `self` for `t = 0.0` and `other` for `t = 1.0`
+    #[inline]
+    pub fn lerp(&self, other: &Self, t: fsize) -> Self {
+        Point2D {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+        }
+    }
+
+    /// Converts a slice of points of another library (for example `glam::Vec2`
+    /// with the `glam` feature) in one call
+    pub fn from_slice<P: Copy + Into<Point2D>>(points: &[P]) -> Vec<Point2D> {
+        points.iter().map(|&p| p.into()).collect()
+    }
+
+    /// Converts a slice of points into the point type of another library,
+    /// the reverse of `from_slice`
+    pub fn to_vec<P: From<Point2D>>(points: &[Point2D]) -> Vec<P> {
+        points.iter().map(|&p| P::from(p)).collect()
+    }
+}
+
+impl Add for Point2D {
+    type Output = Self;
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Point2D { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+
+impl Sub for Point2D {
+    type Output = Self;
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Point2D { x: self.x - other.x, y: self.y - other.y }
+    }
+}
+
+impl Mul<fsize> for Point2D {
+    type Output = Self;
+    #[inline]
+    fn mul(self, factor: fsize) -> Self {
+        Point2D { x: self.x * factor, y: self.y * factor }
+    }
+}
+
+impl Div<fsize> for Point2D {
+    type Output = Self;
+    #[inline]
+    fn div(self, divisor: fsize) -> Self {
+        Point2D { x: self.x / divisor, y: self.y / divisor }
+    }
+}
+
+impl Neg for Point2D {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self {
+        Point2D { x: -self.x, y: -self.y }
+    }
 }
 
 /// Check if two lines intersect.
 ///
 /// The first point is the general intersection. In special edge cases, there
 /// can be two points generated by an intersection (when the vectors of two lines cross each other)
+///
+/// If the segments are collinear and overlap, the two points are the endpoints
+/// of the shared sub-segment (in the order of `Point2D::cmp`). Collinear segments
+/// that only touch return the shared endpoint, parallel segments return None.
+///
+/// The segments are intersected by `DefaultKernel`.
 #[inline]
 pub fn line_intersect(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D)
                       -> Option<(Point2D, Option<Point2D>)>
 {
-    let s1_x = p1.x - p0.x;
-    let s1_y = p1.y - p0.y;
-    let s2_x = p3.x - p2.x;
-    let s2_y = p3.y - p2.y;
+    DefaultKernel::intersect(p0, p1, p2, p3)
+}
 
-    let coef_div = -s2_x * s1_y + s1_x * s2_y;
+/// Same as `line_intersect`, but with a tolerance of `eps` for nearly
+/// parallel segments and intersections close to an endpoint
+///
+/// If both endpoints of the shorter segment are within `eps` of the line
+/// through the longer one, the segments are treated as collinear and the
+/// shared sub-segment is returned. Intersections within `eps` of an endpoint
+/// return that endpoint exactly, and the intersection parameters are clamped
+/// to the segments. This avoids the unstable division for long, nearly
+/// parallel segments that are common in map data.
+pub fn line_intersect_eps(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D, eps: fsize)
+                          -> Option<(Point2D, Option<Point2D>)>
+{
+    let eps = eps.max(0.0);
 
-    if coef_div == 0.0 {
-        /* lines merged to single point, avoid division by 0 */
-        return Some((*p0, None));
+    // (a0, a1) is the longer segment
+    let (a0, a1, b0, b1) = if p0.dist(p1) >= p2.dist(p3) { (p0, p1, p2, p3) } else { (p2, p3, p0, p1) };
+    let s1 = *a1 - *a0;
+    let s2 = *b1 - *b0;
+    let len1 = s1.length();
+    let len2 = s2.length();
+
+    if len1 <= eps {
+        // both segments are shorter than the tolerance
+        return if ::utils::distance_point_segment(a0, b0, b1) <= eps { Some((*a0, None)) } else { None };
     }
 
-    let s = (-s1_y * (p0.x - p2.x) + s1_x * (p0.y - p2.y)) / coef_div;
-    let t = ( s2_x * (p0.y - p2.y) - s2_y * (p0.x - p2.x)) / coef_div;
+    let t_eps = eps / len1;
+
+    if s1.cross(&(*b0 - *a0)).abs() / len1 <= eps && s1.cross(&(*b1 - *a0)).abs() / len1 <= eps {
+        // nearly collinear: overlap of the parameter ranges along (a0, a1)
+        let project = |p: &Point2D| (*p - *a0).dot(&s1) / (len1 * len1);
+        let (mut b_start, mut b_end) = ((project(b0), *b0), (project(b1), *b1));
+        if b_start.0 > b_end.0 {
+            ::std::mem::swap(&mut b_start, &mut b_end);
+        }
 
-    if t >= 0.0 && t <= 1.0 && s >= 0.0 && s <= 1.0 {
-        let first_point = Point2D {
-            x: p0.x + (t * s1_x),
-            y: p0.y + (t * s1_y)
+        let start = if b_start.0 > 0.0 { b_start } else { (0.0, *a0) };
+        let end = if b_end.0 < 1.0 { b_end } else { (1.0, *a1) };
+
+        return if start.0 > end.0 + t_eps {
+            None
+        } else if end.0 - start.0 <= t_eps {
+            Some((start.1, None))
+        } else {
+            Some((::std::cmp::min(start.1, end.1), Some(::std::cmp::max(start.1, end.1))))
         };
-        // if lines are parallel (slopes are equal) { calculate second point }
-        Some((first_point, None))
+    }
+
+    let coef_div = s1.cross(&s2);
+    if coef_div == 0.0 {
+        return None;
+    }
+
+    let d = *b0 - *a0;
+    let t = d.cross(&s2) / coef_div;
+    let s = d.cross(&s1) / coef_div;
+    let s_eps = eps / len2;
+
+    if t < -t_eps || t > 1.0 + t_eps || s < -s_eps || s > 1.0 + s_eps {
+        return None;
+    }
+
+    let point = if t <= t_eps {
+        *a0
+    } else if t >= 1.0 - t_eps {
+        *a1
+    } else if s <= s_eps {
+        *b0
+    } else if s >= 1.0 - s_eps {
+        *b1
     } else {
-        None
+        a0.lerp(a1, t.max(0.0).min(1.0))
+    };
+
+    Some((point, None))
+}
+
+/// Shared sub-segment of two parallel segments, None if they are not on the same line
+///
+/// Along a line, the points are ordered like `Point2D::cmp`, so the overlap
+/// is computed on the endpoints directly, without a division.
+pub(crate) fn collinear_overlap(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D)
+                     -> Option<(Point2D, Option<Point2D>)>
+{
+    let on_same_line = (*p2 - *p0).cross(&(*p1 - *p0)) == 0.0 &&
+                       (*p0 - *p2).cross(&(*p3 - *p2)) == 0.0;
+    if !on_same_line {
+        return None;
+    }
+
+    let start = ::std::cmp::max(::std::cmp::min(*p0, *p1), ::std::cmp::min(*p2, *p3));
+    let end = ::std::cmp::min(::std::cmp::max(*p0, *p1), ::std::cmp::max(*p2, *p3));
+
+    match start.cmp(&end) {
+        Ordering::Less => Some((start, Some(end))),
+        Ordering::Equal => Some((start, None)),
+        Ordering::Greater => None,
     }
 }
 
@@ -99,7 +363,14 @@ pub(crate) fn test_line_intersect_colinear_overlap() {
                                 &Point2D { x: 5.0,  y: 5.0 },
                                 &Point2D { x: 2.0,  y: 2.0 },
                                 &Point2D { x: 7.0,  y: 7.0 });
-    assert!(result.is_some());
+    assert_eq!(result, Some((Point2D { x: 2.0, y: 2.0 }, Some(Point2D { x: 5.0, y: 5.0 }))));
+
+    // Collinear, reversed direction, one segment contains the other
+    let result = line_intersect(&Point2D { x: 0.0,  y: 10.0 },
+                                &Point2D { x: 0.0,  y: 0.0 },
+                                &Point2D { x: 0.0,  y: 2.0 },
+                                &Point2D { x: 0.0,  y: 4.0 });
+    assert_eq!(result, Some((Point2D { x: 0.0, y: 2.0 }, Some(Point2D { x: 0.0, y: 4.0 }))));
 }
 
 #[test]
@@ -109,5 +380,85 @@ pub(crate) fn test_line_intersect_colinear_nooverlap() {
                                 &Point2D { x: 5.0,  y: 5.0 },
                                 &Point2D { x: 7.0,  y: 7.0 },
                                 &Point2D { x: 10.0,  y: 10.0 });
-    assert!(result.is_some());
+    assert!(result.is_none());
+
+    // Collinear, touching at an endpoint
+    let result = line_intersect(&Point2D { x: 0.0,  y: 0.0 },
+                                &Point2D { x: 5.0,  y: 5.0 },
+                                &Point2D { x: 5.0,  y: 5.0 },
+                                &Point2D { x: 10.0,  y: 10.0 });
+    assert_eq!(result, Some((Point2D { x: 5.0, y: 5.0 }, None)));
+}
+
+#[test]
+pub(crate) fn test_line_intersect_eps() {
+    // long, nearly parallel segments are treated as collinear
+    let result = line_intersect_eps(&Point2D { x: 0.0, y: 0.0 },
+                                    &Point2D { x: 1000.0, y: 0.0 },
+                                    &Point2D { x: 0.0, y: 0.0001 },
+                                    &Point2D { x: 1000.0, y: -0.0001 }, 0.001);
+    assert_eq!(result, Some((Point2D { x: 0.0, y: 0.0 }, Some(Point2D { x: 1000.0, y: 0.0 }))));
+
+    // an intersection just past the endpoint snaps to the endpoint
+    let result = line_intersect_eps(&Point2D { x: 0.0, y: 0.0 },
+                                    &Point2D { x: 10.0, y: 0.0 },
+                                    &Point2D { x: 10.0001, y: -1.0 },
+                                    &Point2D { x: 10.0001, y: 1.0 }, 0.001);
+    assert_eq!(result, Some((Point2D { x: 10.0, y: 0.0 }, None)));
+
+    // regular crossings and misses behave like line_intersect
+    let result = line_intersect_eps(&Point2D { x: 0.0, y: 0.0 },
+                                    &Point2D { x: 4.0, y: 4.0 },
+                                    &Point2D { x: 0.0, y: 4.0 },
+                                    &Point2D { x: 4.0, y: 0.0 }, 0.001);
+    assert_eq!(result, Some((Point2D { x: 2.0, y: 2.0 }, None)));
+    assert!(line_intersect_eps(&Point2D { x: 0.0, y: 0.0 },
+                               &Point2D { x: 5.0, y: 5.0 },
+                               &Point2D { x: 2.0, y: 0.0 },
+                               &Point2D { x: 7.0, y: 5.0 }, 0.001).is_none());
+}
+
+#[test]
+pub(crate) fn test_point_vector_ops() {
+    let a = Point2D { x: 3.0, y: 4.0 };
+    let b = Point2D { x: 1.0, y: 0.0 };
+    assert_eq!(a + b, Point2D { x: 4.0, y: 4.0 });
+    assert_eq!(a - b, Point2D { x: 2.0, y: 4.0 });
+    assert_eq!(-(a * 2.0), Point2D { x: -6.0, y: -8.0 });
+    assert_eq!(a.length(), 5.0);
+    assert_eq!(a.normalize(), Point2D { x: 0.6, y: 0.8 });
+    assert_eq!(b.cross(&a), 4.0);
+    assert_eq!(a.dot(&b), 3.0);
+    assert_eq!(a.lerp(&b, 0.5), Point2D { x: 2.0, y: 2.0 });
+}
+
+#[test]
+pub(crate) fn test_point_ord_and_hash() {
+    use std::collections::HashSet;
+
+    let mut points = vec![
+        Point2D { x: 1.0, y: 2.0 },
+        Point2D { x: 0.0, y: 5.0 },
+        Point2D { x: 1.0, y: -1.0 },
+    ];
+    points.sort();
+    assert_eq!(points, vec![
+        Point2D { x: 0.0, y: 5.0 },
+        Point2D { x: 1.0, y: -1.0 },
+        Point2D { x: 1.0, y: 2.0 },
+    ]);
+
+    let set: HashSet<Point2D> = vec![Point2D { x: 0.0, y: 1.0 }, Point2D { x: -0.0, y: 1.0 }].into_iter().collect();
+    assert_eq!(set.len(), 1);
+
+    // as floats, NaN is not equal to NaN, but as keys all NaNs are alike
+    let nan = Point2D { x: fsize::NAN, y: 1.0 };
+    let other_nan = Point2D { x: fsize::from_bits(fsize::NAN.to_bits() + 1), y: 1.0 };
+    assert!(nan != other_nan);
+    assert_eq!(nan.cmp(&other_nan), Ordering::Equal);
+    assert_eq!(PointKey(nan), PointKey(other_nan));
+    assert!(PointKey(nan) != PointKey(Point2D { x: 0.0, y: 1.0 }));
+    let set: HashSet<PointKey> = vec![PointKey(nan), PointKey(other_nan)].into_iter().collect();
+    assert_eq!(set.len(), 1);
+    assert!(set.contains(&PointKey(nan)));
 }
@@ -1,13 +1,15 @@
-use intrusive_collections::{RBTreeLink, KeyAdapter};
-use sweep_event::{SweepEvent, SweepEventRef, PolygonType, EdgeType};
-use segment::Segment;
-use std::collections::BinaryHeap;
-use std::cell::UnsafeCell;
-use Point2D;
+use edges::Edges;
+use options::{ClipOptions, ClipAlgorithm, ClipStats, OperationGuard};
+use error::{ClipError, ClipWarning};
+use std::borrow::Cow;
+use std::iter::FromIterator;
+use precision::PolygonI64;
+use {Point2D, Bbox, fsize};
 
 /// Modifying the nodes of a polygon must be done via a closure,
 /// because if the points are modified, the bounding box has to be recomputed
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Polygon {
     /// The points that this polygon is made of
     pub nodes: Vec<Point2D>,
@@ -17,24 +19,47 @@ pub struct Polygon {
     pub is_closed: bool,
     /// Are the nodes of this polygon in a clockwise order?
     /// By default, this field is not calculated, due to performance reasons
-    /// If you want to calculate it, call `calculate_winding_order(&self.nodes)`
+    /// If you want to calculate it, call `try_calculate_winding_order(&self.nodes)`
     ///
     /// If you already know the winding order, please set it beforehand, to speed up
     /// the calculation.
     pub winding: Option<WindingOrder>,
+    /// Cached bounding box of the nodes. If this is `None`, the bounding box is
+    /// calculated on demand by every boolean operation.
+    ///
+    /// If you modify `nodes` directly, you have to reset or recalculate this
+    /// field - use `with_nodes_mut` to do this automatically.
+    pub bbox: Option<Bbox>,
+    /// Optional extra value for every node (for example a z coordinate or an
+    /// M value for linear referencing), parallel to `nodes`
+    ///
+    /// The boolean operations on `MultiPolygon` linearly interpolate the values
+    /// at new intersection points. Operations that keep the number of nodes keep
+    /// the values, other operations drop them.
+    pub measures: Option<Vec<fsize>>,
 }
 
 /// Winding order of a polygon
 #[derive(Debug, Copy,Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WindingOrder {
     Clockwise,
     CounterClockwise,
 }
 
-/// Only used for internal operations: type of boolean
-/// operation to perform on the polygons
+/// Rule that decides which areas of a set of (possibly overlapping) rings are filled
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FillRule {
+    /// A point is inside if a ray from it crosses an odd number of edges
+    EvenOdd,
+    /// A point is inside if the winding number of the rings around it is not zero
+    NonZero,
+}
+
+/// Type of boolean operation to perform on the polygons
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum BoolOpType {
+pub enum BoolOpType {
     Intersection,
     Union,
     Difference,
@@ -48,54 +73,122 @@ impl Default for Polygon {
             is_hole: false,
             is_closed: true,
             winding: None,
+            bbox: None,
+            measures: None,
         }
     }
 }
 
-use std::rc::Rc;
+/// Polygons are equal if they have the same nodes, flags and measures. The
+/// cached winding order and bounding box are not compared.
+impl PartialEq for Polygon {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes && self.is_hole == other.is_hole &&
+        self.is_closed == other.is_closed && self.measures == other.measures
+    }
+}
 
-struct SweepLineEventLink<'a> {
-    link: RBTreeLink,
-    value: Rc<SweepEventRef<'a>>,
+impl From<Vec<Point2D>> for Polygon {
+    fn from(nodes: Vec<Point2D>) -> Self {
+        Polygon::new(nodes)
+    }
 }
 
-#[derive(Clone, Default)]
-struct SweepLine<'a>(::intrusive_collections::__core::marker::PhantomData<Box<SweepLineEventLink<'a>>>);
+impl From<Vec<(fsize, fsize)>> for Polygon {
+    fn from(nodes: Vec<(fsize, fsize)>) -> Self {
+        nodes.into_iter().map(|(x, y)| Point2D { x: x, y: y }).collect()
+    }
+}
 
-unsafe impl<'a> Send for SweepLine<'a> { }
-unsafe impl<'a> Sync for SweepLine<'a> { }
+impl From<Vec<[fsize; 2]>> for Polygon {
+    fn from(nodes: Vec<[fsize; 2]>) -> Self {
+        nodes.into_iter().map(|p| Point2D { x: p[0], y: p[1] }).collect()
+    }
+}
 
-// manual implementation of intrusive_adapter!() for easier debugging
-#[allow(dead_code)]
-impl<'a> SweepLine<'a> {
-    pub fn new() -> Self {
-        SweepLine(::intrusive_collections::__core::marker::PhantomData::<Box<SweepLineEventLink<'a>>>)
+impl FromIterator<Point2D> for Polygon {
+    fn from_iter<I: IntoIterator<Item = Point2D>>(iter: I) -> Self {
+        Polygon::new(iter.into_iter().collect())
     }
 }
 
-#[allow(dead_code, unsafe_code)]
-unsafe impl<'a> ::intrusive_collections::Adapter for SweepLine<'a> {
-    type Link = RBTreeLink;
-    type Value = SweepLineEventLink<'a>;
-    type Pointer = Box<SweepLineEventLink<'a>>;
+impl Polygon {
+
+    /// Modifies the nodes of the polygon via a closure and recalculates
+    /// the cached bounding box and winding order afterwards
+    pub fn with_nodes_mut<F, R>(&mut self, f: F) -> R where F: FnOnce(&mut Vec<Point2D>) -> R {
+        let result = f(&mut self.nodes);
+        self.bbox = Bbox::from_points(&self.nodes);
+        self.winding = ::utils::try_calculate_winding_order(&self.nodes);
+        result
+    }
+
+    /// Iterates over the edges of the polygon as `(start, end)` pairs,
+    /// including the closing edge if the polygon is closed
     #[inline]
-    unsafe fn get_value(&self, link: *const RBTreeLink) -> *const SweepLineEventLink<'a> {
-        container_of!(link, SweepLineEventLink<'a>, link)
+    pub fn edges(&self) -> Edges {
+        Edges::new(&self.nodes, self.is_closed)
     }
+
+    /// Iterates over the nodes of the polygon
     #[inline]
-    unsafe fn get_link(&self, value: *const SweepLineEventLink<'a>) -> *const RBTreeLink {
-        &(*value).link
+    pub fn points(&self) -> ::std::slice::Iter<Point2D> {
+        self.nodes.iter()
     }
-}
 
-impl<'a, 'b> KeyAdapter<'b> for SweepLine<'a> {
-    type Key = Rc<SweepEventRef<'a>>;
-    fn get_key(&self, e: &'b SweepLineEventLink<'a>) -> Rc<SweepEventRef<'a>> { 
-        e.value.clone()
+    /// Iterates mutably over the nodes of the polygon
+    ///
+    /// Since the nodes can change arbitrarily, the cached bounding box and
+    /// winding order are reset to `None`.
+    #[inline]
+    pub fn points_mut(&mut self) -> ::std::slice::IterMut<Point2D> {
+        self.bbox = None;
+        self.winding = None;
+        self.nodes.iter_mut()
     }
-}
 
-impl Polygon {
+    /// Returns the cached bounding box or calculates it, if it isn't cached
+    #[inline]
+    pub fn bounding_box(&self) -> Bbox {
+        match self.bbox {
+            Some(bbox) => bbox,
+            None => ::utils::calculate_bounding_box(&self.nodes),
+        }
+    }
+
+    /// Creates a copy of the polygon with different nodes, but the same flags
+    /// and winding order. The cached bounding box is recalculated, the measures
+    /// are only kept if the number of nodes did not change.
+    pub(crate) fn with_nodes(&self, nodes: Vec<Point2D>) -> Self {
+        Self {
+            measures: if nodes.len() == self.nodes.len() { self.measures.clone() } else { None },
+            bbox: Bbox::from_points(&nodes),
+            nodes: nodes,
+            is_hole: self.is_hole,
+            is_closed: self.is_closed,
+            winding: self.winding,
+        }
+    }
+
+    /// Creates a closed polygon (not a hole) from its nodes and calculates
+    /// the bounding box and winding order
+    pub fn new(nodes: Vec<Point2D>) -> Self {
+        Polygon::from_parts(nodes, false, true)
+    }
+
+    /// Creates a new polygon and calculates the bounding box and
+    /// (for polygons with more than two nodes) the winding order
+    pub(crate) fn from_parts(nodes: Vec<Point2D>, is_hole: bool, is_closed: bool) -> Self {
+        let winding = ::utils::try_calculate_winding_order(&nodes);
+        Self {
+            bbox: Bbox::from_points(&nodes),
+            nodes: nodes,
+            is_hole: is_hole,
+            is_closed: is_closed,
+            winding: winding,
+            measures: None,
+        }
+    }
 
     /// Substracts a polygon from the current one
     ///
@@ -106,6 +199,13 @@ impl Polygon {
         self.calculate(other, BoolOpType::Intersection)
     }
 
+    /// Calculates the area that is covered by both polygons
+    pub fn intersection(&self, other: &Self)
+    -> Option<Vec<Self>>
+    {
+        self.calculate(other, BoolOpType::Intersection)
+    }
+
     pub fn union(&self, other: &Self)
     -> Option<Vec<Self>>
     {
@@ -124,517 +224,277 @@ impl Polygon {
         self.calculate(other, BoolOpType::Xor)
     }
 
-    // NOTE: The method should be inlined, because this will elide the `operation_type`
-    // tests, which will make the whole thing faster. The function will be inlined four times,
-    // one for each `BoolOpType`.
-    #[inline(always)]
-    fn calculate(&self, other: &Self, operation_type: BoolOpType)
+    /// Runs the boolean operation `operation_type` with the given options
+    ///
+    /// Returns None if the result is empty, including the case where all
+    /// result contours were removed by the output filters.
+    pub fn clip(&self, other: &Self, operation_type: BoolOpType, options: &ClipOptions)
     -> Option<Vec<Self>>
     {
-        use self::BoolOpType::*;
-        use self::EdgeType::*;
-        use connector::Connector;
-
-        // Trivial result case - either self or other polygon do not exist
-        // or they are lines. At the very least we need a triangle.
-        if (self.nodes.len() * other.nodes.len()) == 0 {
-            match operation_type {
-                Difference => return Some(vec![self.clone()]),
-                Intersection => return None,
-                Union | Xor  => if self.nodes.is_empty() {
-                    return Some(vec![other.clone()])
-                } else {
-                    return Some(vec![self.clone()])
-                },
-            }
-        }
-
-        // Trivial result case - one of the polygons is actually a line
-        // Cannot subtract a polygon and a line (this may change in the future)
-        if self.nodes.len() < 3 || other.nodes.len() < 3 {
-            return None;
-        }
-
-        // Trivial result case - boundaries don't overlap
-        // NOTE: This should not be done here, this should be done in the MultiPolygon
-        // class (R* tree)
-        let self_bbox = ::utils::calculate_bounding_box(&self.nodes);
-        let other_bbox = ::utils::calculate_bounding_box(&other.nodes);
-
-        if !self_bbox.overlaps(&other_bbox) {
-            match operation_type {
-                Difference => return Some(vec![self.clone()]),
-                Intersection => return None,
-                Union | Xor => return Some(vec![self.clone(), other.clone()])
-            }
-        }
-
-        // Boolean operation is non-trivial
-
-        // Create the sweep events
-        let vec_of_sweep_events_subject = create_sweep_events(&self.nodes, PolygonType::Subject);
-        let vec_of_sweep_events_clipping = create_sweep_events(&other.nodes, PolygonType::Clipping);
+        let result = self.try_clip(other, operation_type, options).ok()?;
+        if result.is_empty() { None } else { Some(result) }
+    }
 
-        // Sort the sweep events
-        // Insert all the endpoints associated to the line segments into the event queue
-        let mut event_queue = BinaryHeap::<&SweepEventRef>::with_capacity((self.nodes.len() * 2) + (other.nodes.len() * 2));
+    /// Same as `clip`, but returns an error if the operation was aborted (see
+    /// `ClipOptions::cancel_token`). An empty result is returned as an empty list.
+    ///
+    /// The result is computed by `ClipOptions::backend` if one is set, otherwise
+    /// by `ClipOptions::algorithm`.
+    pub fn try_clip(&self, other: &Self, operation_type: BoolOpType, options: &ClipOptions)
+    -> Result<Vec<Self>, ClipError>
+    {
+        self.clip_guarded(other, operation_type, options, &mut options.guard())
+    }
 
-        for event in &*vec_of_sweep_events_subject {
-            event_queue.push(event);
-        }
+    /// Same as `try_clip`, but also returns what the operation did: the number
+    /// of events, intersections and subdivisions, the size of the result and
+    /// the time it took
+    pub fn clip_with_stats(&self, other: &Self, operation_type: BoolOpType, options: &ClipOptions)
+    -> Result<(Vec<Self>, ClipStats), ClipError>
+    {
+        let mut guard = options.timed_guard();
+        let result = self.clip_guarded(other, operation_type, options, &mut guard)?;
+        let stats = guard.stats(&result);
+        Ok((result, stats))
+    }
 
-        for event in &*vec_of_sweep_events_clipping {
-            event_queue.push(event);
+    /// Runs `try_clip` with the `guard`, which a `ClipOptions::backend` does not use
+    fn clip_guarded(&self, other: &Self, operation_type: BoolOpType, options: &ClipOptions, guard: &mut OperationGuard)
+    -> Result<Vec<Self>, ClipError>
+    {
+        let mut result = match options.backend {
+            Some(ref backend) => backend.clip(self, other, operation_type, options)?,
+            None => self.clip_with_algorithm(other, options.algorithm, operation_type, options, guard)?,
+        };
+        if options.deterministic {
+            ::multi_polygon::sort_canonical(&mut result);
         }
+        let result = options.filter_output(result);
+        options.check_output_size(&result)?;
+        Ok(result)
+    }
 
-        // -------------------------------------------------------------------- sweep events created
-
-        let mut connector = Connector::new();
-        let mut event_holder = Vec::<SweepEventRef>::new();
-        let mut sweep_line = SweepLine::new();
-
-        let minimum_x_bbox_pt = self_bbox.right.min(other_bbox.right);
-
-        // calculate the necessary events
-        while let Some(mut event) = event_queue.pop() {
-
-            // -----------------------------------------------------------------   optimization 1
-
-            if (operation_type == Intersection && (inner!(event).p.x > minimum_x_bbox_pt)) ||
-               (operation_type == Difference && (inner!(event).p.x > self_bbox.right)) {
-                break;
-            }
-
-            if operation_type == Union && (inner!(event).p.x > minimum_x_bbox_pt) && !inner!(event).left {
-                // add all the non-processed line segments to the result
-                connector.add_segment(Segment::new(inner!(event).p, other!(event).p));
-                while let Some(new_event) = event_queue.pop() {
-                    if !inner!(new_event).left {
-                        connector.add_segment(Segment::new(inner!(new_event).p, other!(new_event).p));
-                    }
-                }
-                break;
-            }
-
-            // ---------------------------------------------------------------- end of optimization 1
-
-            if inner!(event).left {
-/*
-                // the current line segment must be inserted into the sweepline
-
-                // NOTE: This won't work correctly. A BTreeSet cannot be indexed,
-                // since it is not contigouus in memory. This should return an
-                // interator instead, so that we can use .next() and the like.
-                //
-                // Returning a number as an index is only a placeholder and will 100%
-                // crash at runtime
-                let event_pos_in_sweep_line = sweep_line.insert_return_index(event);
-
-                // Also: Note that we are assigning to event here.
-                // Not sure if event should be a &mut Event
-                inner_mut!(event).position_in_sweep_line = event_pos_in_sweep_line;
-
-                let it = event_pos_in_sweep_line;
-                let next = event_pos_in_sweep_line;
-                let mut prev = event_pos_in_sweep_line;
-
-                // TODO: does the sweep line get modified after this initial insert?
-                // If yes, the it iterator is invalid
-
-                let sweep_line_len = sweep_line.len();
-                // make "prev" wrap around
-                if prev != 0 {
-                    prev -= 1;
-                } else {
-                    prev = sweep_line_len;
-                }
-
-                if prev == sweep_line_len {
-                    // there is not a previous line segment in S?
-                    inner!(event).is_inside = false;
-                    inner!(event).in_out = false;
-                } else if sweep_line.map.keys_mut()[prev].edge_type != EdgeType::Normal {
-                    if prev == 0 {
-                        inner!(event).is_inside = true; // it is not relevant to set true or false
-                        inner!(event).in_out = false;
-                    } else {
-                        // the previous two line segments in S are overlapping line segments
-                        let sli = prev;
-                        sli -= 1;
-
-                        let ptr_prev = sweep_line.map.keys_mut()[prev];
-                        let ptr_sli = sweep_line.map.keys_mut()[sli];
-
-                        if ptr_prev.polygon_type == inner!(event).polygon_type {
-                            inner!(event).in_out = !ptr_prev.in_out;
-                            inner!(event).is_inside = !ptr_sli.in_out;
-                        } else {
-                            inner!(event).in_out = !ptr_sli.in_out;
-                            inner!(event).is_inside = !ptr_prev.in_out;
-                        }
-                    }
-                } else if inner!(event).polygon_type == sweep_line.map.keys_mut()[prev].polygon_type {
-                    inner!(event).is_inside = sweep_line.map.keys_mut()[prev].inside;
-                    inner!(event).in_out = sweep_line.map.keys_mut()[prev].in_out;
-                } else {
-                    inner!(event).is_inside = sweep_line.map.keys_mut()[prev].in_out;
-                    inner!(event).in_out = sweep_line.map.keys_mut()[prev].inside;
-                }
-
-                if (next + 1) != sweep_line_len {
-                    possible_intersection(&mut event, &mut sweep_line.map.keys_mut()[next], &mut event_holder, &mut event_queue)
-                }
-
-                if prev != sweep_line_len {
-                    possible_intersection(&mut event, &mut sweep_line.map.keys_mut()[next], &mut event_holder, &mut event_queue)
-                }
-*/
-            } else {
-/*
-                // NOTE: In this block, there is no insertion happening!
-
-                // the current line segment must be removed into the sweep_line
-                let sli = other!(event).position_in_sweep_line;
-                let mut prev = sli;
-                let next = prev + 1;
-                let sweep_line_len = sweep_line.len();
-
-                // Get the next and previous line segments to "event" in sweep_line
-                if prev != 0 {
-                    prev -= 1;
-                } else {
-                    prev = sweep_line_len;
-                }
-
-                match inner!(event).edge_type {
-                    Normal => {
-                        match operation_type {
-                            Intersection => {
-                                if other!(event).is_inside {
-                                    connector.add_segment(Segment::new(inner!(event).p, other!(event).p));
-                                }
-                            },
-                            Union => {
-                                if !(other!(event).is_inside) {
-                                    connector.add_segment(Segment::new(inner!(event).p, other!(event).p));
-                                }
-                            },
-                            Difference => {
-                                if (inner!(event).polygon_type == PolygonType::Subject) && !(other!(event).is_inside) ||
-                                   (inner!(event).polygon_type == PolygonType::Clipping && other!(event).is_inside) {
-                                        connector.add_segment(Segment::new(inner!(event).p, other!(event).p));
-                                }
-                            },
-                            Xor => {
-                                connector.add_segment(Segment::new(inner!(event).p, other!(event).p));
-                            }
-                        }
-                    },
-                    SameTransition => {
-                        if operation_type == Intersection || operation_type == Union {
-                            connector.add_segment(Segment::new(inner!(event).p, other!(event).p));
-                        }
-                    },
-                    DifferentTransition => {
-                        if operation_type == Difference {
-                            connector.add_segment(Segment::new(inner!(event).p, other!(event).p));
-                        }
-                    },
-                    NonContributing => { },
-                }
-
-                // delete line segment associated to event from sweep_line and
-                // check for intersection between the neighbors of "event" in sweep_line
-                sweep_line.remove(&sli);
-
-                if next != sweep_line_len && prev != sweep_line_len {
-                    let ptr_prev = sweep_line.map.keys_mut()[prev];
-                    let ptr_next = sweep_line.map.keys_mut()[next];
-                    possible_intersection(ptr_prev, ptr_next, &mut event_holder, &mut event_queue);
+    /// Runs one of the built-in algorithms, without the output filters
+    pub(crate) fn clip_with_algorithm(&self, other: &Self, algorithm: ClipAlgorithm, operation_type: BoolOpType,
+                                      options: &ClipOptions, guard: &mut OperationGuard)
+    -> Result<Vec<Self>, ClipError>
+    {
+        match algorithm {
+            ClipAlgorithm::Arrangement | ClipAlgorithm::GreinerHormann => {
+                ::utils::check_finite(self.nodes.iter().chain(other.nodes.iter()))?;
+                match self.trivial_result(other, operation_type) {
+                    Some(result) => Ok(result.map(|polygons| polygons.into_iter().map(Cow::into_owned).collect()).unwrap_or_default()),
+                    None if algorithm == ClipAlgorithm::GreinerHormann => ::greiner_hormann::clip(self, other, operation_type, guard),
+                    None => ::multi_polygon::clip_arrangement(::std::slice::from_ref(self), ::std::slice::from_ref(other),
+                                                              operation_type, options, guard),
                 }
-            */
-            }
+            },
+            ClipAlgorithm::Rectilinear if self.is_rectilinear() && other.is_rectilinear() => {
+                ::utils::check_finite(self.nodes.iter().chain(other.nodes.iter()))?;
+                ::rects::clip_rectilinear(&[&self.nodes], &[&other.nodes], operation_type, guard)
+            },
+            ClipAlgorithm::Rectilinear => self.clip_with_algorithm(other, ClipAlgorithm::Arrangement, operation_type, options, guard),
+            ClipAlgorithm::Scanbeam if self.has_integer_coordinates() && other.has_integer_coordinates() => {
+                let result = ::scanbeam::clip_scanbeam(&[PolygonI64::from(self)], &[PolygonI64::from(other)], operation_type, guard)?;
+                Ok(result.into_iter().map(Polygon::from).collect())
+            },
+            ClipAlgorithm::Scanbeam => self.clip_with_algorithm(other, ClipAlgorithm::Arrangement, operation_type, options, guard),
         }
-
-        connector.to_polygons()
     }
-}
-
-// DO NOT modify the return type, otherwise you will invalidate all internal pointers!
-fn create_sweep_events(nodes: &[Point2D], polygon_type: PolygonType) -> Box<[SweepEventRef]> {
-
-    let vec_len = nodes.len() * 2;
-    let mut new_vec = Vec::<SweepEventRef>::with_capacity(vec_len);
-    unsafe { new_vec.set_len(vec_len); }
 
-    let iter1 = nodes.iter();
-    let mut iter2 = nodes.iter().cycle();
-    iter2.next();
-
-    let mut cur_pt_idx = 0;
-    for (cur_point, next_point) in iter1.zip(iter2) {
+    /// Same as `try_clip`, but also returns the warnings about degenerate input
+    /// contours, which are treated as empty (see `is_degenerate`), and about
+    /// the result if `ClipOptions::verify` is set
+    pub fn clip_with_warnings(&self, other: &Self, operation_type: BoolOpType, options: &ClipOptions)
+    -> Result<(Vec<Self>, Vec<ClipWarning>), ClipError>
+    {
+        let result = self.try_clip(other, operation_type, options)?;
+        let mut warnings: Vec<ClipWarning> = self.degenerate_warning(0).into_iter().chain(other.degenerate_warning(1)).collect();
+        if options.verify {
+            let operand = |p: &Self| if p.is_degenerate() { Vec::new() } else { vec![p.clone()] };
+            warnings.extend(::reference::verify(&operand(self), &operand(other), operation_type, &result));
+        }
+        Ok((result, warnings))
+    }
 
-        let mut e1_left = true;
-        let mut e2_left = true;
+    /// Returns true if the polygon has vertices, but does not enclose any area:
+    /// it has less than three vertices, or all of them are duplicates or lie
+    /// on a single line. The boolean operations treat such polygons as empty.
+    pub fn is_degenerate(&self) -> bool {
+        self.degenerate_warning(0).is_some()
+    }
 
-        if cur_point.x < next_point.x {
-            e2_left = false;
-        } else if cur_point.x > next_point.x {
-            e1_left = false;
-        } else if cur_point.y < next_point.y {
-            // The line segment is vertical.
-            // The bottom endpoint is the left endpoint
-            e2_left = false;
+    /// Returns the warning for a degenerate contour, with `ring` as its index
+    pub(crate) fn degenerate_warning(&self, ring: usize) -> Option<ClipWarning> {
+        if self.nodes.is_empty() {
+            None
+        } else if self.nodes.len() < 3 {
+            Some(ClipWarning::TooFewVertices { ring: ring })
+        } else if ::clean::clean_nodes(&self.nodes, true, 0.0).len() < 3 {
+            Some(ClipWarning::ZeroArea { ring: ring })
         } else {
-            e1_left = false;
+            None
         }
-
-        let e1_idx = cur_pt_idx;
-        let e2_idx = cur_pt_idx + 1;
-
-        let e1 = SweepEventRef {
-            inner: UnsafeCell::new(SweepEvent {
-                p: cur_point,
-                other: unsafe { ::std::mem::zeroed() },
-                left: e1_left,
-                position_in_sweep_line: 0,
-                polygon_type: polygon_type,
-                in_out: false,
-                is_inside: false,
-                edge_type: EdgeType::Normal,
-            })
-        };
-
-        unsafe { *new_vec.get_unchecked_mut(e1_idx) = e1; }
-
-        let e2 = SweepEventRef {
-            inner: UnsafeCell::new(SweepEvent {
-                p: next_point,
-                other: unsafe { new_vec.get_unchecked(e1_idx) }, // new_vec does not live long enough
-                position_in_sweep_line: 0,
-                left: e2_left,
-                polygon_type: polygon_type,
-                in_out: false,
-                is_inside: false,
-                edge_type: EdgeType::Normal,
-            })
-        };
-
-        unsafe { *new_vec.get_unchecked_mut(e2_idx) = e2; }
-        unsafe { (*new_vec.get_unchecked_mut(e1_idx).inner.get()).other = new_vec.get_unchecked(e2_idx); }
-
-        cur_pt_idx += 2;
     }
 
-    // assert that the vector does not have moved (in memory)
-    // if it did, the internal pointer would be garbage
-    assert_eq!(new_vec.len(), vec_len);
-    assert_eq!(new_vec.capacity(), vec_len);
+    /// Runs the boolean operation, but returns the inputs that are part of the
+    /// result without changes as `Cow::Borrowed` instead of cloning them
+    ///
+    /// This happens in the trivial cases, for example if the bounding boxes
+    /// do not overlap, so a fast path for disjoint inputs does not have to
+    /// copy large rings. Returns None if the result is empty.
+    pub fn clip_cow<'a>(&'a self, other: &'a Self, operation_type: BoolOpType) -> Option<Vec<Cow<'a, Self>>> {
+        match self.trivial_result(other, operation_type) {
+            Some(result) => result,
+            None => self.calculate(other, operation_type).map(|polygons| polygons.into_iter().map(Cow::Owned).collect()),
+        }
+    }
 
-    new_vec.into_boxed_slice()
-}
+    /// Returns the result of the operation if it can be determined without
+    /// running the sweep (the outer Option), borrowing the unchanged inputs
+    fn trivial_result<'a>(&'a self, other: &'a Self, operation_type: BoolOpType) -> Option<Option<Vec<Cow<'a, Self>>>> {
+        use self::BoolOpType::*;
 
-/// NOTE: `possible_intersection` is the only function that calls `point::line_intersect`
-fn possible_intersection<'a>(e1: &'a SweepEventRef<'a>, e2: &'a SweepEventRef<'a>,
-                             event_holder: &'a mut Vec<SweepEventRef<'a>>,
-                             eq: &'a mut BinaryHeap<&'a SweepEventRef<'a>>)
-{
-
-    // This function essentially moves events from the event_vec to the event_holder
-    // Do not call this function on the same index twice!
-    //
-    // NOTE: `event.other` gets mutated!
-    // `event_holder` and `eq` get pushed to!
-    fn divide_segment<'a>(event: &'a mut SweepEvent<'a>,
-                          divide_pt: &'a Point2D,
-                          event_holder: &'a mut Vec<SweepEventRef<'a>>,
-                          eq: &'a mut BinaryHeap<&SweepEventRef<'a>>)
-    {
-        {
-            // push right event
-            event_holder.push(SweepEventRef {
-                inner: UnsafeCell::new(SweepEvent {
-                    p: divide_pt,
-                    left: false,
-                    polygon_type: event.polygon_type,
-                    other: event.other,
-                    in_out: false,
-                    position_in_sweep_line: 0,
-                    is_inside: false,
-                    edge_type: event.edge_type,
-            })});
-
-            // push left event
-            event_holder.push(SweepEventRef {
-                inner: UnsafeCell::new(SweepEvent {
-                    p: divide_pt,
-                    left: true,
-                    polygon_type: event.polygon_type,
-                    other: event.other,
-                    in_out: false,
-                    position_in_sweep_line: 0,
-                    is_inside: false,
-                    edge_type: event.edge_type,
-            })});
+        // Trivial result case - either self or other polygon do not exist
+        // or do not enclose any area (lines, points, collinear rings).
+        // Degenerate contours are treated as empty and dropped from the result.
+        let self_empty = self.nodes.is_empty() || self.is_degenerate();
+        let other_empty = other.nodes.is_empty() || other.is_degenerate();
+
+        if self_empty || other_empty {
+            let kept = match operation_type {
+                Intersection => None,
+                Difference => if self_empty { None } else { Some(self) },
+                Union | Xor => if !self_empty { Some(self) } else if !other_empty { Some(other) } else { None },
+            };
+            return Some(kept.map(|polygon| vec![Cow::Borrowed(polygon)]));
         }
 
-        let last = event_holder.len() - 1;
-
-        {
-            let left = event_holder.get_mut(last).unwrap();
+        // Trivial result case - boundaries don't overlap
+        // NOTE: This should not be done here, this should be done in the MultiPolygon
+        // class (R* tree)
+        let self_bbox = self.bounding_box();
+        let other_bbox = other.bounding_box();
 
-            if inner!(left).compare(unsafe { &(*(*event.other).inner.get()) }) {
-                unsafe { (*(*event.other).inner.get()).left = true; }
-                inner_mut!(left).left = false;
+        if !self_bbox.overlaps(&other_bbox) {
+            match operation_type {
+                Difference => return Some(Some(vec![Cow::Borrowed(self)])),
+                Intersection => return Some(None),
+                Union | Xor => return Some(Some(vec![Cow::Borrowed(self), Cow::Borrowed(other)]))
             }
         }
 
-        let left  = event_holder.get(last).unwrap();
-        let right = event_holder.get(last - 1).unwrap();
-
-        /*
-            event->other->other = left;   
-            event->other = right;
-            eq.push(left);
-            eq.push(right);
-        */
-
-        // NOTE: this will be invalidated when the event_holder resizes !!!
-        // CHECK THAT THIS DOES NOT HAPPEN!
-        unsafe { (*(*event.other).inner.get()).other = left };
-        event.other = right;
-
-        eq.push(left.clone());
-        eq.push(right.clone());
+        None
     }
 
-    // end of divide_segment()
-
-    // note: this will COPY the point!
-    let e1_other_p = other!(e1).p;
-    let e2_other_p = other!(e2).p;
-
-    let result = ::point::line_intersect(&inner!(e1).p, &e1_other_p, &inner!(e2).p, &e2_other_p);
-
-    let (a, b) = match result {
-        Some(a) => (a.0, a.1),
-        None => return, // no intersections found
-    };
-
-    let new_b;
-
-    match b {
-        Some(new) => {
-            if inner!(e1).polygon_type == inner!(e2).polygon_type {
-                eprintln!("A polygon has overlapping edges. \n
-                           Sorry, but the program does not work yet
-                           with this kind of polygon");
-                return;
-            }
-            new_b = new;
-        },
-        None => {
-            if !((inner!(e1).p == inner!(e2).p) || (e1_other_p == e2_other_p)){
-                if *inner!(e1).p != a && *e1_other_p != a {
-                    // if a is not an endpoint of the line segment associated to e1 then divide "e1"
-                    // divide_segment(e1, a);
-                }
-
-                if *inner!(e2).p != a && *e2_other_p != a {
-                    // divide_segment(e2, a);
-                }
-            }
-
-            // else:
-            // the line segments intersect at an endpoint of both line segments
-            return;
-        },
+    /// Absolute area of the polygon, as if it was closed
+    pub fn area(&self) -> fsize {
+        ::utils::calculate_signed_area(&self.nodes).abs()
     }
 
-    // the line segments overlap
-    let mut sorted_events: [Option<&mut SweepEvent>; 4] = [None, None, None, None];
-
-/*
-    if inner!(e1).p == inner!(e2).p {
-        sorted_events.push(None)
-    } else if inner!(e1).compare(inner_mut!(e2)) {
-        sorted_events.push(Some(inner_mut!(e2)));
-        sorted_events.push(Some(inner_mut!(e1)));
-    } else {
-        sorted_events.push(Some(inner_mut!(e1)));
-        sorted_events.push(Some(inner_mut!(e2)));
+    /// Runs the operation with the default algorithm and options, None if
+    /// the result is empty
+    fn calculate(&self, other: &Self, operation_type: BoolOpType)
+    -> Option<Vec<Self>>
+    {
+        let result = self.clip_with_algorithm(other, ClipAlgorithm::default(), operation_type,
+                                              &ClipOptions::default(), &mut OperationGuard::unlimited()).ok()?;
+        if result.is_empty() { None } else { Some(result) }
     }
+}
 
-    if e1_other_p == e2_other_p {
+#[test]
+fn test_clip_cow() {
+    let a = Polygon::rect(0.0, 0.0, 1.0, 1.0);
+    let b = Polygon::rect(5.0, 5.0, 1.0, 1.0);
 
-    } else if inner!(e1).compare(inner!(e2)) {
-        sorted_events.push(Some(other_mut!(e2)));
-        sorted_events.push(Some(other_mut!(e1)));
-    } else {
-        sorted_events.push(Some(other_mut!(e1)));
-        sorted_events.push(Some(other_mut!(e2)));
-    }
+    let result = a.clip_cow(&b, BoolOpType::Union).unwrap();
+    assert_eq!(result.len(), 2);
+    assert!(result.iter().all(|p| match *p { Cow::Borrowed(_) => true, Cow::Owned(_) => false }));
+    assert!(a.clip_cow(&b, BoolOpType::Intersection).is_none());
+}
 
+#[test]
+fn test_degenerate_contours() {
+    let square = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    let line = Polygon::from(vec![(0.0, 0.0), (5.0, 5.0)]);
+    let flat = Polygon::from(vec![(0.0, 1.0), (1.0, 1.0), (3.0, 1.0), (1.0, 1.0)]);
+    let options = ClipOptions::default();
 
-    if sorted_events.len() == 2 {
-        // are both line segments equal?
-        inner_mut!(e1).edge_type = EdgeType::NonContributing;
-        other_mut!(e1).edge_type = EdgeType::NonContributing;
-        return;
-    }
+    assert!(line.is_degenerate() && flat.is_degenerate() && !square.is_degenerate());
 
-    if sorted_events.len() == 3 {
-        // the line segments share an endpoint
-        sorted_events.get(1).unwrap().edge_type = EdgeType::NonContributing;
-        unsafe { (*(*sorted_events[1].unwrap().other).inner.get()) }.edge_type = EdgeType::NonContributing;
+    // a degenerate contour is treated as empty instead of failing the operation
+    let (result, warnings) = square.clip_with_warnings(&flat, BoolOpType::Difference, &options).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes, square.nodes);
+    assert_eq!(warnings, vec![ClipWarning::ZeroArea { ring: 1 }]);
 
-        if sorted_events[0].is_some() {
-            // is the right endpoint the shared point?
-            sorted_events[0].unwrap().edge_type = if inner!(e1).in_out == inner!(e2).in_out {
-                EdgeType::SameTransition
-            } else {
-                EdgeType::DifferentTransition
-            };
-            divide_segment(sorted_events[0].unwrap(), sorted_events[1].unwrap().p, event_holder, eq);
-        } else {
-            // the shared point is the left endpoint
-            sorted_events[2].unwrap().edge_type = if inner!(e1).in_out == inner!(e2).in_out {
-                EdgeType::SameTransition
-            } else {
-                EdgeType::DifferentTransition
-            };
-            divide_segment(sorted_events[2].unwrap(), sorted_events[1].unwrap().p, event_holder, eq);
-        }
+    let (result, warnings) = line.clip_with_warnings(&square, BoolOpType::Union, &options).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes, square.nodes);
+    assert_eq!(warnings, vec![ClipWarning::TooFewVertices { ring: 0 }]);
 
-        return;
-    }
+    assert!(square.intersection(&line).is_none());
+    assert!(line.difference(&square).is_none());
+}
 
-    // sorted_events are all initialized
+#[test]
+fn test_polygon_conversions() {
+    let from_tuples = Polygon::from(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+    let from_arrays = Polygon::from(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]);
+    let collected: Polygon = from_tuples.nodes.iter().cloned().collect();
+
+    assert_eq!(from_tuples.nodes, from_arrays.nodes);
+    assert_eq!(from_tuples.nodes, collected.nodes);
+    assert_eq!(collected.winding, Some(WindingOrder::CounterClockwise));
+    assert_eq!(collected.bbox, Some(Bbox::new(0.0, 0.0, 1.0, 1.0)));
+    assert!(collected.is_closed && !collected.is_hole);
+}
 
-    if (*sorted_events[0].unwrap()) != (*(*sorted_events[3].unwrap().other).inner.get()) {
-        // no line segment includes totally the other one
-        sorted_events[1].unwrap().edge_type = EdgeType::NonContributing;
-        sorted_events[2].unwrap().edge_type = if inner!(e1).in_out == inner!(e2).in_out {
-            EdgeType::SameTransition
-        } else {
-            EdgeType::DifferentTransition
-        };
-        divide_segment(sorted_events[0].unwrap(), sorted_events[1].unwrap().p, event_holder, eq);
-        divide_segment(sorted_events[1].unwrap(), sorted_events[2].unwrap().p, event_holder, eq);
-        return;
-    }
-    // one line segment includes the other one
-    sorted_events[1].unwrap().edge_type = EdgeType::NonContributing;
-    unsafe { (*(*sorted_events[1].unwrap().other).inner.get()) }.edge_type = EdgeType::NonContributing;
-    divide_segment(sorted_events[0].unwrap(), sorted_events[1].unwrap().p, event_holder, eq);
-
-    unsafe { (*(*sorted_events[3].unwrap().other).inner.get()) }.edge_type =
-        if inner!(e1).in_out == inner!(e2).in_out {
-            EdgeType::SameTransition
-        } else {
-            EdgeType::DifferentTransition
-        };
+#[test]
+fn test_clip_grid_aligned() {
+    use self::BoolOpType::*;
+
+    // vertical edges, shared edges and corners: many events share their x
+    // coordinate or their point
+    let options = ClipOptions::default();
+    let area = |a: &Polygon, b: &Polygon, op| -> (usize, fsize) {
+        let result = a.clip(b, op, &options).unwrap_or_default();
+        (result.len(), result.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum())
+    };
+    let square = Polygon::rect(0.0, 0.0, 1.0, 1.0);
+
+    // side by side and stacked: the shared edge disappears in the union
+    let right = Polygon::rect(1.0, 0.0, 1.0, 1.0);
+    let above = Polygon::rect(0.0, 1.0, 1.0, 1.0);
+    for other in [&right, &above].iter() {
+        assert_eq!(area(&square, other, Union), (1, 2.0));
+        assert_eq!(area(&square, other, Intersection), (0, 0.0));
+        assert_eq!(area(&square, other, Difference), (1, 1.0));
+        assert_eq!(area(&square, other, Xor), (1, 2.0));
+    }
 
-    divide_segment(&mut (*(*sorted_events[3].unwrap().other).inner.get()), sorted_events[2].unwrap().p, event_holder, eq);
-*/
+    // touching at a corner only
+    let diagonal = Polygon::rect(1.0, 1.0, 1.0, 1.0);
+    assert_eq!(area(&square, &diagonal, Intersection), (0, 0.0));
+    assert_eq!(area(&square, &diagonal, Union).1, 2.0);
+
+    // a shared vertical edge that is only partly covered
+    let shifted = Polygon::rect(1.0, 0.5, 1.0, 1.0);
+    assert_eq!(area(&square, &shifted, Union), (1, 2.0));
+    assert_eq!(area(&square, &shifted, Intersection), (0, 0.0));
+
+    // overlapping squares with collinear edges, and identical squares
+    let wide = Polygon::rect(0.0, 0.0, 2.0, 1.0);
+    assert_eq!(area(&wide, &square, Difference), (1, 1.0));
+    assert_eq!(area(&wide, &square, Intersection), (1, 1.0));
+    assert_eq!(area(&square, &square, Xor), (0, 0.0));
+    assert_eq!(area(&square, &square, Union), (1, 1.0));
+
+    // a square hole that shares the x coordinates of the cut
+    let frame = Polygon::rect(0.0, 0.0, 3.0, 3.0);
+    let middle = Polygon::rect(1.0, 1.0, 1.0, 1.0);
+    assert_eq!(area(&frame, &middle, Difference), (2, 8.0));
+    let column = Polygon::rect(1.0, 0.0, 1.0, 3.0);
+    assert_eq!(area(&frame, &column, Difference), (2, 6.0));
 }
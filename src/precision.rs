@@ -0,0 +1,118 @@
+//! Polygons with a fixed coordinate type, independent of `fsize`
+//!
+//! `Polygon32` and `Polygon64` only store the nodes and flags of a polygon,
+//! so a pipeline can keep its data in `f32` (half the memory) and convert it
+//! to `Polygon` for the boolean operations. The operations compute in `fsize`,
+//! use the `adaptive_precision` or `exact_predicates` feature where the
+//! robustness of `f32` is not enough.
+//!
+//! Conversions that can not lose precision (`f32` to `f64`, and to and from
+//! `Polygon` where `fsize` is the same type) are exact, all others round to
+//! the nearest representable value.
+//!
+//! `PolygonI64` is the exception: its boolean operations (see `clip_i64`)
+//! compute on the integer coordinates directly, with a scanbeam sweep.
+
+use polygon::Polygon;
+use {Point2D, fsize};
+
+/// Nodes and flags of a polygon, with coordinates of type `T`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TypedPolygon<T> {
+    /// The points that this polygon is made of, as `[x, y]`
+    pub nodes: Vec<[T; 2]>,
+    /// Is this polygon a hole?
+    pub is_hole: bool,
+    /// Is this polygon closed?
+    pub is_closed: bool,
+}
+
+/// Polygon with `f32` coordinates
+pub type Polygon32 = TypedPolygon<f32>;
+
+/// Polygon with `f64` coordinates
+pub type Polygon64 = TypedPolygon<f64>;
+
+/// Polygon with `i64` coordinates
+pub type PolygonI64 = TypedPolygon<i64>;
+
+macro_rules! impl_typed_polygon {
+    ($t:ty) => {
+        impl<'a> From<&'a Polygon> for TypedPolygon<$t> {
+            fn from(polygon: &'a Polygon) -> Self {
+                TypedPolygon {
+                    nodes: polygon.nodes.iter().map(|p| [p.x as $t, p.y as $t]).collect(),
+                    is_hole: polygon.is_hole,
+                    is_closed: polygon.is_closed,
+                }
+            }
+        }
+
+        impl From<TypedPolygon<$t>> for Polygon {
+            fn from(polygon: TypedPolygon<$t>) -> Self {
+                let nodes = polygon.nodes.iter().map(|p| Point2D { x: p[0] as fsize, y: p[1] as fsize }).collect();
+                Polygon::from_parts(nodes, polygon.is_hole, polygon.is_closed)
+            }
+        }
+    }
+}
+
+impl_typed_polygon!(f32);
+impl_typed_polygon!(f64);
+
+impl<'a> From<&'a Polygon> for PolygonI64 {
+    fn from(polygon: &'a Polygon) -> Self {
+        TypedPolygon {
+            nodes: polygon.nodes.iter().map(|p| [p.x.round() as i64, p.y.round() as i64]).collect(),
+            is_hole: polygon.is_hole,
+            is_closed: polygon.is_closed,
+        }
+    }
+}
+
+impl From<PolygonI64> for Polygon {
+    fn from(polygon: PolygonI64) -> Self {
+        let nodes = polygon.nodes.iter().map(|p| Point2D { x: p[0] as fsize, y: p[1] as fsize }).collect();
+        Polygon::from_parts(nodes, polygon.is_hole, polygon.is_closed)
+    }
+}
+
+impl From<Polygon32> for Polygon64 {
+    fn from(polygon: Polygon32) -> Self {
+        TypedPolygon {
+            nodes: polygon.nodes.iter().map(|p| [p[0] as f64, p[1] as f64]).collect(),
+            is_hole: polygon.is_hole,
+            is_closed: polygon.is_closed,
+        }
+    }
+}
+
+impl From<Polygon64> for Polygon32 {
+    fn from(polygon: Polygon64) -> Self {
+        TypedPolygon {
+            nodes: polygon.nodes.iter().map(|p| [p[0] as f32, p[1] as f32]).collect(),
+            is_hole: polygon.is_hole,
+            is_closed: polygon.is_closed,
+        }
+    }
+}
+
+#[test]
+fn test_typed_polygons() {
+    let polygon = Polygon::rect(0.1, 0.2, 1.0, 3.0);
+
+    // f32 -> f64 -> f32 is exact
+    let wide = Polygon64::from(&polygon);
+    assert_eq!(Polygon::from(wide.clone()), polygon);
+    assert_eq!(Polygon32::from(wide.clone()), Polygon32::from(&polygon));
+    assert_eq!(Polygon64::from(Polygon32::from(&polygon)), wide);
+
+    // f64 -> f32 rounds
+    let precise = Polygon64 { nodes: vec![[0.1, 0.0], [1.0, 1e-10], [0.0, 1.0]], is_hole: true, is_closed: true };
+    let rounded = Polygon32::from(precise.clone());
+    assert_eq!(rounded.nodes[0], [0.1f32, 0.0]);
+    assert!(Polygon64::from(rounded) != precise);
+
+    let converted = Polygon::from(precise);
+    assert!(converted.is_hole && converted.bbox.is_some());
+}
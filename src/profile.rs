@@ -0,0 +1,97 @@
+//! Counters and timers for the steps of the boolean operations (`profiling` feature)
+//!
+//! The steps are measured per thread. Run an operation, then call
+//! `take_profile_report` on the same thread to see where its time went:
+//!
+//! ```rust
+//! # use polyclip::{Polygon, BoolOpType, ClipOptions};
+//! # let (a, b) = (Polygon::rect(0.0, 0.0, 2.0, 2.0), Polygon::rect(1.0, 1.0, 2.0, 2.0));
+//! let result = a.try_clip(&b, BoolOpType::Union, &ClipOptions::default());
+//! let report = polyclip::take_profile_report();
+//! println!("{} events popped", report.event_queue_pop.calls);
+//! ```
+
+use std::cell::RefCell;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Number of calls of one step and the time spent in them
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Timing {
+    pub calls: u64,
+    /// Always zero on `wasm32-unknown-unknown`, which has no clock
+    pub total: Duration,
+}
+
+/// Counters and timers of the steps of the boolean operations, see
+/// `take_profile_report`
+///
+/// The steps are those of the arrangement, which runs the operations of
+/// `Polygon` and `MultiPolygon`: its intersection sweep and the tracing of
+/// the result rings. There is no connector that links result segments into
+/// chains, so there is no counter for it.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ProfileReport {
+    /// Insertions into the event queue. The intersection sweep sorts its
+    /// initial events at once, which counts as one call.
+    pub event_queue_push: Timing,
+    /// Removals from the event queue, one call for all events at a point
+    pub event_queue_pop: Timing,
+    /// Insertions into the sweep line
+    pub sweep_line_insert: Timing,
+    /// Removals from the sweep line
+    pub sweep_line_remove: Timing,
+    /// Checks of two neighbouring segments for an intersection
+    pub possible_intersection: Timing,
+    /// Boundary edges of the result that were linked to the next edge of
+    /// their ring, by rotating around their end vertex
+    pub ring_link: Timing,
+}
+
+thread_local! {
+    static REPORT: RefCell<ProfileReport> = RefCell::new(ProfileReport::default());
+}
+
+/// Returns the counters of the current thread and resets them
+pub fn take_profile_report() -> ProfileReport {
+    REPORT.with(|report| ::std::mem::take(&mut *report.borrow_mut()))
+}
+
+/// Runs `f` and adds its call and duration to the step that `timing` selects
+#[inline]
+pub(crate) fn measure<R, F: FnOnce() -> R>(timing: fn(&mut ProfileReport) -> &mut Timing, f: F) -> R {
+    #[cfg(not(target_arch = "wasm32"))]
+    let started = Instant::now();
+    let result = f();
+    REPORT.with(|report| {
+        let mut report = report.borrow_mut();
+        let timing = timing(&mut report);
+        timing.calls += 1;
+        #[cfg(not(target_arch = "wasm32"))]
+        { timing.total += started.elapsed(); }
+    });
+    result
+}
+
+#[test]
+fn test_profile_report() {
+    use multi_polygon::MultiPolygon;
+    use options::ClipOptions;
+    use polygon::{Polygon, BoolOpType};
+
+    take_profile_report();
+    let a = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 2.0, 2.0)]);
+    let b = MultiPolygon::new(vec![Polygon::rect(1.0, 1.0, 2.0, 2.0)]);
+    a.try_clip(&b, BoolOpType::Union, &ClipOptions::default()).unwrap();
+
+    let report = take_profile_report();
+    assert_eq!(report.event_queue_pop.calls, 10);
+    assert!(report.sweep_line_insert.calls > 0);
+    assert!(report.possible_intersection.calls > 0);
+    // one link per edge of the united ring
+    assert_eq!(report.ring_link.calls, 8);
+
+    // the counters were reset
+    assert_eq!(take_profile_report(), ProfileReport::default());
+}
@@ -0,0 +1,111 @@
+//! Tracking where the vertices and edges of a result come from
+
+use multi_polygon::MultiPolygon;
+use polygon::{Polygon, BoolOpType};
+use arrangement::Arrangement;
+
+/// Edge `edge` (from `nodes[edge]` to the next node) of the ring `ring` of an input
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EdgeRef {
+    pub ring: usize,
+    pub edge: usize,
+}
+
+/// Origin of a vertex of a result polygon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VertexSource {
+    /// The vertex is the start point of the given subject edge
+    Subject(EdgeRef),
+    /// The vertex is the start point of the given clip edge
+    Clip(EdgeRef),
+    /// The vertex is a new point where the boundaries cross, in the interior of
+    /// the given subject and / or clip edge
+    Intersection(Option<EdgeRef>, Option<EdgeRef>),
+}
+
+/// Origin of an edge of a result polygon
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EdgeSource {
+    /// The edge is a part of the given subject edge
+    Subject(EdgeRef),
+    /// The edge is a part of the given clip edge, for example a "cut" edge
+    Clip(EdgeRef),
+    /// Subject and clip boundary coincide along the edge
+    Both(EdgeRef, EdgeRef),
+}
+
+/// Result polygon together with the origin of its vertices and edges
+///
+/// `vertices[i]` describes `polygon.nodes[i]`, `edges[i]` describes the edge
+/// from `polygon.nodes[i]` to the next node.
+#[derive(Debug, Clone)]
+pub struct TracedPolygon {
+    pub polygon: Polygon,
+    pub vertices: Vec<VertexSource>,
+    pub edges: Vec<EdgeSource>,
+}
+
+impl VertexSource {
+
+    /// Updates the source with one more input edge passing through the vertex.
+    /// Original vertices take precedence over intersections, the subject takes
+    /// precedence over the clip.
+    pub(crate) fn merge(&self, is_start: bool, is_end: bool, from_clip: bool, edge: EdgeRef) -> Self {
+        use self::VertexSource::*;
+        match (self.clone(), from_clip) {
+            (Subject(e), _) => Subject(e),
+            (Clip(e), false) => if is_start { Subject(edge) } else { Clip(e) },
+            (Clip(e), true) => Clip(e),
+            (Intersection(a, b), _) if is_end => Intersection(a, b),
+            (Intersection(_, _), false) if is_start => Subject(edge),
+            (Intersection(_, _), true) if is_start => Clip(edge),
+            (Intersection(a, b), false) => Intersection(a.or(Some(edge)), b),
+            (Intersection(a, b), true) => Intersection(a, b.or(Some(edge))),
+        }
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Runs the boolean operation and reports for every vertex and edge of the
+    /// result whether it comes from `self` (the subject), from `other` (the
+    /// clip) or from an intersection, with the indices of the source edges
+    ///
+    /// Ring indices refer to `self.polygons` and `other.polygons`. Unlike the
+    /// other boolean operations, the result is not sorted canonically.
+    pub fn clip_with_provenance<U>(&self, other: &MultiPolygon<U>, operation_type: BoolOpType) -> Vec<TracedPolygon> {
+        let arrangement = Arrangement::new(&self.polygons, &other.polygons);
+        match operation_type {
+            BoolOpType::Union => arrangement.extract_traced(|a, b| a || b),
+            BoolOpType::Intersection => arrangement.extract_traced(|a, b| a && b),
+            BoolOpType::Difference => arrangement.extract_traced(|a, b| a && !b),
+            BoolOpType::Xor => arrangement.extract_traced(|a, b| a != b),
+        }
+    }
+}
+
+#[test]
+fn test_clip_with_provenance() {
+    let viewport = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 2.0, 2.0)]);
+    let feature = MultiPolygon::new(vec![Polygon::rect(1.0, 1.0, 2.0, 2.0)]);
+
+    let result = feature.clip_with_provenance(&viewport, BoolOpType::Intersection);
+    assert_eq!(result.len(), 1);
+    let traced = &result[0];
+    assert_eq!(traced.vertices.len(), 4);
+
+    for (i, node) in traced.polygon.nodes.iter().enumerate() {
+        let expected = match (node.x, node.y) {
+            (1.0, 1.0) => VertexSource::Subject(EdgeRef { ring: 0, edge: 0 }),
+            (2.0, 2.0) => VertexSource::Clip(EdgeRef { ring: 0, edge: 2 }),
+            (2.0, 1.0) => VertexSource::Intersection(Some(EdgeRef { ring: 0, edge: 0 }), Some(EdgeRef { ring: 0, edge: 1 })),
+            (1.0, 2.0) => VertexSource::Intersection(Some(EdgeRef { ring: 0, edge: 3 }), Some(EdgeRef { ring: 0, edge: 2 })),
+            _ => panic!("unexpected vertex {:?}", node),
+        };
+        assert_eq!(traced.vertices[i], expected);
+    }
+
+    // the edges along the viewport border are "cut" edges
+    let cut_edges = traced.edges.iter().filter(|e| match **e { EdgeSource::Clip(_) => true, _ => false }).count();
+    assert_eq!(cut_edges, 2);
+}
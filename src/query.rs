@@ -0,0 +1,76 @@
+//! Spatial queries on the members of a `MultiPolygon`
+//!
+//! The queries test the cached bounding boxes of the members first and only
+//! look at the vertices of the members that pass, so each query takes time
+//! linear in the number of members plus the vertices of the candidates.
+
+use multi_polygon::MultiPolygon;
+use utils::{locate_point_in_ring, distance_point_segment, PointLocation};
+use edges::Edges;
+use {Point2D, Bbox, fsize};
+
+impl<T> MultiPolygon<T> {
+
+    /// Indices of the polygons whose bounding box overlaps `bbox`
+    pub fn query_bbox(&self, bbox: &Bbox) -> Vec<usize> {
+        self.members().enumerate()
+            .filter(|&(_, (_, member))| member.map_or(false, |b| b.overlaps(bbox)))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Indices of the polygons that contain `p` (points on the boundary count
+    /// as contained). Holes are members of their own, so a point inside of a
+    /// hole returns both the hole and its exterior.
+    pub fn query_point(&self, p: &Point2D) -> Vec<usize> {
+        self.members().enumerate()
+            .filter(|&(_, (_, member))| member.map_or(false, |b| b.contains_point(p)))
+            .filter(|&(_, (polygon, _))| polygon.is_closed && locate_point_in_ring(p, &polygon.nodes) != PointLocation::Outside)
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Indices of the `k` polygons closest to `p`, nearest first. The distance
+    /// of a polygon that contains `p` is zero, ties are ordered by index.
+    pub fn nearest(&self, p: &Point2D, k: usize) -> Vec<usize> {
+        let mut distances: Vec<(fsize, usize)> = self.polygons.iter().enumerate()
+            .filter(|&(_, polygon)| !polygon.nodes.is_empty())
+            .map(|(idx, polygon)| {
+                let distance = if polygon.is_closed && locate_point_in_ring(p, &polygon.nodes) != PointLocation::Outside {
+                    0.0
+                } else {
+                    Edges::new(&polygon.nodes, polygon.is_closed)
+                        .map(|(a, b)| distance_point_segment(p, &a, &b))
+                        .fold(distance_point_segment(p, &polygon.nodes[0], &polygon.nodes[0]), fsize::min)
+                };
+                (distance, idx)
+            })
+            .collect();
+
+        distances.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal).then(a.1.cmp(&b.1)));
+        distances.into_iter().take(k).map(|(_, idx)| idx).collect()
+    }
+}
+
+#[test]
+fn test_spatial_queries() {
+    use polygon::Polygon;
+
+    let multi = MultiPolygon::new(vec![
+        Polygon::rect(0.0, 0.0, 4.0, 4.0),
+        Polygon { is_hole: true, .. Polygon::rect(1.0, 1.0, 1.0, 1.0) },
+        Polygon::rect(10.0, 0.0, 2.0, 2.0),
+        Polygon::from(vec![(5.0, 5.0), (6.0, 5.0), (5.0, 6.0)]),
+    ]);
+
+    assert_eq!(multi.query_bbox(&Bbox::new(3.0, 3.0, 5.5, 5.5)), vec![0, 3]);
+    assert_eq!(multi.query_bbox(&Bbox::new(20.0, 20.0, 21.0, 21.0)), Vec::<usize>::new());
+
+    assert_eq!(multi.query_point(&Point2D { x: 3.0, y: 3.0 }), vec![0]);
+    assert_eq!(multi.query_point(&Point2D { x: 1.5, y: 1.5 }), vec![0, 1]);
+    assert_eq!(multi.query_point(&Point2D { x: 5.9, y: 5.9 }), Vec::<usize>::new());
+
+    assert_eq!(multi.nearest(&Point2D { x: 9.0, y: 1.0 }, 2), vec![2, 0]);
+    assert_eq!(multi.nearest(&Point2D { x: 6.0, y: 6.0 }, 1), vec![3]);
+    assert_eq!(multi.nearest(&Point2D { x: 0.0, y: 0.0 }, 10).len(), 4);
+}
@@ -0,0 +1,185 @@
+//! Scanline rasterization of polygons
+
+use multi_polygon::MultiPolygon;
+use polygon::{Polygon, FillRule};
+use transform::transform_point;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Renders the polygon into a `width * height` mask (row-major, one byte per pixel)
+    ///
+    /// The affine `transform` (see `Polygon::transform`) maps the coordinates of
+    /// the polygon to pixel coordinates. A pixel is set to `255` if its center
+    /// lies inside of the polygon, otherwise it is `0`.
+    pub fn rasterize(&self, width: usize, height: usize, transform: &[fsize; 6], fill_rule: FillRule) -> Vec<u8> {
+        rasterize_rings(&[&self.nodes[..]], width, height, transform, fill_rule)
+    }
+
+    /// Returns the horizontal interior spans `(x_start, x_end)` of the polygon
+    /// on scanlines that are `y_step` apart
+    ///
+    /// The scanlines run through the centers of rows of height `y_step`,
+    /// starting at the bottom of the bounding box. Scanlines without any
+    /// interior spans are omitted.
+    pub fn scan_spans(&self, y_step: fsize) -> Vec<(fsize, Vec<(fsize, fsize)>)> {
+        scan_spans_rings(&[&self.nodes[..]], self.bbox.or_else(|| ::Bbox::from_points(&self.nodes)), y_step)
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Renders all polygons and holes into one mask, see `Polygon::rasterize`
+    ///
+    /// With `FillRule::EvenOdd`, holes are always cut out. With `FillRule::NonZero`,
+    /// holes have to be oriented opposite to their exteriors (see `orient`).
+    pub fn rasterize(&self, width: usize, height: usize, transform: &[fsize; 6], fill_rule: FillRule) -> Vec<u8> {
+        let rings: Vec<&[Point2D]> = self.polygons.iter().map(|p| &p.nodes[..]).collect();
+        rasterize_rings(&rings, width, height, transform, fill_rule)
+    }
+
+    /// Returns the interior spans of all polygons, with holes cut out
+    /// (even-odd rule), see `Polygon::scan_spans`
+    pub fn scan_spans(&self, y_step: fsize) -> Vec<(fsize, Vec<(fsize, fsize)>)> {
+        let rings: Vec<&[Point2D]> = self.polygons.iter().map(|p| &p.nodes[..]).collect();
+        let bbox = self.polygons.iter().map(|p| p.bounding_box()).fold(None, |acc: Option<::Bbox>, b| {
+            Some(acc.map(|a| a.union(&b)).unwrap_or(b))
+        });
+        scan_spans_rings(&rings, bbox, y_step)
+    }
+}
+
+fn scan_spans_rings(rings: &[&[Point2D]], bbox: Option<::Bbox>, y_step: fsize) -> Vec<(fsize, Vec<(fsize, fsize)>)> {
+
+    let bbox = match bbox {
+        Some(b) if y_step > 0.0 => b,
+        _ => return Vec::new(),
+    };
+
+    let (min_y, max_y) = (bbox.min().y, bbox.max().y);
+    let mut result = Vec::new();
+    let mut row = 0;
+
+    loop {
+        let y = min_y + (row as fsize + 0.5) * y_step;
+        if y > max_y {
+            break;
+        }
+        let spans = scanline_spans(rings, y, FillRule::EvenOdd);
+        if !spans.is_empty() {
+            result.push((y, spans));
+        }
+        row += 1;
+    }
+
+    result
+}
+
+fn rasterize_rings(rings: &[&[Point2D]], width: usize, height: usize,
+                   transform: &[fsize; 6], fill_rule: FillRule) -> Vec<u8>
+{
+    let transformed: Vec<Vec<Point2D>> = rings.iter()
+        .map(|r| r.iter().map(|p| transform_point(p, transform)).collect())
+        .collect();
+    let rings: Vec<&[Point2D]> = transformed.iter().map(|r| &r[..]).collect();
+
+    let mut mask = vec![0_u8; width * height];
+
+    for row in 0..height {
+        let y = row as fsize + 0.5;
+        for (start, end) in scanline_spans(&rings, y, fill_rule) {
+            // pixels whose center lies in [start, end)
+            let first = ((start - 0.5).ceil().max(0.0) as usize).min(width);
+            let last = ((end - 0.5).ceil().max(0.0) as usize).min(width);
+            for pixel in &mut mask[row * width + first..row * width + last] {
+                *pixel = 255;
+            }
+        }
+    }
+
+    mask
+}
+
+/// Computes the filled intervals `[start, end)` of the rings along the
+/// horizontal line at `y`, sorted from left to right
+///
+/// Edges are treated as half-open in y, so a vertex exactly on the scanline
+/// is counted once.
+pub(crate) fn scanline_spans(rings: &[&[Point2D]], y: fsize, fill_rule: FillRule) -> Vec<(fsize, fsize)> {
+
+    // (x, winding direction) of every edge crossing the scanline
+    let mut crossings = Vec::<(fsize, i32)>::new();
+    for ring in rings.iter().filter(|r| r.len() > 2) {
+        for (a, b) in ::edges::Edges::new(ring, true) {
+            if (a.y <= y) == (b.y <= y) {
+                continue;
+            }
+            let x = a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x);
+            crossings.push((x, if b.y > a.y { 1 } else { -1 }));
+        }
+    }
+
+    crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal));
+
+    let mut spans = Vec::new();
+    let mut winding = 0;
+    let mut span_start = None;
+
+    for (x, direction) in crossings {
+        winding += direction;
+        let inside = match fill_rule {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        };
+        match (span_start, inside) {
+            (None, true) => span_start = Some(x),
+            (Some(start), false) => {
+                if x > start {
+                    spans.push((start, x));
+                }
+                span_start = None;
+            },
+            _ => { },
+        }
+    }
+
+    spans
+}
+
+#[test]
+fn test_scan_spans() {
+    let outer = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+    let hole = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+
+    let spans = MultiPolygon::new(vec![outer, hole]).scan_spans(2.0);
+    assert_eq!(spans, vec![
+        (1.0, vec![(0.0, 1.0), (3.0, 4.0)]),
+        (3.0, vec![(0.0, 4.0)]),
+    ]);
+
+    let triangle = Polygon::from(vec![(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)]);
+    let spans = triangle.scan_spans(1.0);
+    assert_eq!(spans.len(), 4);
+    assert_eq!(spans[0], (0.5, vec![(0.0, 3.5)]));
+}
+
+#[test]
+fn test_rasterize_with_hole() {
+    let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+    let outer = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+    let hole = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+
+    let mask = MultiPolygon::new(vec![outer.clone(), hole]).rasterize(5, 4, &identity, FillRule::EvenOdd);
+    let expected: Vec<u8> = [
+        "####.",
+        "#..#.",
+        "#..#.",
+        "####.",
+    ].iter().flat_map(|row| row.chars().map(|c| if c == '#' { 255 } else { 0 })).collect();
+    assert_eq!(mask, expected);
+
+    // scaled by 0.5: the polygon covers the top left 2x2 pixels
+    let scale = [0.5, 0.0, 0.0, 0.0, 0.5, 0.0];
+    let mask = outer.rasterize(3, 3, &scale, FillRule::NonZero);
+    assert_eq!(mask, vec![255, 255, 0, 255, 255, 0, 0, 0, 0]);
+}
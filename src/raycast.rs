@@ -0,0 +1,81 @@
+//! Intersection of rays with the polygon outline
+
+use polygon::Polygon;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Casts a ray from `origin` in `direction` and returns all hits with the
+    /// edges of the polygon, ordered by their distance along the ray
+    ///
+    /// Each hit is `(t, point, edge)`, where `point = origin + direction * t`
+    /// (so `t` is the distance if `direction` has a length of 1.0) and `edge`
+    /// is the index of the edge from `nodes[edge]` to the next node. A ray
+    /// through a vertex hits both adjacent edges. For an edge that lies on the
+    /// ray, only the first point of the edge along the ray is returned.
+    pub fn raycast(&self, origin: Point2D, direction: Point2D) -> Vec<(fsize, Point2D, usize)> {
+
+        let len = self.nodes.len();
+        if direction.dot(&direction) == 0.0 || len < 2 {
+            return Vec::new();
+        }
+
+        let edge_count = if self.is_closed { len } else { len - 1 };
+        let mut hits: Vec<(fsize, Point2D, usize)> = (0..edge_count).filter_map(|idx| {
+            let t = ray_segment(&origin, &direction, &self.nodes[idx], &self.nodes[(idx + 1) % len])?;
+            Some((t, origin + direction * t, idx))
+        }).collect();
+
+        hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal).then(a.2.cmp(&b.2)));
+        hits
+    }
+}
+
+/// Ray parameter of the first point of the segment (a, b) on the ray, if any
+fn ray_segment(origin: &Point2D, direction: &Point2D, a: &Point2D, b: &Point2D) -> Option<fsize> {
+
+    let edge = *b - *a;
+    let to_a = *a - *origin;
+    let denom = direction.cross(&edge);
+
+    if denom == 0.0 {
+        // parallel: only a hit if the edge lies on the ray
+        if to_a.cross(direction) != 0.0 {
+            return None;
+        }
+        let dir_sq = direction.dot(direction);
+        let t_a = to_a.dot(direction) / dir_sq;
+        let t_b = (*b - *origin).dot(direction) / dir_sq;
+        return if t_a.max(t_b) >= 0.0 { Some(t_a.min(t_b).max(0.0)) } else { None };
+    }
+
+    let t = to_a.cross(&edge) / denom;
+    let u = to_a.cross(direction) / denom;
+
+    if t >= 0.0 && u >= 0.0 && u <= 1.0 {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_raycast() {
+    let square = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+
+    let hits = square.raycast(Point2D { x: -1.0, y: 2.0 }, Point2D { x: 1.0, y: 0.0 });
+    assert_eq!(hits, vec![
+        (1.0, Point2D { x: 0.0, y: 2.0 }, 3),
+        (5.0, Point2D { x: 4.0, y: 2.0 }, 1),
+    ]);
+
+    // from the inside, only the edge in front is hit
+    let hits = square.raycast(Point2D { x: 2.0, y: 2.0 }, Point2D { x: 0.0, y: -0.5 });
+    assert_eq!(hits, vec![(4.0, Point2D { x: 2.0, y: 0.0 }, 0)]);
+
+    // along the bottom edge, which also touches the two side edges
+    let hits = square.raycast(Point2D { x: -2.0, y: 0.0 }, Point2D { x: 1.0, y: 0.0 });
+    assert_eq!(hits.iter().map(|h| (h.0, h.2)).collect::<Vec<_>>(), vec![(2.0, 0), (2.0, 3), (6.0, 1)]);
+
+    assert!(square.raycast(Point2D { x: 5.0, y: 5.0 }, Point2D { x: 1.0, y: 1.0 }).is_empty());
+}
@@ -0,0 +1,309 @@
+//! Boolean operations of axis-aligned rectangles and rectilinear polygons
+//!
+//! The operations are computed by a rectilinear sweep from left to right over
+//! the compressed y-coordinates of the inputs. Every y-cell (the space between
+//! two consecutive y-coordinates) counts the rectangles that cover it, or the
+//! vertical polygon edges that were crossed, at the current x. When a cell
+//! changes between inside and outside of the result, a vertical boundary edge
+//! is emitted. A horizontal boundary runs along every y-coordinate where
+//! exactly one of the two adjacent cells is inside, and is emitted once its
+//! extent along x is known. No intersections have to be computed, so this is
+//! much faster than the general algorithm, and the output only contains
+//! coordinates of the input.
+
+use std::collections::HashMap;
+use edges::Edges;
+use error::ClipError;
+use multi_polygon::sort_canonical;
+use options::OperationGuard;
+use polygon::{Polygon, BoolOpType};
+use {Point2D, Bbox, fsize};
+
+impl Polygon {
+
+    /// Returns true if all edges of the polygon (including the closing edge
+    /// of a closed polygon) are horizontal or vertical
+    pub fn is_rectilinear(&self) -> bool {
+        Edges::new(&self.nodes, self.is_closed).all(|(p0, p1)| p0.x == p1.x || p0.y == p1.y)
+    }
+}
+
+/// Calculates the union of the rectangles
+///
+/// The result consists of counter-clockwise exteriors and clockwise holes
+/// (with `is_hole` set), without collinear vertices. Rectangles that only
+/// touch in a corner are returned as separate polygons. Empty rectangles and
+/// rectangles with non-finite coordinates are ignored.
+pub fn union_rects(rects: &[Bbox]) -> Vec<Polygon> {
+
+    let rects: Vec<&Bbox> = rects.iter()
+        .filter(|r| r.left.is_finite() && r.right.is_finite() && r.bottom.is_finite() && r.top.is_finite())
+        .filter(|r| r.left < r.right && r.bottom < r.top)
+        .collect();
+
+    let ys = sorted_ys(rects.iter().flat_map(|r| vec![r.bottom, r.top]));
+    let mut events = Vec::with_capacity(rects.len() * 2);
+    for r in rects.iter() {
+        let (first, end) = (cell_of(&ys, r.bottom), cell_of(&ys, r.top));
+        events.push(Event { x: r.left, first: first, end: end, operand: 0, delta: 1 });
+        events.push(Event { x: r.right, first: first, end: end, operand: 0, delta: -1 });
+    }
+
+    match sweep(&ys, events, |counts| counts[0] > 0, &mut OperationGuard::unlimited()) {
+        Ok(result) => result,
+        Err(_) => unreachable!("an unlimited guard never aborts"),
+    }
+}
+
+/// Boolean operation of two sets of rings whose edges are all horizontal or
+/// vertical (see `Polygon::is_rectilinear`), with the even-odd rule
+pub(crate) fn clip_rectilinear(a: &[&[Point2D]], b: &[&[Point2D]], operation_type: BoolOpType, guard: &mut OperationGuard)
+                               -> Result<Vec<Polygon>, ClipError>
+{
+    let rings = a.iter().map(|r| (0, r)).chain(b.iter().map(|r| (1, r)));
+    let ys = sorted_ys(a.iter().chain(b.iter()).flat_map(|r| r.iter().map(|p| p.y)));
+
+    // a downwards edge starts the inside of a counter-clockwise ring, an
+    // upwards edge ends it. Only the parity of the counts is used.
+    let mut events = Vec::new();
+    for (operand, ring) in rings {
+        for (p0, p1) in Edges::new(ring, true) {
+            if p0.x == p1.x && p0.y != p1.y {
+                let (first, end) = (cell_of(&ys, p0.y.min(p1.y)), cell_of(&ys, p0.y.max(p1.y)));
+                events.push(Event { x: p0.x, first: first, end: end, operand: operand, delta: if p1.y < p0.y { 1 } else { -1 } });
+            }
+        }
+    }
+
+    sweep(&ys, events, |counts| {
+        let (in_a, in_b) = (counts[0] % 2 != 0, counts[1] % 2 != 0);
+        match operation_type {
+            BoolOpType::Intersection => in_a && in_b,
+            BoolOpType::Union => in_a || in_b,
+            BoolOpType::Difference => in_a && !in_b,
+            BoolOpType::Xor => in_a != in_b,
+        }
+    }, guard)
+}
+
+/// Vertical edge of an operand between the y-cells `first..end`
+struct Event {
+    x: fsize,
+    first: usize,
+    end: usize,
+    operand: usize,
+    delta: i32,
+}
+
+fn sorted_ys<I: Iterator<Item = fsize>>(ys: I) -> Vec<fsize> {
+    let mut ys: Vec<fsize> = ys.collect();
+    ys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    ys.dedup();
+    ys
+}
+
+fn cell_of(ys: &[fsize], y: fsize) -> usize {
+    ys.binary_search_by(|v| v.partial_cmp(&y).unwrap()).unwrap()
+}
+
+/// Sweeps over the events from left to right and returns the boundary of the
+/// cells whose counts are `inside`, as polygons
+fn sweep<F>(ys: &[fsize], mut events: Vec<Event>, inside: F, guard: &mut OperationGuard) -> Result<Vec<Polygon>, ClipError>
+    where F: Fn([i32; 2]) -> bool
+{
+    events.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+
+    let cells = ys.len().saturating_sub(1);
+    let mut counts = vec![[0, 0]; cells];
+    // for every y-coordinate: where its current horizontal edge started and
+    // whether the covered cell is above it
+    let mut boundaries: Vec<Option<(fsize, bool)>> = vec![None; ys.len()];
+    let mut edges: Vec<(Point2D, Point2D)> = Vec::new();
+
+    let mut idx = 0;
+    while idx < events.len() {
+        guard.tick()?;
+        let x = events[idx].x;
+
+        // apply all events at x, remembering the previous state of every touched cell
+        let mut touched: HashMap<usize, bool> = HashMap::new();
+        while idx < events.len() && events[idx].x == x {
+            let event = &events[idx];
+            for cell in event.first..event.end {
+                touched.entry(cell).or_insert(inside(counts[cell]));
+                counts[cell][event.operand] += event.delta;
+            }
+            idx += 1;
+        }
+
+        let mut flipped: Vec<(usize, bool)> = touched.into_iter()
+            .filter(|&(cell, was_covered)| inside(counts[cell]) != was_covered)
+            .map(|(cell, was_covered)| (cell, !was_covered))
+            .collect();
+        flipped.sort();
+
+        // vertical edges, with the covered side on the left: downwards where
+        // the coverage starts, upwards where it ends. Runs of cells that flip
+        // the same way form one edge.
+        let mut run = 0;
+        while run < flipped.len() {
+            let (first, covered) = flipped[run];
+            let mut end = first + 1;
+            run += 1;
+            while run < flipped.len() && flipped[run] == (end, covered) {
+                end += 1;
+                run += 1;
+            }
+            let (bottom, top) = (Point2D { x: x, y: ys[first] }, Point2D { x: x, y: ys[end] });
+            edges.push(if covered { (top, bottom) } else { (bottom, top) });
+        }
+
+        // horizontal edges along the y-coordinates next to the flipped cells
+        let is_covered = |cell: usize| cell < cells && inside(counts[cell]);
+        let mut changed: Vec<usize> = flipped.iter().flat_map(|&(cell, _)| vec![cell, cell + 1]).collect();
+        changed.dedup();
+        for k in changed {
+            let below = k > 0 && is_covered(k - 1);
+            let above = is_covered(k);
+            let status = if below != above { Some(above) } else { None };
+            if boundaries[k].map(|b| b.1) == status {
+                continue;
+            }
+            if let Some((start, covered_above)) = boundaries[k] {
+                let (left, right) = (Point2D { x: start, y: ys[k] }, Point2D { x: x, y: ys[k] });
+                edges.push(if covered_above { (left, right) } else { (right, left) });
+            }
+            boundaries[k] = status.map(|covered_above| (x, covered_above));
+        }
+    }
+
+    let mut polygons: Vec<Polygon> = link_edges(&edges).into_iter()
+        .map(|ring| ::clean::clean_nodes(&ring, true, 0.0))
+        .filter(|ring| ring.len() > 2)
+        .map(|ring| {
+            let is_hole = ::utils::calculate_signed_area(&ring) < 0.0;
+            Polygon::from_parts(ring, is_hole, true)
+        })
+        .collect();
+    sort_canonical(&mut polygons);
+    Ok(polygons)
+}
+
+/// Links the directed edges into rings. Where two rings touch in a vertex,
+/// the sharpest left turn is taken, so that they stay separate.
+fn link_edges(edges: &[(Point2D, Point2D)]) -> Vec<Vec<Point2D>> {
+
+    let mut outgoing: HashMap<Point2D, Vec<usize>> = HashMap::new();
+    for (idx, edge) in edges.iter().enumerate() {
+        outgoing.entry(edge.0).or_insert_with(Vec::new).push(idx);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+
+        let mut ring = Vec::new();
+        let mut current = first;
+        loop {
+            used[current] = true;
+            let (start, end) = edges[current];
+            ring.push(start);
+
+            let direction = end - start;
+            let next = outgoing.get(&end).and_then(|candidates| {
+                candidates.iter().cloned()
+                    .filter(|&c| !used[c])
+                    .max_by_key(|&c| {
+                        // left turn, straight on, right turn
+                        let cross = direction.cross(&(edges[c].1 - edges[c].0));
+                        if cross > 0.0 { 2 } else if cross == 0.0 { 1 } else { 0 }
+                    })
+            });
+
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        rings.push(ring);
+    }
+
+    rings
+}
+
+#[test]
+fn test_union_rects() {
+    use multi_polygon::MultiPolygon;
+
+    let area = |polygons: &[Polygon]| -> fsize {
+        polygons.iter().map(|p| if p.is_hole { -p.area() } else { p.area() }).sum()
+    };
+
+    // overlapping rectangles merge into one L-shape
+    let result = union_rects(&[Bbox::new(0.0, 0.0, 2.0, 1.0), Bbox::new(1.0, 0.0, 2.0, 3.0)]);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes.len(), 6);
+    assert_eq!(area(&result), 4.0);
+
+    // a frame of four rectangles encloses a hole
+    let frame = [
+        Bbox::new(0.0, 0.0, 4.0, 1.0),
+        Bbox::new(0.0, 3.0, 4.0, 4.0),
+        Bbox::new(0.0, 0.0, 1.0, 4.0),
+        Bbox::new(3.0, 0.0, 4.0, 4.0),
+    ];
+    let result = union_rects(&frame);
+    assert_eq!(result.len(), 2);
+    assert_eq!(result.iter().filter(|p| p.is_hole).count(), 1);
+    assert_eq!(area(&result), 12.0);
+
+    // touching corners stay separate
+    let result = union_rects(&[Bbox::new(0.0, 0.0, 1.0, 1.0), Bbox::new(1.0, 1.0, 2.0, 2.0), Bbox::new(2.0, 0.0, 3.0, 1.0)]);
+    assert_eq!(result.len(), 3);
+    assert!(result.iter().all(|p| p.nodes.len() == 4 && !p.is_hole));
+
+    // same result as the general algorithm
+    let rects: Vec<Bbox> = (0..20).map(|i| {
+        let i = i as fsize;
+        Bbox::new((i * 7.0) % 11.0, (i * 5.0) % 9.0, (i * 7.0) % 11.0 + 3.0, (i * 5.0) % 9.0 + 2.0)
+    }).collect();
+    let polygons = rects.iter().map(|r| Polygon::rect(r.left, r.bottom, r.width(), r.height())).collect();
+    let general = MultiPolygon::new(polygons).resolve_fill_rule(::polygon::FillRule::NonZero);
+    let nodes = |polygons: &[Polygon]| polygons.iter().map(|p| (p.nodes.clone(), p.is_hole)).collect::<Vec<_>>();
+    assert_eq!(nodes(&union_rects(&rects)), nodes(&general.polygons));
+
+    assert!(union_rects(&[Bbox::new(0.0, 0.0, 0.0, 1.0)]).is_empty());
+}
+
+#[test]
+fn test_clip_rectilinear() {
+    use multi_polygon::MultiPolygon;
+    use options::{ClipOptions, ClipAlgorithm};
+
+    // an L-shaped room and a rectangular cut-out
+    let room = Polygon::from(vec![(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (2.0, 2.0), (2.0, 4.0), (0.0, 4.0)]);
+    let cut = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+    assert!(room.is_rectilinear() && cut.is_rectilinear());
+    assert!(!Polygon::from(vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]).is_rectilinear());
+
+    let options = ClipOptions { algorithm: ClipAlgorithm::Rectilinear, .. Default::default() };
+    let a = MultiPolygon::new(vec![room.clone()]);
+    let b = MultiPolygon::new(vec![cut.clone()]);
+    let nodes = |polygons: &[Polygon]| polygons.iter().map(|p| (p.nodes.clone(), p.is_hole)).collect::<Vec<_>>();
+
+    for &op in [BoolOpType::Intersection, BoolOpType::Union, BoolOpType::Difference, BoolOpType::Xor].iter() {
+        let expected = a.clip(&b, op, &ClipOptions::default());
+        assert_eq!(nodes(&a.clip(&b, op, &options).polygons), nodes(&expected.polygons), "{:?}", op);
+        assert_eq!(nodes(&room.try_clip(&cut, op, &options).unwrap()), nodes(&expected.polygons), "{:?}", op);
+    }
+
+    // the difference cuts the corner out of the L
+    let difference = room.try_clip(&cut, BoolOpType::Difference, &options).unwrap();
+    assert_eq!(difference.len(), 1);
+    assert_eq!(difference[0].area(), 9.0);
+    assert!(difference[0].is_rectilinear());
+}
@@ -0,0 +1,212 @@
+//! Slow reference implementation of the boolean operations
+//!
+//! Every edge is split at its intersections with all other edges (O(n²)),
+//! then each piece is classified by testing a point on it against the inputs.
+//! The pieces where the result is inside on one side only are linked into
+//! rings. There is no sweep and no shared state, so the result can be used
+//! as an oracle for the fast algorithms, see `ClipOptions::verify`.
+
+use edges::Edges;
+use error::ClipWarning;
+use polygon::{Polygon, BoolOpType};
+use utils::{calculate_sign, calculate_signed_area, is_on_segment, locate_point_in_ring, PointLocation, Sign};
+use std::collections::HashMap;
+use {Point2D, fsize};
+
+/// Computes `subject <operation_type> clip` with the reference algorithm
+///
+/// Both inputs are sets of closed rings with the even-odd rule, like the
+/// operations of `MultiPolygon`. Every ring has to be simple. The result
+/// consists of counter-clockwise outer rings and clockwise holes (with
+/// `is_hole` set); rings that touch in a vertex are not merged.
+pub fn reference_clip(subject: &[Polygon], clip: &[Polygon], operation_type: BoolOpType) -> Vec<Polygon> {
+
+    let operands = [rings(subject), rings(clip)];
+    let select = |a: bool, b: bool| match operation_type {
+        BoolOpType::Intersection => a && b,
+        BoolOpType::Union => a || b,
+        BoolOpType::Difference => a && !b,
+        BoolOpType::Xor => a != b,
+    };
+
+    // all edges, as (operand, ring, start, end)
+    let edges: Vec<(usize, usize, Point2D, Point2D)> = operands.iter().enumerate()
+        .flat_map(|(operand, rings)| rings.iter().enumerate().flat_map(move |(ring, nodes)| {
+            Edges::new(nodes, true).filter(|&(a, b)| a != b).map(move |(a, b)| (operand, ring, a, b))
+        }))
+        .collect();
+
+    let mut result_edges = Vec::new();
+    for &(operand, ring, a, b) in edges.iter() {
+
+        let mut points = vec![a, b];
+        for &(_, _, c, d) in edges.iter() {
+            if let Some((p, q)) = ::point::line_intersect(&a, &b, &c, &d) {
+                points.push(p);
+                points.extend(q);
+            }
+        }
+        points.sort_by(|p, q| p.dist(&a).partial_cmp(&q.dist(&a)).unwrap());
+        points.dedup();
+
+        for piece in points.windows(2) {
+            // the inside of each operand left and right of the piece
+            let mut sides = [[false; 2]; 2];
+            for (other, rings) in operands.iter().enumerate() {
+                let on = if other == operand { Some((ring, true)) } else { on_boundary(&piece[0], &piece[1], rings) };
+                match on {
+                    // a piece on the boundary of both operands is only taken from the subject
+                    Some(_) if other < operand => { sides = [[false; 2]; 2]; break; },
+                    Some((on_ring, forward)) => {
+                        let around = inside(&piece[0].lerp(&piece[1], 0.5), rings, Some(on_ring));
+                        let left = around != (is_ccw(&rings[on_ring]) == forward);
+                        sides[other] = [left, !left];
+                    },
+                    None => {
+                        let inside = inside(&piece[0].lerp(&piece[1], 0.5), rings, None);
+                        sides[other] = [inside, inside];
+                    },
+                }
+            }
+
+            // orient the piece so that the result lies on its left side
+            match (select(sides[0][0], sides[1][0]), select(sides[0][1], sides[1][1])) {
+                (true, false) => result_edges.push((piece[0], piece[1])),
+                (false, true) => result_edges.push((piece[1], piece[0])),
+                _ => { },
+            }
+        }
+    }
+
+    link_rings(result_edges)
+}
+
+/// Compares the `result` of `subject <operation_type> clip` with the result
+/// of `reference_clip`. They match if the area of their symmetric difference
+/// is a negligible part of the area of the inputs.
+pub(crate) fn verify(subject: &[Polygon], clip: &[Polygon], operation_type: BoolOpType, result: &[Polygon])
+                     -> Option<ClipWarning>
+{
+    let reference = reference_clip(subject, clip, operation_type);
+    let difference: fsize = reference_clip(&reference, result, BoolOpType::Xor).iter()
+        .map(|p| calculate_signed_area(&p.nodes))
+        .sum();
+    let scale: fsize = subject.iter().chain(clip.iter()).map(Polygon::area).sum();
+
+    // the results may differ by rounding, where the algorithms snap intersections differently
+    if difference > scale * 1e-4 { Some(ClipWarning::ReferenceMismatch) } else { None }
+}
+
+/// The rings with at least three vertices
+pub(crate) fn rings(polygons: &[Polygon]) -> Vec<Vec<Point2D>> {
+    polygons.iter().filter(|p| p.nodes.len() > 2).map(|p| p.nodes.clone()).collect()
+}
+
+fn is_ccw(ring: &[Point2D]) -> bool {
+    calculate_signed_area(ring) > 0.0
+}
+
+/// Even-odd test of a point against the rings, ignoring the ring `skip`
+pub(crate) fn inside(p: &Point2D, rings: &[Vec<Point2D>], skip: Option<usize>) -> bool {
+    rings.iter().enumerate()
+        .filter(|&(idx, ring)| Some(idx) != skip && locate_point_in_ring(p, ring) == PointLocation::Inside)
+        .count() % 2 == 1
+}
+
+/// The ring whose boundary contains the segment (a, b), and whether the ring
+/// runs in the direction from a to b there
+fn on_boundary(a: &Point2D, b: &Point2D, rings: &[Vec<Point2D>]) -> Option<(usize, bool)> {
+    rings.iter().enumerate().filter_map(|(idx, ring)| {
+        Edges::new(ring, true).find(|&(p, q)| {
+            calculate_sign(&p, &q, a) == Sign::Equal && calculate_sign(&p, &q, b) == Sign::Equal &&
+            is_on_segment(&p, &q, a) && is_on_segment(&p, &q, b)
+        }).map(|(p, q)| (idx, (q - p).dot(&(*b - *a)) > 0.0))
+    }).next()
+}
+
+/// Links directed edges into closed rings, following the edges at every vertex
+/// in the order they were found
+fn link_rings(edges: Vec<(Point2D, Point2D)>) -> Vec<Polygon> {
+    let mut outgoing = HashMap::<Point2D, Vec<usize>>::new();
+    for (idx, &(a, _)) in edges.iter().enumerate() {
+        outgoing.entry(a).or_insert_with(Vec::new).push(idx);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut polygons = Vec::new();
+    for start in 0..edges.len() {
+        let mut nodes = Vec::new();
+        let mut current = Some(start);
+        while let Some(idx) = current.filter(|&idx| !used[idx]) {
+            used[idx] = true;
+            nodes.push(edges[idx].0);
+            current = outgoing.get(&edges[idx].1).and_then(|ids| ids.iter().cloned().find(|&next| !used[next]));
+        }
+        if nodes.len() > 2 {
+            let area: fsize = calculate_signed_area(&nodes);
+            polygons.push(Polygon::from_parts(nodes, area < 0.0, true));
+        }
+    }
+
+    ::multi_polygon::sort_canonical(&mut polygons);
+    polygons
+}
+
+#[test]
+fn test_reference_clip() {
+    use multi_polygon::MultiPolygon;
+    use options::ClipOptions;
+
+    let a = vec![Polygon::rect(0.0, 0.0, 4.0, 4.0), Polygon::rect(1.0, 1.0, 1.0, 1.0)];
+    let b = vec![Polygon::rect(2.0, -1.0, 3.0, 3.0), Polygon::from(vec![(0.0, 4.0), (2.0, 6.0), (-1.0, 5.0)])];
+    let area = |polygons: &[Polygon]| polygons.iter().map(|p| calculate_signed_area(&p.nodes)).sum::<fsize>();
+
+    for &op in [BoolOpType::Intersection, BoolOpType::Union, BoolOpType::Difference, BoolOpType::Xor].iter() {
+        let reference = reference_clip(&a, &b, op);
+        let fast = MultiPolygon::new(a.clone()).clip(&MultiPolygon::new(b.clone()), op, &ClipOptions::default());
+        assert!((area(&reference) - fast.total_area()).abs() < 1e-4, "{:?}", op);
+        assert!(reference.iter().all(|p| p.is_hole == (calculate_signed_area(&p.nodes) < 0.0)));
+
+        // the reference agrees with itself
+        assert!(area(&reference_clip(&reference, &fast.polygons, BoolOpType::Xor)).abs() < 1e-4, "{:?}", op);
+    }
+
+    // shared edges
+    let left = [Polygon::rect(0.0, 0.0, 2.0, 2.0)];
+    let right = [Polygon::rect(2.0, 0.0, 2.0, 2.0)];
+    assert_eq!(reference_clip(&left, &right, BoolOpType::Union).len(), 1);
+    assert!(reference_clip(&left, &right, BoolOpType::Intersection).is_empty());
+    assert_eq!(reference_clip(&left, &left, BoolOpType::Intersection)[0].nodes, left[0].nodes);
+}
+
+#[test]
+fn test_verify_option() {
+    use std::sync::Arc;
+    use backend::ClipBackend;
+    use error::ClipError;
+    use multi_polygon::MultiPolygon;
+    use options::ClipOptions;
+
+    // returns the subject, whatever the operation
+    struct Broken;
+
+    impl ClipBackend for Broken {
+        fn clip(&self, subject: &Polygon, _: &Polygon, _: BoolOpType, _: &ClipOptions) -> Result<Vec<Polygon>, ClipError> {
+            Ok(vec![subject.clone()])
+        }
+    }
+
+    let a = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    let b = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+    let options = ClipOptions { verify: true, .. Default::default() };
+
+    let (_, warnings) = a.clip_with_warnings(&b, BoolOpType::Union, &options).unwrap();
+    assert!(warnings.is_empty());
+    let (_, warnings) = MultiPolygon::new(vec![a.clone()])
+        .clip_with_warnings(&MultiPolygon::new(vec![b.clone()]), BoolOpType::Xor, &options).unwrap();
+    assert!(warnings.is_empty());
+
+    let options = ClipOptions { backend: Some(Arc::new(Broken)), .. options };
+    let (_, warnings) = a.clip_with_warnings(&b, BoolOpType::Union, &options).unwrap();
+    assert_eq!(warnings, vec![ClipWarning::ReferenceMismatch]);
+}
@@ -0,0 +1,308 @@
+//! Spatial relations between polygons: containment, shared boundaries and
+//! the intersection of a boundary with another polygon
+
+use intersections::find_intersections;
+use polygon::Polygon;
+use segment::Segment;
+use utils::{calculate_sign, is_on_segment, locate_point_in_ring, PointLocation, Sign};
+use edges::Edges;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Returns true if `other` lies inside of the polygon. The boundaries may
+    /// touch or overlap, so a polygon contains itself.
+    ///
+    /// After a bounding box check, the points where the boundaries meet are
+    /// found with a sweep (see `find_intersections`). If there are none, the
+    /// first vertex of `other` that is not on the boundary decides, or the
+    /// first such edge midpoint. Otherwise, the edges of `other` are split at
+    /// these points and every piece is probed at its midpoint.
+    pub fn contains_polygon(&self, other: &Polygon) -> bool {
+
+        if self.nodes.len() < 3 || other.nodes.is_empty() {
+            return false;
+        }
+
+        if !self.bounding_box().contains_bbox(&other.bounding_box()) {
+            return false;
+        }
+
+        let other_edges: Vec<Segment> = Edges::new(&other.nodes, other.is_closed).map(|(a, b)| Segment::new(a, b)).collect();
+        let pieces = split_at_boundary(&other_edges, &self.nodes);
+
+        if pieces.iter().all(|points| points.len() == 2) && !other_edges.is_empty() {
+            let midpoints = other_edges.iter().map(|edge| Point2D { x: (edge.a.x + edge.b.x) / 2.0, y: (edge.a.y + edge.b.y) / 2.0 });
+            return other.nodes.iter().cloned().chain(midpoints)
+                .map(|p| locate_point_in_ring(&p, &self.nodes))
+                .find(|location| *location != PointLocation::OnBoundary)
+                .map_or(true, |location| location == PointLocation::Inside);
+        }
+
+        pieces.iter().all(|points| points.windows(2).all(|piece| !is_piece_outside(&piece[0], &piece[1], &self.nodes)))
+    }
+
+    /// Returns true if the polygon lies inside of `other`, see `contains_polygon`
+    pub fn within(&self, other: &Polygon) -> bool {
+        other.contains_polygon(self)
+    }
+
+    /// Returns the parts of the boundary that coincide with the boundary of
+    /// `other`, as polylines that follow the order of the polygon's vertices
+    ///
+    /// Edges count as coincident if they are within `tolerance` of each other
+    /// (see `line_intersect_eps`). If the whole boundary is shared, a single
+    /// polyline is returned that ends at its first point.
+    pub fn shared_boundary(&self, other: &Polygon, tolerance: fsize) -> Vec<Vec<Point2D>> {
+
+        let edges: Vec<(Point2D, Point2D)> = Edges::new(&self.nodes, self.is_closed).collect();
+        let other_edges: Vec<(Point2D, Point2D)> = Edges::new(&other.nodes, other.is_closed).collect();
+        if edges.is_empty() || other_edges.is_empty() {
+            return Vec::new();
+        }
+
+        // pieces (start, end, covers the start, covers the end of the edge), in order along the boundary
+        let mut pieces: Vec<(Point2D, Point2D, bool, bool)> = Vec::new();
+        for &(a, b) in edges.iter() {
+            let length_sq = (b - a).dot(&(b - a));
+            if length_sq == 0.0 {
+                continue;
+            }
+            let t_eps = tolerance.max(0.0) / length_sq.sqrt();
+            let param = |p: &Point2D| {
+                let t = (*p - a).dot(&(b - a)) / length_sq;
+                if t <= t_eps { 0.0 } else if t >= 1.0 - t_eps { 1.0 } else { t }
+            };
+
+            let mut intervals: Vec<(fsize, fsize)> = other_edges.iter()
+                .filter_map(|&(c, d)| match ::point::line_intersect_eps(&a, &b, &c, &d, tolerance) {
+                    Some((p, Some(q))) => {
+                        let (s, t) = (param(&p), param(&q));
+                        Some((s.min(t), s.max(t)))
+                    },
+                    _ => None,
+                })
+                .filter(|&(s, t)| t > s)
+                .collect();
+            intervals.sort_by(|x, y| x.partial_cmp(y).unwrap());
+
+            let point = |t: fsize| if t == 0.0 { a } else if t == 1.0 { b } else { a.lerp(&b, t) };
+            let mut merged: Option<(fsize, fsize)> = None;
+            for (s, t) in intervals {
+                merged = match merged {
+                    Some((start, end)) if s <= end => Some((start, end.max(t))),
+                    Some((start, end)) => {
+                        pieces.push((point(start), point(end), start == 0.0, end == 1.0));
+                        Some((s, t))
+                    },
+                    None => Some((s, t)),
+                };
+            }
+            if let Some((start, end)) = merged {
+                pieces.push((point(start), point(end), start == 0.0, end == 1.0));
+            }
+        }
+
+        // join the pieces that continue over a vertex
+        let mut polylines: Vec<Vec<Point2D>> = Vec::new();
+        let mut continues = false;
+        for (start, end, from_start, to_end) in pieces {
+            match polylines.last_mut() {
+                Some(ref mut line) if continues && from_start && line.last() == Some(&start) => line.push(end),
+                _ => polylines.push(vec![start, end]),
+            }
+            continues = to_end;
+        }
+
+        // the last polyline continues with the first one over the first vertex
+        if self.is_closed && polylines.len() > 1 && polylines[0][0] == self.nodes[0] &&
+           polylines.last().and_then(|l| l.last()) == Some(&self.nodes[0]) {
+            let first = polylines.remove(0);
+            polylines.last_mut().unwrap().extend(first.into_iter().skip(1));
+        }
+
+        polylines
+    }
+
+    /// Returns the parts of the boundary that lie inside of `other` (or on its
+    /// boundary), as polylines that follow the order of the polygon's
+    /// vertices, together with the points where the boundary only touches
+    /// `other` from the outside. This is the line and point part of the
+    /// overlay, for example the coastline within a viewport.
+    ///
+    /// If the whole boundary lies inside, a single polyline is returned that
+    /// ends at its first point.
+    pub fn boundary_intersection(&self, other: &Polygon) -> (Vec<Vec<Point2D>>, Vec<Point2D>) {
+
+        let edges: Vec<Segment> = Edges::new(&self.nodes, self.is_closed).map(|(a, b)| Segment::new(a, b)).collect();
+        if edges.is_empty() || other.nodes.len() < 3 || !self.bounding_box().overlaps(&other.bounding_box()) {
+            return (Vec::new(), Vec::new());
+        }
+
+        let on_boundary = |p: &Point2D| locate_point_in_ring(p, &other.nodes) == PointLocation::OnBoundary;
+
+        // the boundary split at the boundary of `other`, as (start, lies inside, start touches `other`)
+        let mut pieces: Vec<(Point2D, bool, bool)> = Vec::new();
+        for points in split_at_boundary(&edges, &other.nodes) {
+            for (i, piece) in points.windows(2).enumerate() {
+                let touches = i > 0 || on_boundary(&piece[0]);
+                pieces.push((piece[0], !is_piece_outside(&piece[0], &piece[1], &other.nodes), touches));
+            }
+        }
+        if !self.is_closed {
+            let end = edges[edges.len() - 1].b;
+            pieces.push((end, false, on_boundary(&end)));
+        }
+
+        if pieces.iter().all(|&(_, inside, _)| inside) {
+            let mut ring: Vec<Point2D> = pieces.iter().map(|&(p, _, _)| p).collect();
+            ring.push(ring[0]);
+            return (vec![ring], Vec::new());
+        }
+
+        // start after a piece that lies outside, so that no polyline wraps around
+        let n = pieces.len();
+        let start = if self.is_closed { (0..n).find(|&i| !pieces[(i + n - 1) % n].1).unwrap() } else { 0 };
+        let mut polylines: Vec<Vec<Point2D>> = Vec::new();
+        let mut points = Vec::new();
+        let mut previous_inside = false;
+        for k in 0..n {
+            let (p, inside, touches) = pieces[(start + k) % n];
+            match (previous_inside, inside) {
+                (true, _) => polylines.last_mut().unwrap().push(p),
+                (false, true) => polylines.push(vec![p]),
+                (false, false) => if touches { points.push(p) },
+            }
+            previous_inside = inside;
+        }
+
+        (polylines, points)
+    }
+}
+
+/// Splits the `edges` at the points where they meet the boundary of the
+/// closed `ring`. Returns the sorted points (including the end points) of
+/// every edge.
+fn split_at_boundary(edges: &[Segment], ring: &[Point2D]) -> Vec<Vec<Point2D>> {
+
+    let ring_edges: Vec<Segment> = Edges::new(ring, true).map(|(a, b)| Segment::new(a, b)).collect();
+    let n = ring_edges.len();
+    let segments: Vec<Segment> = ring_edges.into_iter().chain(edges.iter().cloned()).collect();
+
+    let mut splits: Vec<Vec<Point2D>> = edges.iter().map(|edge| vec![edge.a, edge.b]).collect();
+    for event in find_intersections(&segments) {
+        if event.segments.iter().any(|&s| s < n) {
+            for &s in event.segments.iter().filter(|&&s| s >= n) {
+                splits[s - n].push(event.point);
+            }
+        }
+    }
+
+    for (edge, points) in edges.iter().zip(splits.iter_mut()) {
+        points.sort_by(|p, q| p.dist(&edge.a).partial_cmp(&q.dist(&edge.a)).unwrap());
+        points.dedup();
+    }
+
+    splits
+}
+
+/// Checks if the piece (a, b) of an edge that was split by `split_at_boundary`
+/// lies outside of the ring, by probing its midpoint
+fn is_piece_outside(a: &Point2D, b: &Point2D, ring: &[Point2D]) -> bool {
+    // the rounded midpoint of a piece along a shared edge can fall on either side of it
+    locate_point_in_ring(&a.lerp(b, 0.5), ring) == PointLocation::Outside && !lies_on_boundary(a, b, ring)
+}
+
+/// Checks if the segment (a, b) lies on one of the edges of the ring
+fn lies_on_boundary(a: &Point2D, b: &Point2D, ring: &[Point2D]) -> bool {
+    Edges::new(ring, true).any(|(p, q)| {
+        calculate_sign(&p, &q, a) == Sign::Equal && calculate_sign(&p, &q, b) == Sign::Equal &&
+        is_on_segment(&p, &q, a) && is_on_segment(&p, &q, b)
+    })
+}
+
+#[test]
+fn test_contains_polygon() {
+    let outer = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+    let inner = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+
+    assert!(outer.contains_polygon(&inner));
+    assert!(inner.within(&outer));
+    assert!(!inner.contains_polygon(&outer));
+    assert!(outer.contains_polygon(&outer));
+
+    // touching the boundary from the inside, along an edge and in a corner
+    assert!(outer.contains_polygon(&Polygon::rect(0.0, 1.0, 2.0, 2.0)));
+    assert!(outer.contains_polygon(&Polygon::from(vec![(0.0, 0.0), (2.0, 1.0), (1.0, 2.0)])));
+
+    // crossing and disjoint
+    assert!(!outer.contains_polygon(&Polygon::rect(3.0, 3.0, 2.0, 2.0)));
+    assert!(!outer.contains_polygon(&Polygon::rect(5.0, 5.0, 1.0, 1.0)));
+
+    // a U-shape does not contain a bar across its notch, although all
+    // vertices of the bar lie inside of the arms
+    let u = Polygon::from(vec![(0.0, 0.0), (6.0, 0.0), (6.0, 4.0), (4.0, 4.0), (4.0, 1.0), (2.0, 1.0), (2.0, 4.0), (0.0, 4.0)]);
+    let bar = Polygon::rect(1.0, 2.0, 4.0, 1.0);
+    assert!(!u.contains_polygon(&bar));
+    assert!(u.contains_polygon(&Polygon::rect(0.0, 0.0, 6.0, 1.0)));
+
+    // stands on the bottom of the notch with its first vertex
+    assert!(!u.contains_polygon(&Polygon::from(vec![(3.0, 1.0), (3.5, 3.0), (2.5, 3.0)])));
+    assert!(u.contains_polygon(&Polygon::from(vec![(3.0, 1.0), (3.5, 0.5), (2.5, 0.5)])));
+
+    // shares a diagonal edge
+    let triangle = Polygon::from(vec![(0.0, 0.0), (3.0, 1.0), (0.0, 7.0)]);
+    assert!(triangle.contains_polygon(&Polygon::from(vec![(0.0, 0.0), (3.0, 1.0), (1.0, 2.0)])));
+}
+
+#[test]
+fn test_shared_boundary() {
+    // two neighbouring parcels share the edge x = 2, partially
+    let a = Polygon::rect(0.0, 0.0, 2.0, 4.0);
+    let b = Polygon::rect(2.0, 1.0, 2.0, 2.0);
+    assert_eq!(a.shared_boundary(&b, 0.0), vec![vec![Point2D { x: 2.0, y: 1.0 }, Point2D { x: 2.0, y: 3.0 }]]);
+
+    // the shared part runs over a vertex and across the start of the ring
+    let c = Polygon::from(vec![(-1.0, 0.0), (2.0, 0.0), (2.0, 2.0), (-1.0, 2.0)]);
+    let rotated = Polygon::from(vec![(2.0, 0.0), (2.0, 4.0), (0.0, 4.0), (0.0, 0.0)]);
+    assert_eq!(rotated.shared_boundary(&c, 0.0), vec![vec![
+        Point2D { x: 0.0, y: 0.0 }, Point2D { x: 2.0, y: 0.0 }, Point2D { x: 2.0, y: 2.0 },
+    ]]);
+
+    // within a tolerance
+    let d = Polygon::rect(2.001, 0.0, 1.0, 4.0);
+    assert!(a.shared_boundary(&d, 0.0).is_empty());
+    assert_eq!(a.shared_boundary(&d, 0.01).len(), 1);
+
+    // the whole ring
+    let whole = a.shared_boundary(&a, 0.0);
+    assert_eq!(whole.len(), 1);
+    assert_eq!(whole[0].len(), 5);
+}
+
+#[test]
+fn test_boundary_intersection() {
+    let viewport = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+    let p = |x, y| Point2D { x: x, y: y };
+
+    // an island that sticks out of the viewport on the right
+    let island = Polygon::from(vec![(2.0, 1.0), (6.0, 1.0), (6.0, 3.0), (2.0, 3.0)]);
+    let (lines, points) = island.boundary_intersection(&viewport);
+    assert_eq!(lines, vec![vec![p(4.0, 3.0), p(2.0, 3.0), p(2.0, 1.0), p(4.0, 1.0)]]);
+    assert!(points.is_empty());
+
+    // an open path (a pipe) that crosses the viewport twice
+    let mut pipe = Polygon::from(vec![(-1.0, 1.0), (5.0, 1.0), (5.0, 3.0), (-1.0, 3.0)]);
+    pipe.is_closed = false;
+    let (lines, _) = pipe.boundary_intersection(&viewport);
+    assert_eq!(lines, vec![vec![p(0.0, 1.0), p(4.0, 1.0)], vec![p(4.0, 3.0), p(0.0, 3.0)]]);
+
+    // touching the viewport in a single point from the outside
+    let diamond = Polygon::from(vec![(4.0, 2.0), (5.0, 1.0), (6.0, 2.0), (5.0, 3.0)]);
+    assert_eq!(diamond.boundary_intersection(&viewport), (Vec::new(), vec![p(4.0, 2.0)]));
+
+    // inside, and disjoint
+    let (lines, points) = Polygon::rect(1.0, 1.0, 1.0, 1.0).boundary_intersection(&viewport);
+    assert_eq!((lines.len(), lines[0].len(), points.len()), (1, 5, 0));
+    assert_eq!(Polygon::rect(5.0, 5.0, 1.0, 1.0).boundary_intersection(&viewport), (Vec::new(), Vec::new()));
+}
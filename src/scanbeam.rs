@@ -0,0 +1,692 @@
+//! Boolean operations on integer coordinates with a scanbeam sweep
+//!
+//! This follows Vatti's algorithm (and Clipper): the plane is cut into
+//! scanbeams, the horizontal strips between consecutive vertex y-coordinates,
+//! and the edges that cross a beam are kept in a list ordered from left to
+//! right. The winding numbers of the operands only change across these edges,
+//! so every gap of the list is either inside or outside of the result, and an
+//! edge is part of the boundary if the gaps on its two sides differ.
+//! Horizontal edges do not change the winding numbers, they are part of the
+//! boundary if the beams above and below of them differ.
+//!
+//! Before the sweep, the edges are split at all points where they cross or
+//! touch, so that no two edges cross inside of a beam. Crossings are rounded
+//! to the nearest integer point, and the split edges are checked again until
+//! none of them cross. All other decisions use exact predicates on `i128`, so
+//! the topology of the result does not depend on floating-point rounding.
+//! Coordinates are limited to `PolygonI64::MAX_COORDINATE`, for which these
+//! predicates can not overflow.
+//!
+//! Open paths of the subject (`is_closed` is false) are clipped natively: the
+//! intersection keeps their parts inside of the clip, the other operations
+//! the parts outside of it. Parts that run along the boundary of the clip
+//! count as inside. Open paths of the clip are closed like rings.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use error::ClipError;
+use options::OperationGuard;
+use polygon::{Polygon, BoolOpType};
+use precision::{TypedPolygon, PolygonI64};
+use fsize;
+
+impl TypedPolygon<i64> {
+
+    /// Largest absolute value of a coordinate that the boolean operations
+    /// accept, 2^60
+    pub const MAX_COORDINATE: i64 = 1 << 60;
+
+    /// Runs the boolean operation `operation_type` with the scanbeam sweep,
+    /// see `clip_i64`
+    pub fn try_clip(&self, other: &Self, operation_type: BoolOpType) -> Result<Vec<Self>, ClipError> {
+        clip_i64(::std::slice::from_ref(self), ::std::slice::from_ref(other), operation_type)
+    }
+}
+
+impl Polygon {
+
+    /// Returns true if all coordinates are integers whose absolute value is at
+    /// most `PolygonI64::MAX_COORDINATE`, so `ClipAlgorithm::Scanbeam` can
+    /// compute with them exactly
+    pub fn has_integer_coordinates(&self) -> bool {
+        let max = PolygonI64::MAX_COORDINATE as fsize;
+        self.nodes.iter().all(|p| p.x.fract() == 0.0 && p.y.fract() == 0.0 && p.x.abs() <= max && p.y.abs() <= max)
+    }
+}
+
+/// Runs the boolean operation `subject <operation_type> clip` on integer
+/// coordinates, with the even-odd rule
+///
+/// The result consists of counter-clockwise exteriors and clockwise holes
+/// (with `is_hole` set) without collinear vertices, followed by the clipped
+/// open paths of the subject. Rings that only touch in a vertex are returned
+/// separately. The points where edges cross are rounded to the nearest
+/// integer point, all other vertices are taken from the input. Returns
+/// `ClipError::CoordinateOutOfRange` if a coordinate is larger than
+/// `PolygonI64::MAX_COORDINATE`.
+pub fn clip_i64(subject: &[PolygonI64], clip: &[PolygonI64], operation_type: BoolOpType) -> Result<Vec<PolygonI64>, ClipError> {
+    clip_scanbeam(subject, clip, operation_type, &mut OperationGuard::unlimited())
+}
+
+/// Runs `clip_i64` with the `guard`
+pub(crate) fn clip_scanbeam(subject: &[PolygonI64], clip: &[PolygonI64], operation_type: BoolOpType, guard: &mut OperationGuard)
+                            -> Result<Vec<PolygonI64>, ClipError>
+{
+    let max = PolygonI64::MAX_COORDINATE;
+    let polygons = subject.iter().map(|p| (0, p)).chain(clip.iter().map(|p| (1, p)));
+    let nodes = polygons.clone().flat_map(|(_, p)| p.nodes.iter());
+    if let Some(index) = nodes.clone().position(|n| n[0] < -max || n[0] > max || n[1] < -max || n[1] > max) {
+        return Err(ClipError::CoordinateOutOfRange { index: index });
+    }
+
+    let mut paths: Vec<Path> = polygons.map(|(operand, polygon)| {
+        let is_closed = polygon.is_closed || operand == 1;
+        Path {
+            nodes: without_duplicates(polygon.nodes.iter().map(|n| IntPoint { x: n[0], y: n[1] }).collect(), is_closed),
+            operand: operand,
+            is_closed: is_closed,
+        }
+    }).collect();
+
+    node_paths(&mut paths, guard)?;
+    let (boundary, kept) = sweep(&edges_of(&paths), operation_type, guard)?;
+
+    let mut result: Vec<PolygonI64> = link_rings(&boundary).into_iter()
+        .map(|ring| without_straight_vertices(ring, true))
+        .filter(|ring| ring.len() > 2)
+        .map(|ring| {
+            let is_hole = signed_area2(&ring) < 0;
+            to_polygon(ring, is_hole, true)
+        })
+        .collect();
+
+    for (idx, path) in paths.iter().enumerate().filter(|&(_, p)| !p.is_closed) {
+        let mut run: Vec<IntPoint> = Vec::new();
+        for piece in 0..path.nodes.len() {
+            if kept.contains(&(idx, piece)) {
+                if run.is_empty() {
+                    run.push(path.nodes[piece]);
+                }
+                run.push(path.nodes[piece + 1]);
+            } else if !run.is_empty() {
+                result.push(to_polygon(without_straight_vertices(run, false), false, false));
+                run = Vec::new();
+            }
+        }
+    }
+
+    sort_canonical(&mut result);
+    Ok(result)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct IntPoint {
+    x: i64,
+    y: i64,
+}
+
+/// Ring or open path of one of the operands
+struct Path {
+    nodes: Vec<IntPoint>,
+    operand: usize,
+    is_closed: bool,
+}
+
+/// Edge of a ring or path, from its lower to its upper end point (from left
+/// to right if it is horizontal)
+struct Edge {
+    bottom: IntPoint,
+    top: IntPoint,
+    operand: usize,
+    /// Change of the winding number of the operand from the left to the
+    /// right side, zero for the edges of open paths
+    delta: i32,
+    /// Index of the path and of the edge in it, for the edges of open paths
+    piece: Option<(usize, usize)>,
+}
+
+fn sub(a: IntPoint, b: IntPoint) -> (i128, i128) {
+    (a.x as i128 - b.x as i128, a.y as i128 - b.y as i128)
+}
+
+fn cross(u: (i128, i128), v: (i128, i128)) -> i128 {
+    u.0 * v.1 - u.1 * v.0
+}
+
+fn dot(u: (i128, i128), v: (i128, i128)) -> i128 {
+    u.0 * v.0 + u.1 * v.1
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`: positive if `c` lies
+/// left of the line from `a` to `b`
+fn orient(a: IntPoint, b: IntPoint, c: IntPoint) -> i128 {
+    cross(sub(b, a), sub(c, a))
+}
+
+/// Returns true if `p`, which lies on the line through `a` and `b`, lies
+/// strictly between them
+fn strictly_between(a: IntPoint, b: IntPoint, p: IntPoint) -> bool {
+    dot(sub(p, a), sub(b, a)) > 0 && dot(sub(p, b), sub(a, b)) > 0
+}
+
+/// `n / d`, rounded to the nearest integer
+fn div_round(n: i128, d: i128) -> i128 {
+    let (n, d) = if d < 0 { (-n, -d) } else { (n, d) };
+    let q = n.div_euclid(d);
+    if 2 * n.rem_euclid(d) >= d { q + 1 } else { q }
+}
+
+/// Point where the edges `a`-`b` and `c`-`d` cross, rounded to the nearest
+/// integer point in the overlap of their bounding boxes
+fn crossing(a: IntPoint, b: IntPoint, c: IntPoint, d: IntPoint) -> IntPoint {
+    let (r, s) = (sub(b, a), sub(d, c));
+    let denominator = cross(r, s);
+    let numerator = cross(sub(c, a), s);
+
+    // exact as long as the product fits into an i128, which it does for
+    // coordinates below 2^31
+    let along = |start: i64, delta: i128| -> i64 {
+        match delta.checked_mul(numerator) {
+            Some(product) => start + div_round(product, denominator) as i64,
+            None => start + (delta as f64 * (numerator as f64 / denominator as f64)).round() as i64,
+        }
+    };
+
+    let clamp = |v: i64, p: i64, q: i64, u: i64, w: i64| {
+        let (low, high) = (p.min(q).max(u.min(w)), p.max(q).min(u.max(w)));
+        v.max(low).min(high)
+    };
+    IntPoint {
+        x: clamp(along(a.x, r.0), a.x, b.x, c.x, d.x),
+        y: clamp(along(a.y, r.1), a.y, b.y, c.y, d.y),
+    }
+}
+
+/// Removes consecutive duplicate nodes, including the closing node of a ring
+fn without_duplicates(mut nodes: Vec<IntPoint>, is_closed: bool) -> Vec<IntPoint> {
+    nodes.dedup();
+    while is_closed && nodes.len() > 1 && nodes.first() == nodes.last() {
+        nodes.pop();
+    }
+    nodes
+}
+
+/// Removes the nodes where the path continues in a straight line
+fn without_straight_vertices(nodes: Vec<IntPoint>, is_closed: bool) -> Vec<IntPoint> {
+    let straight = |a: IntPoint, b: IntPoint, c: IntPoint| orient(a, b, c) == 0 && dot(sub(b, a), sub(c, b)) > 0;
+
+    let mut out: Vec<IntPoint> = Vec::with_capacity(nodes.len());
+    for p in nodes {
+        while out.len() >= 2 && straight(out[out.len() - 2], out[out.len() - 1], p) {
+            out.pop();
+        }
+        out.push(p);
+    }
+
+    while is_closed && out.len() >= 3 {
+        let len = out.len();
+        if straight(out[len - 2], out[len - 1], out[0]) {
+            out.pop();
+        } else if straight(out[len - 1], out[0], out[1]) {
+            out.remove(0);
+        } else {
+            break;
+        }
+    }
+    out
+}
+
+/// Twice the signed area of the ring, positive if it is counter-clockwise
+fn signed_area2(ring: &[IntPoint]) -> i128 {
+    // relative to the first vertex every term fits into an i128, and so does
+    // the sum, so intermediate overflows cancel out
+    let origin = ring[0];
+    (1..ring.len().saturating_sub(1))
+        .map(|i| cross(sub(ring[i], origin), sub(ring[i + 1], origin)))
+        .fold(0i128, |sum, term| sum.wrapping_add(term))
+}
+
+fn to_polygon(nodes: Vec<IntPoint>, is_hole: bool, is_closed: bool) -> PolygonI64 {
+    TypedPolygon {
+        nodes: nodes.into_iter().map(|p| [p.x, p.y]).collect(),
+        is_hole: is_hole,
+        is_closed: is_closed,
+    }
+}
+
+/// Rotates every ring so that it starts at its smallest vertex and sorts the
+/// result, like `MultiPolygon::sort_canonical`
+fn sort_canonical(polygons: &mut Vec<PolygonI64>) {
+    for polygon in polygons.iter_mut().filter(|p| p.is_closed) {
+        let start = (0..polygon.nodes.len()).min_by_key(|i| polygon.nodes[*i]).unwrap_or(0);
+        polygon.nodes.rotate_left(start);
+    }
+    polygons.sort_by(|a, b| {
+        b.is_closed.cmp(&a.is_closed)
+            .then_with(|| a.nodes.cmp(&b.nodes))
+            .then_with(|| a.is_hole.cmp(&b.is_hole))
+    });
+}
+
+/// Splits the edges of the paths at the points where they cross or touch,
+/// until no two edges cross
+fn node_paths(paths: &mut [Path], guard: &mut OperationGuard) -> Result<(), ClipError> {
+    loop {
+        let mut edges: Vec<(usize, usize, IntPoint, IntPoint)> = Vec::new();
+        for (idx, path) in paths.iter().enumerate() {
+            let len = path.nodes.len();
+            let count = if path.is_closed { len } else { len.saturating_sub(1) };
+            edges.extend((0..count)
+                .map(|i| (idx, i, path.nodes[i], path.nodes[(i + 1) % len]))
+                .filter(|&(_, _, a, b)| a != b));
+        }
+        edges.sort_by_key(|&(_, _, a, b)| a.y.min(b.y));
+
+        // edges whose y-range overlaps the one of the current edge
+        let mut active: Vec<usize> = Vec::new();
+        let mut splits: HashMap<(usize, usize), Vec<IntPoint>> = HashMap::new();
+        for current in 0..edges.len() {
+            guard.tick()?;
+            let (path, i, a, b) = edges[current];
+            active.retain(|&other| {
+                let (_, _, c, d) = edges[other];
+                c.y.max(d.y) >= a.y.min(b.y)
+            });
+
+            for &other in active.iter() {
+                let (other_path, j, c, d) = edges[other];
+                if a.x.max(b.x) < c.x.min(d.x) || c.x.max(d.x) < a.x.min(b.x) {
+                    continue;
+                }
+                let (first, second) = split_points(a, b, c, d, guard);
+                if !first.is_empty() {
+                    splits.entry((path, i)).or_insert_with(Vec::new).extend(first);
+                }
+                if !second.is_empty() {
+                    splits.entry((other_path, j)).or_insert_with(Vec::new).extend(second);
+                }
+            }
+            active.push(current);
+        }
+
+        if splits.is_empty() {
+            return Ok(());
+        }
+        for (idx, path) in paths.iter_mut().enumerate() {
+            insert_splits(idx, path, &splits);
+        }
+    }
+}
+
+/// Points where the edge `a`-`b` has to be split for the edge `c`-`d`, and
+/// the other way round
+fn split_points(a: IntPoint, b: IntPoint, c: IntPoint, d: IntPoint, guard: &mut OperationGuard) -> (Vec<IntPoint>, Vec<IntPoint>) {
+    let (mut first, mut second) = (Vec::new(), Vec::new());
+    let (o1, o2) = (orient(a, b, c).signum(), orient(a, b, d).signum());
+    let (o3, o4) = (orient(c, d, a).signum(), orient(c, d, b).signum());
+
+    if o1 * o2 < 0 && o3 * o4 < 0 {
+        let p = crossing(a, b, c, d);
+        if p != a && p != b {
+            first.push(p);
+        }
+        if p != c && p != d {
+            second.push(p);
+        }
+        guard.record(1, first.len() + second.len());
+    } else {
+        // an end point on the other edge, which includes overlapping edges
+        for &(o, p) in [(o1, c), (o2, d)].iter() {
+            if o == 0 && strictly_between(a, b, p) {
+                first.push(p);
+            }
+        }
+        for &(o, p) in [(o3, a), (o4, b)].iter() {
+            if o == 0 && strictly_between(c, d, p) {
+                second.push(p);
+            }
+        }
+        if !first.is_empty() || !second.is_empty() {
+            guard.record(1, first.len() + second.len());
+        }
+    }
+    (first, second)
+}
+
+/// Inserts the split points of the edges of path `idx` into its nodes
+fn insert_splits(idx: usize, path: &mut Path, splits: &HashMap<(usize, usize), Vec<IntPoint>>) {
+    let len = path.nodes.len();
+    if !(0..len).any(|i| splits.contains_key(&(idx, i))) {
+        return;
+    }
+
+    let mut nodes = Vec::with_capacity(len);
+    for i in 0..len {
+        let a = path.nodes[i];
+        nodes.push(a);
+        if let Some(points) = splits.get(&(idx, i)) {
+            let direction = sub(path.nodes[(i + 1) % len], a);
+            let mut points = points.clone();
+            points.sort_by_key(|&p| (dot(sub(p, a), direction), p.x, p.y));
+            nodes.extend(points);
+        }
+    }
+    path.nodes = without_duplicates(nodes, path.is_closed);
+}
+
+/// The edges of all paths, after they were split by `node_paths`
+fn edges_of(paths: &[Path]) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for (idx, path) in paths.iter().enumerate() {
+        let len = path.nodes.len();
+        let count = if path.is_closed { len } else { len.saturating_sub(1) };
+        for i in 0..count {
+            let (a, b) = (path.nodes[i], path.nodes[(i + 1) % len]);
+            let upwards = (a.y, a.x) < (b.y, b.x);
+            let (bottom, top) = if upwards { (a, b) } else { (b, a) };
+            // a ring that runs upwards has its inside on the left
+            let (delta, piece) = if path.is_closed { (if upwards { -1 } else { 1 }, None) } else { (0, Some((idx, i))) };
+            edges.push(Edge { bottom: bottom, top: top, operand: path.operand, delta: delta, piece: piece });
+        }
+    }
+    edges
+}
+
+/// Order of two edges that cross the same scanbeam, from left to right. The
+/// edges must not cross inside of the beam.
+fn compare_edges(e: &Edge, f: &Edge) -> Ordering {
+    if e.bottom == f.bottom {
+        return cross(sub(e.top, e.bottom), sub(f.top, f.bottom)).cmp(&0);
+    }
+
+    // if both end points of one edge lie on the same side of the line through
+    // the other, so does the part of the edge in the beam
+    let (s, t) = (orient(e.bottom, e.top, f.bottom).signum(), orient(e.bottom, e.top, f.top).signum());
+    if s >= 0 && t >= 0 && s + t > 0 {
+        return Ordering::Greater;
+    }
+    if s <= 0 && t <= 0 && s + t < 0 {
+        return Ordering::Less;
+    }
+    let (u, v) = (orient(f.bottom, f.top, e.bottom).signum(), orient(f.bottom, f.top, e.top).signum());
+    if u >= 0 && v >= 0 && u + v > 0 {
+        Ordering::Less
+    } else if u <= 0 && v <= 0 && u + v < 0 {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Winding numbers of both operands in the gaps of the active edges, left of
+/// the first edge up to right of the last one
+fn winding_prefix(active: &[&Edge]) -> Vec<[i32; 2]> {
+    let mut counts = [0, 0];
+    let mut prefix = Vec::with_capacity(active.len() + 1);
+    prefix.push(counts);
+    for edge in active {
+        counts[edge.operand] += edge.delta;
+        prefix.push(counts);
+    }
+    prefix
+}
+
+/// Number of active edges that pass the scanline `y` left of or at `x`
+fn position(active: &[&Edge], x: i64, y: i64) -> usize {
+    let p = IntPoint { x: x, y: y };
+    active.partition_point(|e| orient(e.bottom, e.top, p) <= 0)
+}
+
+/// Sweeps over the scanbeams from bottom to top and returns the directed
+/// boundary edges of the result, with the inside on the left, and the pieces
+/// of the open paths that are kept
+fn sweep(edges: &[Edge], operation_type: BoolOpType, guard: &mut OperationGuard)
+         -> Result<(Vec<(IntPoint, IntPoint)>, HashSet<(usize, usize)>), ClipError>
+{
+    let inside = |counts: [i32; 2]| {
+        let (in_a, in_b) = (counts[0] % 2 != 0, counts[1] % 2 != 0);
+        match operation_type {
+            BoolOpType::Intersection => in_a && in_b,
+            BoolOpType::Union => in_a || in_b,
+            BoolOpType::Difference => in_a && !in_b,
+            BoolOpType::Xor => in_a != in_b,
+        }
+    };
+    let keep_piece = |one_side: [i32; 2], other_side: [i32; 2]| {
+        let in_clip = one_side[1] % 2 != 0 || other_side[1] % 2 != 0;
+        in_clip == (operation_type == BoolOpType::Intersection)
+    };
+
+    let (mut rising, mut flat): (Vec<&Edge>, Vec<&Edge>) = edges.iter().partition(|e| e.bottom.y != e.top.y);
+    rising.sort_by_key(|e| e.bottom.y);
+    flat.sort_by_key(|e| (e.bottom.y, e.bottom.x, e.top.x));
+    let mut ys: Vec<i64> = edges.iter().flat_map(|e| vec![e.bottom.y, e.top.y]).collect();
+    ys.sort();
+    ys.dedup();
+
+    let mut boundary = Vec::new();
+    let mut kept = HashSet::new();
+    let mut active: Vec<&Edge> = Vec::new();
+    let (mut next_rising, mut next_flat) = (0, 0);
+
+    for y in ys {
+        guard.tick()?;
+
+        // the horizontal edges on the scanline, grouped by their extent, and
+        // the winding numbers in the beam below of them
+        let first_flat = next_flat;
+        while next_flat < flat.len() && flat[next_flat].bottom.y == y {
+            next_flat += 1;
+        }
+        let below = winding_prefix(&active);
+        let flat_below: Vec<[i32; 2]> = flat[first_flat..next_flat].iter()
+            .map(|e| below[position(&active, e.bottom.x, y)])
+            .collect();
+
+        active.retain(|e| e.top.y != y);
+        let first_rising = next_rising;
+        while next_rising < rising.len() && rising[next_rising].bottom.y == y {
+            let edge = rising[next_rising];
+            let pos = active.partition_point(|e| compare_edges(e, edge) != Ordering::Greater);
+            active.insert(pos, edge);
+            next_rising += 1;
+        }
+        if first_rising == next_rising && first_flat == next_flat {
+            continue;
+        }
+        let above = winding_prefix(&active);
+
+        // the edges that start on the scanline, grouped with the edges that
+        // are identical to them
+        let mut start = 0;
+        while start < active.len() {
+            let mut end = start + 1;
+            while end < active.len() && active[end].bottom == active[start].bottom && active[end].top == active[start].top {
+                end += 1;
+            }
+            if active[start].bottom.y == y {
+                let (left, right) = (above[start], above[end]);
+                let edge = active[start];
+                if inside(left) != inside(right) {
+                    boundary.push(if inside(left) { (edge.bottom, edge.top) } else { (edge.top, edge.bottom) });
+                }
+                if keep_piece(left, right) {
+                    kept.extend(active[start..end].iter().filter_map(|e| e.piece));
+                }
+            }
+            start = end;
+        }
+
+        let mut start = first_flat;
+        while start < next_flat {
+            let mut end = start + 1;
+            while end < next_flat && flat[end].bottom == flat[start].bottom && flat[end].top == flat[start].top {
+                end += 1;
+            }
+            let edge = flat[start];
+            let (under, over) = (flat_below[start - first_flat], above[position(&active, edge.bottom.x, y)]);
+            if inside(under) != inside(over) {
+                boundary.push(if inside(over) { (edge.bottom, edge.top) } else { (edge.top, edge.bottom) });
+            }
+            if keep_piece(under, over) {
+                kept.extend(flat[start..end].iter().filter_map(|e| e.piece));
+            }
+            start = end;
+        }
+    }
+
+    Ok((boundary, kept))
+}
+
+/// Links the directed boundary edges into rings. At a vertex with several
+/// outgoing edges, the first one clockwise from the incoming edge is taken,
+/// so that rings which touch in a vertex stay separate.
+fn link_rings(edges: &[(IntPoint, IntPoint)]) -> Vec<Vec<IntPoint>> {
+
+    let mut outgoing: HashMap<IntPoint, Vec<usize>> = HashMap::new();
+    for (idx, edge) in edges.iter().enumerate() {
+        outgoing.entry(edge.0).or_insert_with(Vec::new).push(idx);
+    }
+
+    // 0: less than half a turn clockwise from `back`, 1: opposite of it,
+    // 2: more than half a turn, 3: the same direction
+    let class = |back: (i128, i128), d: (i128, i128)| {
+        let c = cross(back, d);
+        if c < 0 { 0 } else if c == 0 && dot(back, d) < 0 { 1 } else if c > 0 { 2 } else { 3 }
+    };
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        let mut ring = Vec::new();
+        let mut current = start;
+        loop {
+            used[current] = true;
+            let (from, to) = edges[current];
+            ring.push(from);
+            let back = sub(from, to);
+            let next = outgoing[&to].iter().cloned().min_by(|&i, &j| {
+                let (u, v) = (sub(edges[i].1, to), sub(edges[j].1, to));
+                class(back, u).cmp(&class(back, v)).then_with(|| cross(u, v).cmp(&0))
+            });
+            match next {
+                Some(next) if !used[next] => current = next,
+                _ => break,
+            }
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+#[test]
+fn test_clip_i64() {
+    let square = |x: i64, y: i64, size: i64| PolygonI64 {
+        nodes: vec![[x, y], [x + size, y], [x + size, y + size], [x, y + size]],
+        is_hole: false,
+        is_closed: true,
+    };
+    let area = |polygons: &[PolygonI64]| -> i128 {
+        polygons.iter().map(|p| {
+            let ring: Vec<IntPoint> = p.nodes.iter().map(|n| IntPoint { x: n[0], y: n[1] }).collect();
+            signed_area2(&ring) / 2
+        }).sum()
+    };
+
+    let (a, b) = (square(0, 0, 4), square(2, 2, 4));
+    let intersection = a.try_clip(&b, BoolOpType::Intersection).unwrap();
+    assert_eq!(intersection, vec![square(2, 2, 2)]);
+    assert_eq!(area(&a.try_clip(&b, BoolOpType::Union).unwrap()), 28);
+    assert_eq!(area(&a.try_clip(&b, BoolOpType::Difference).unwrap()), 12);
+    let xor = a.try_clip(&b, BoolOpType::Xor).unwrap();
+    assert_eq!(xor.len(), 2);
+    assert_eq!(area(&xor), 24);
+
+    // a hole, an identical ring and rings that only touch in a corner
+    let frame = a.try_clip(&square(1, 1, 2), BoolOpType::Difference).unwrap();
+    assert_eq!(frame.len(), 2);
+    assert_eq!(frame.iter().filter(|p| p.is_hole).count(), 1);
+    assert_eq!(area(&frame), 12);
+    assert_eq!(a.try_clip(&a, BoolOpType::Union).unwrap(), vec![a.clone()]);
+    assert!(a.try_clip(&a, BoolOpType::Xor).unwrap().is_empty());
+    assert_eq!(a.try_clip(&square(4, 4, 4), BoolOpType::Union).unwrap().len(), 2);
+
+    // the same near the largest coordinates, where the crossings are rounded
+    // in floating point
+    let far = PolygonI64::MAX_COORDINATE - 8;
+    let result = square(far, far, 4).try_clip(&square(far + 2, far + 2, 4), BoolOpType::Intersection).unwrap();
+    assert_eq!(result, vec![square(far + 2, far + 2, 2)]);
+    assert_eq!(square(0, 0, PolygonI64::MAX_COORDINATE + 1).try_clip(&a, BoolOpType::Union),
+               Err(ClipError::CoordinateOutOfRange { index: 1 }));
+}
+
+#[test]
+fn test_clip_i64_rounds_crossings() {
+    let triangle = PolygonI64 { nodes: vec![[0, 0], [7, 0], [0, 7]], is_hole: false, is_closed: true };
+    let square = PolygonI64 { nodes: vec![[2, 2], [6, 2], [6, 6], [2, 6]], is_hole: false, is_closed: true };
+
+    // the hypotenuse crosses the square at (2, 5) and (5, 2) exactly
+    let result = triangle.try_clip(&square, BoolOpType::Intersection).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes, vec![[2, 2], [5, 2], [2, 5]]);
+
+    // the hypotenuse of this one crosses the square at (6, 2.8) and (2, 5.6)
+    let flat = PolygonI64 { nodes: vec![[0, 0], [10, 0], [0, 7]], is_hole: false, is_closed: true };
+    let result = flat.try_clip(&square, BoolOpType::Intersection).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes, vec![[2, 2], [6, 2], [6, 3], [2, 6]]);
+}
+
+#[test]
+fn test_clip_i64_open_paths() {
+    let square = PolygonI64 { nodes: vec![[0, 0], [4, 0], [4, 4], [0, 4]], is_hole: false, is_closed: true };
+    let path = PolygonI64 { nodes: vec![[-2, 1], [2, 1], [6, 3]], is_hole: false, is_closed: false };
+
+    // the path leaves the square at (4, 2)
+    let inside = path.try_clip(&square, BoolOpType::Intersection).unwrap();
+    assert_eq!(inside, vec![PolygonI64 { nodes: vec![[0, 1], [2, 1], [4, 2]], is_hole: false, is_closed: false }]);
+
+    let outside = path.try_clip(&square, BoolOpType::Difference).unwrap();
+    assert_eq!(outside.len(), 2);
+    assert!(outside.iter().all(|p| !p.is_closed));
+    assert!(outside.contains(&PolygonI64 { nodes: vec![[-2, 1], [0, 1]], is_hole: false, is_closed: false }));
+    assert!(outside.contains(&PolygonI64 { nodes: vec![[4, 2], [6, 3]], is_hole: false, is_closed: false }));
+
+    // a path along the boundary counts as inside, the union only keeps the clip
+    let along = PolygonI64 { nodes: vec![[0, 0], [4, 0]], is_hole: false, is_closed: false };
+    assert_eq!(along.try_clip(&square, BoolOpType::Intersection).unwrap(), vec![along.clone()]);
+    assert_eq!(along.try_clip(&square, BoolOpType::Union).unwrap(), vec![square.clone()]);
+}
+
+#[test]
+fn test_scanbeam_algorithm() {
+    use multi_polygon::MultiPolygon;
+    use options::{ClipOptions, ClipAlgorithm};
+
+    let options = ClipOptions { algorithm: ClipAlgorithm::Scanbeam, .. Default::default() };
+    let triangle = Polygon::from(vec![(0.0, 0.0), (10.0, 0.0), (0.0, 7.0)]);
+    let square = Polygon::rect(2.0, 2.0, 4.0, 4.0);
+    assert!(triangle.has_integer_coordinates() && square.has_integer_coordinates());
+
+    // the crossings at (6, 2.8) and (2, 5.6) are rounded
+    let result = triangle.try_clip(&square, BoolOpType::Intersection, &options).unwrap();
+    assert_eq!(result, vec![Polygon::from(vec![(2.0, 2.0), (6.0, 2.0), (6.0, 3.0), (2.0, 6.0)])]);
+    let multi = MultiPolygon::new(vec![triangle.clone()]).try_clip(&MultiPolygon::new(vec![square.clone()]), BoolOpType::Intersection, &options);
+    assert_eq!(multi.unwrap().polygons, result);
+
+    // an open subject is clipped as a path
+    let path = Polygon { is_closed: false, .. Polygon::from(vec![(0.0, 4.0), (8.0, 4.0)]) };
+    let result = path.try_clip(&square, BoolOpType::Intersection, &options).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].nodes, Polygon::from(vec![(2.0, 4.0), (6.0, 4.0)]).nodes);
+    assert!(!result[0].is_closed);
+
+    // fractional coordinates fall back to the arrangement
+    let shifted = Polygon::rect(2.5, 2.0, 4.0, 4.0);
+    assert!(!shifted.has_integer_coordinates());
+    assert_eq!(triangle.try_clip(&shifted, BoolOpType::Union, &options),
+               triangle.try_clip(&shifted, BoolOpType::Union, &ClipOptions::default()));
+}
@@ -1,29 +1,70 @@
-use point::Point2D;
+use {Point2D, Bbox, fsize};
 
-#[derive(Clone)]
-pub(crate) struct Segment<'a> {
-    pub(crate) begin_pt: &'a Point2D,
-    pub(crate) end_pt: &'a Point2D,
+/// Line segment from `a` to `b`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Segment {
+    /// Start point of the segment
+    pub a: Point2D,
+    /// End point of the segment
+    pub b: Point2D,
 }
 
-impl<'a> Segment<'a> {
+impl Segment {
     #[inline]
-    pub(crate) fn new(begin_pt: &'a Point2D, end_pt: &'a Point2D) -> Self {
+    pub fn new(a: Point2D, b: Point2D) -> Self {
         Self {
-            begin_pt: begin_pt,
-            end_pt: end_pt,
+            a: a,
+            b: b,
         }
     }
 
-    pub(crate) fn change_orientation(&mut self) {
-        ::std::mem::swap(&mut self.begin_pt, &mut self.end_pt);
+    /// Swaps the start and end point
+    #[inline]
+    pub fn reverse(&mut self) {
+        ::std::mem::swap(&mut self.a, &mut self.b);
+    }
+
+    #[inline]
+    pub fn length(&self) -> fsize {
+        self.a.dist(&self.b)
+    }
+
+    #[inline]
+    pub fn midpoint(&self) -> Point2D {
+        self.a.lerp(&self.b, 0.5)
+    }
+
+    /// Intersects two segments, see `line_intersect`
+    #[inline]
+    pub fn intersect(&self, other: &Segment) -> Option<(Point2D, Option<Point2D>)> {
+        ::point::line_intersect(&self.a, &self.b, &other.a, &other.b)
     }
 
-    pub(crate) fn set_begin(&mut self, begin: &'a Point2D) {
-        self.begin_pt = begin;
+    /// Shortest distance between the point and any point on the segment
+    #[inline]
+    pub fn distance_to_point(&self, p: &Point2D) -> fsize {
+        ::utils::distance_point_segment(p, &self.a, &self.b)
     }
 
-    pub(crate) fn set_end(&mut self, end: &'a Point2D) {
-        self.end_pt = end;
+    #[inline]
+    pub fn bbox(&self) -> Bbox {
+        Bbox::new(self.a.x, self.a.y, self.b.x, self.b.y)
     }
 }
+
+#[test]
+fn test_segment_utilities() {
+    let s = Segment::new(Point2D { x: 0.0, y: 0.0 }, Point2D { x: 4.0, y: 0.0 });
+    assert_eq!(s.length(), 4.0);
+    assert_eq!(s.midpoint(), Point2D { x: 2.0, y: 0.0 });
+    assert_eq!(s.distance_to_point(&Point2D { x: 2.0, y: 3.0 }), 3.0);
+    assert_eq!(s.distance_to_point(&Point2D { x: 7.0, y: 4.0 }), 5.0);
+
+    let t = Segment::new(Point2D { x: 1.0, y: -1.0 }, Point2D { x: 1.0, y: 1.0 });
+    assert_eq!(s.intersect(&t), Some((Point2D { x: 1.0, y: 0.0 }, None)));
+
+    let bbox = t.bbox();
+    assert_eq!(bbox.min(), Point2D { x: 1.0, y: -1.0 });
+    assert_eq!(bbox.max(), Point2D { x: 1.0, y: 1.0 });
+}
@@ -0,0 +1,91 @@
+//! Constructors for common shapes
+//!
+//! All curved shapes are approximated with line segments, so that the
+//! segments deviate no more than `tolerance` from the true outline.
+
+use flatten::flatten_arc;
+use polygon::Polygon;
+use {Point2D, fsize};
+
+const TWO_PI: fsize = 2.0 * ::std::f64::consts::PI as fsize;
+
+impl Polygon {
+
+    /// Axis-aligned rectangle with the corner `(x, y)`, the `width` and the `height`
+    pub fn rect(x: fsize, y: fsize, width: fsize, height: fsize) -> Self {
+        Polygon::from_parts(vec![
+            Point2D { x: x, y: y },
+            Point2D { x: x + width, y: y },
+            Point2D { x: x + width, y: y + height },
+            Point2D { x: x, y: y + height },
+        ], false, true)
+    }
+
+    /// Counter-clockwise circle
+    pub fn circle(center: Point2D, radius: fsize, tolerance: fsize) -> Self {
+        Polygon::ellipse(center, radius, radius, 0.0, tolerance)
+    }
+
+    /// Counter-clockwise ellipse with the radii `rx` and `ry`, where the x axis
+    /// of the ellipse is rotated by `rotation` (in radians)
+    pub fn ellipse(center: Point2D, rx: fsize, ry: fsize, rotation: fsize, tolerance: fsize) -> Self {
+        let mut nodes = Vec::new();
+        // the arc does not include its start point, so the closing
+        // point is not duplicated
+        flatten_arc(&center, rx, ry, rotation, 0.0, TWO_PI, tolerance, &mut nodes);
+        Polygon::from_parts(nodes, false, true)
+    }
+
+    /// Counter-clockwise regular polygon with `n` corners (at least 3) on a circle
+    /// with the given `radius`. The first corner points in the direction of the x axis.
+    pub fn regular(n: usize, center: Point2D, radius: fsize) -> Self {
+        let n = n.max(3);
+        let nodes = (0..n).map(|i| {
+            let (sin, cos) = (TWO_PI * i as fsize / n as fsize).sin_cos();
+            Point2D { x: center.x + radius * cos, y: center.y + radius * sin }
+        }).collect();
+        Polygon::from_parts(nodes, false, true)
+    }
+
+    /// Circular sector ("pie slice") from `start_angle` over `sweep_angle`
+    /// (in radians, counter-clockwise if positive)
+    ///
+    /// If the sweep covers the full circle, a circle is returned instead.
+    pub fn sector(center: Point2D, radius: fsize, start_angle: fsize, sweep_angle: fsize,
+                  tolerance: fsize) -> Self
+    {
+        if sweep_angle.abs() >= TWO_PI {
+            return Polygon::circle(center, radius, tolerance);
+        }
+
+        let (sin, cos) = start_angle.sin_cos();
+        let mut nodes = vec![center, Point2D { x: center.x + radius * cos, y: center.y + radius * sin }];
+        flatten_arc(&center, radius, radius, 0.0, start_angle, sweep_angle, tolerance, &mut nodes);
+        Polygon::from_parts(nodes, false, true)
+    }
+}
+
+#[test]
+fn test_shape_constructors() {
+    use polygon::WindingOrder;
+
+    let center = Point2D { x: 1.0, y: 2.0 };
+
+    let rect = Polygon::rect(0.0, 0.0, 4.0, 2.0);
+    assert_eq!(rect.bounding_box(), ::Bbox::new(0.0, 0.0, 4.0, 2.0));
+    assert_eq!(rect.winding, Some(WindingOrder::CounterClockwise));
+
+    let circle = Polygon::circle(center, 10.0, 0.01);
+    assert!(circle.nodes.len() > 16);
+    assert!(circle.nodes.iter().all(|p| (p.dist(&center) - 10.0).abs() < 0.001));
+    assert!(circle.nodes.first() != circle.nodes.last());
+    assert_eq!(circle.winding, Some(WindingOrder::CounterClockwise));
+
+    let hexagon = Polygon::regular(6, center, 1.0);
+    assert_eq!(hexagon.nodes.len(), 6);
+    assert_eq!(hexagon.nodes[0], Point2D { x: 2.0, y: 2.0 });
+
+    let quarter = Polygon::sector(center, 1.0, 0.0, ::std::f64::consts::FRAC_PI_2 as fsize, 0.5);
+    assert_eq!(quarter.nodes[0], center);
+    assert_eq!(quarter.nodes.len(), 3);
+}
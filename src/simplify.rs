@@ -0,0 +1,339 @@
+//! Douglas-Peucker simplification of polygon rings and open chains
+
+use polygon::Polygon;
+use multi_polygon::MultiPolygon;
+use std::collections::{HashMap, HashSet};
+use utils::distance_point_segment;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Simplifies the polygon using the Douglas-Peucker algorithm
+    ///
+    /// Every vertex that is closer than `tolerance` to the simplified
+    /// boundary is removed. Closed rings are never reduced below three
+    /// vertices and the simplified boundary is checked for self-intersections:
+    /// if removing vertices would make two edges cross, the offending edges
+    /// are refined again until the result is simple.
+    ///
+    /// Open chains (`is_closed == false`) keep their first and last point.
+    pub fn simplify(&self, tolerance: fsize) -> Self {
+        let keep = simplify_mask(&self.nodes, self.is_closed, tolerance);
+        self.with_nodes(self.nodes.iter().zip(keep.iter()).filter(|&(_, k)| *k).map(|(p, _)| *p).collect())
+    }
+}
+
+impl<T: Clone> MultiPolygon<T> {
+
+    /// Simplifies all polygons in the set, without opening gaps or
+    /// creating overlaps between adjacent polygons
+    ///
+    /// Boundaries that are shared between polygons (vertex-for-vertex, as
+    /// produced by clipping a coverage) are split into arcs at every junction
+    /// vertex. Each arc is simplified once, with its endpoints fixed, and the
+    /// same result is used on both sides of the boundary.
+    pub fn simplify_preserve_topology(&self, tolerance: fsize) -> Self {
+
+        // collect the distinct neighbours of every vertex
+        let mut neighbours = HashMap::<Point2D, HashSet<Point2D>>::new();
+        for poly in &self.polygons {
+            let len = poly.nodes.len();
+            if len < 2 {
+                continue;
+            }
+            let edge_count = if poly.is_closed { len } else { len - 1 };
+            for i in 0..edge_count {
+                let a = poly.nodes[i];
+                let b = poly.nodes[(i + 1) % len];
+                if a == b {
+                    continue;
+                }
+                neighbours.entry(a).or_insert_with(HashSet::new).insert(b);
+                neighbours.entry(b).or_insert_with(HashSet::new).insert(a);
+            }
+        }
+
+        // a vertex that is not connected to exactly two others is a junction
+        let is_junction = |p: &Point2D| neighbours.get(p).map(|n| n.len() != 2).unwrap_or(true);
+
+        let mut kept = HashSet::<Point2D>::new();
+        let mut done_arcs = HashSet::<Vec<Point2D>>::new();
+
+        for poly in &self.polygons {
+            let len = poly.nodes.len();
+            if len < 3 && poly.is_closed {
+                kept.extend(poly.nodes.iter().cloned());
+                continue;
+            }
+
+            let mut junctions: Vec<usize> = (0..len).filter(|i| is_junction(&poly.nodes[*i])).collect();
+
+            if !poly.is_closed {
+                // endpoints of open chains are always fixed
+                if junctions.first() != Some(&0) {
+                    junctions.insert(0, 0);
+                }
+                if junctions.last() != Some(&(len - 1)) {
+                    junctions.push(len - 1);
+                }
+            }
+
+            if junctions.is_empty() {
+                // free-standing ring or a ring that is shared as a whole:
+                // rotate and orient it canonically, so all copies simplify the same way
+                let ring = canonical_ring(&poly.nodes);
+                if !done_arcs.contains(&ring) {
+                    let mask = simplify_mask(&ring, true, tolerance);
+                    kept.extend(ring.iter().zip(mask.iter()).filter(|&(_, k)| *k).map(|(p, _)| *p));
+                    done_arcs.insert(ring);
+                }
+                continue;
+            }
+
+            let arc_count = if poly.is_closed { junctions.len() } else { junctions.len() - 1 };
+            for j in 0..arc_count {
+                let start = junctions[j];
+                let end = if j + 1 == junctions.len() { junctions[0] + len } else { junctions[j + 1] };
+                let forward: Vec<Point2D> = (start..(end + 1)).map(|i| poly.nodes[i % len]).collect();
+                let mut backward = forward.clone();
+                backward.reverse();
+
+                // orient the arc canonically, so both sides of a shared boundary agree
+                let arc = if backward < forward { backward } else { forward };
+
+                if !done_arcs.contains(&arc) {
+                    let mask = simplify_mask(&arc, false, tolerance);
+                    kept.extend(arc.iter().zip(mask.iter()).filter(|&(_, k)| *k).map(|(p, _)| *p));
+                    done_arcs.insert(arc);
+                }
+            }
+        }
+
+        // rings that would collapse keep all their vertices - since the set of kept
+        // vertices is shared, their neighbours keep the same vertices, too
+        for poly in &self.polygons {
+            let min_len = if poly.is_closed { 3 } else { 2 };
+            if poly.nodes.iter().filter(|p| kept.contains(p)).count() < min_len {
+                kept.extend(poly.nodes.iter().cloned());
+            }
+        }
+
+        Self {
+            polygons: self.polygons.iter().map(|poly| {
+                poly.with_nodes(poly.nodes.iter().filter(|p| kept.contains(p)).cloned().collect())
+            }).collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+/// Rotates the ring to start at its smallest vertex and orients it so that
+/// the same ring always results in the same sequence of points
+fn canonical_ring(nodes: &[Point2D]) -> Vec<Point2D> {
+    let len = nodes.len();
+    let start = (0..len).min_by_key(|i| nodes[*i]).unwrap_or(0);
+    let forward: Vec<Point2D> = (0..len).map(|i| nodes[(start + i) % len]).collect();
+    let backward: Vec<Point2D> = (0..len).map(|i| nodes[(start + len - i) % len]).collect();
+    if backward < forward { backward } else { forward }
+}
+
+/// Runs Douglas-Peucker on `nodes` and returns which vertices are kept
+///
+/// Shared with the topology-preserving simplification, which needs
+/// to simplify individual arcs with fixed endpoints.
+pub(crate) fn simplify_mask(nodes: &[Point2D], is_closed: bool, tolerance: fsize) -> Vec<bool> {
+
+    let len = nodes.len();
+    let min_len = if is_closed { 3 } else { 2 };
+
+    if len <= min_len || !(tolerance > 0.0) {
+        return vec![true; len];
+    }
+
+    let mut keep = vec![false; len];
+
+    if is_closed {
+        // A ring has no natural endpoints: anchor it at the first vertex
+        // and the vertex furthest away from it, then simplify both halves
+        let far_idx = (1..len)
+            .max_by(|a, b| nodes[0].dist(&nodes[*a]).partial_cmp(&nodes[0].dist(&nodes[*b])).unwrap())
+            .unwrap();
+        keep[0] = true;
+        keep[far_idx] = true;
+        douglas_peucker(nodes, 0, far_idx, tolerance, &mut keep);
+        douglas_peucker_wrapping(nodes, far_idx, len, tolerance, &mut keep);
+
+        // Both halves collapsed onto the anchor line: re-add the
+        // vertex furthest from it, so the ring keeps an area
+        if keep.iter().filter(|k| **k).count() < 3 {
+            if let Some(idx) = furthest_from_chord(nodes, 0, far_idx, len).or_else(|| furthest_from_chord(nodes, far_idx, len, len)) {
+                keep[idx % len] = true;
+            }
+        }
+    } else {
+        keep[0] = true;
+        keep[len - 1] = true;
+        douglas_peucker(nodes, 0, len - 1, tolerance, &mut keep);
+    }
+
+    // Removing vertices can make previously disjoint edges cross.
+    // Re-insert the furthest dropped vertex of each crossing edge until
+    // the simplified boundary is simple again. Every iteration adds at least
+    // one vertex, so this always terminates.
+    loop {
+        let kept: Vec<usize> = (0..len).filter(|i| keep[*i]).collect();
+        let crossing = find_crossing_edges(nodes, &kept, is_closed);
+        if crossing.is_empty() {
+            break;
+        }
+
+        let mut changed = false;
+        for k in crossing {
+            let start = kept[k];
+            let end = if k + 1 == kept.len() { kept[0] + len } else { kept[k + 1] };
+            if let Some(idx) = furthest_from_chord(nodes, start, end, len) {
+                keep[idx % len] = true;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    keep
+}
+
+/// Recursive Douglas-Peucker step between the (kept) vertices `start` and `end`
+fn douglas_peucker(nodes: &[Point2D], start: usize, end: usize, tolerance: fsize, keep: &mut [bool]) {
+    if end <= start + 1 {
+        return;
+    }
+
+    if let Some(idx) = furthest_from_chord(nodes, start, end, nodes.len()) {
+        if distance_point_segment(&nodes[idx], &nodes[start], &nodes[end]) > tolerance {
+            keep[idx] = true;
+            douglas_peucker(nodes, start, idx, tolerance, keep);
+            douglas_peucker(nodes, idx, end, tolerance, keep);
+        }
+    }
+}
+
+/// Same as `douglas_peucker`, but `end` may be `nodes.len()`, which
+/// refers to the first vertex again (closing edge of a ring)
+fn douglas_peucker_wrapping(nodes: &[Point2D], start: usize, end: usize, tolerance: fsize, keep: &mut [bool]) {
+    let len = nodes.len();
+    if end <= start + 1 {
+        return;
+    }
+
+    if let Some(idx) = furthest_from_chord(nodes, start, end, len) {
+        if distance_point_segment(&nodes[idx % len], &nodes[start % len], &nodes[end % len]) > tolerance {
+            keep[idx % len] = true;
+            douglas_peucker_wrapping(nodes, start, idx, tolerance, keep);
+            douglas_peucker_wrapping(nodes, idx, end, tolerance, keep);
+        }
+    }
+}
+
+/// Returns the index of the vertex strictly between `start` and `end`
+/// that is furthest away from the chord (start, end). Indices may exceed
+/// `len`, in which case they wrap around.
+fn furthest_from_chord(nodes: &[Point2D], start: usize, end: usize, len: usize) -> Option<usize> {
+    let a = &nodes[start % len];
+    let b = &nodes[end % len];
+    let mut max_dist = -1.0;
+    let mut max_idx = None;
+
+    for idx in (start + 1)..end {
+        let dist = distance_point_segment(&nodes[idx % len], a, b);
+        if dist > max_dist {
+            max_dist = dist;
+            max_idx = Some(idx);
+        }
+    }
+
+    max_idx
+}
+
+/// Returns the indices (into `kept`) of all edges that cross
+/// a non-adjacent edge of the simplified boundary
+fn find_crossing_edges(nodes: &[Point2D], kept: &[usize], is_closed: bool) -> Vec<usize> {
+    let kept_len = kept.len();
+    let edge_count = if is_closed { kept_len } else { kept_len.saturating_sub(1) };
+    let mut crossing = Vec::new();
+
+    for i in 0..edge_count {
+        let a0 = &nodes[kept[i]];
+        let a1 = &nodes[kept[(i + 1) % kept_len]];
+        for j in (i + 2)..edge_count {
+            // first and last edge of a ring share a vertex
+            if is_closed && i == 0 && j + 1 == edge_count {
+                continue;
+            }
+            let b0 = &nodes[kept[j]];
+            let b1 = &nodes[kept[(j + 1) % kept_len]];
+            if ::utils::segments_intersect(a0, a1, b0, b1) {
+                crossing.push(i);
+                crossing.push(j);
+            }
+        }
+    }
+
+    crossing.sort();
+    crossing.dedup();
+    crossing
+}
+
+#[test]
+fn test_simplify_removes_noise() {
+    let poly = Polygon {
+        nodes: vec![
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 5.0, y: 0.01 },
+            Point2D { x: 10.0, y: 0.0 },
+            Point2D { x: 10.0, y: 10.0 },
+            Point2D { x: 0.0, y: 10.0 },
+        ],
+        .. Default::default()
+    };
+    assert_eq!(poly.simplify(0.1).nodes.len(), 4);
+}
+
+#[test]
+fn test_simplify_keeps_triangle() {
+    let poly = Polygon {
+        nodes: vec![
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 10.0, y: 0.0 },
+            Point2D { x: 5.0, y: 0.5 },
+            Point2D { x: 0.0, y: 0.1 },
+        ],
+        .. Default::default()
+    };
+    assert!(poly.simplify(100.0).nodes.len() >= 3);
+}
+
+#[test]
+fn test_simplify_preserve_topology_shared_edge() {
+    // two squares sharing a noisy edge at x = 10
+    let shared = [
+        Point2D { x: 10.0, y: 0.0 },
+        Point2D { x: 10.01, y: 5.0 },
+        Point2D { x: 10.0, y: 10.0 },
+    ];
+    let left = Polygon {
+        nodes: vec![Point2D { x: 0.0, y: 0.0 }, shared[0], shared[1], shared[2], Point2D { x: 0.0, y: 10.0 }],
+        .. Default::default()
+    };
+    let right = Polygon {
+        nodes: vec![shared[2], shared[1], shared[0], Point2D { x: 20.0, y: 0.0 }, Point2D { x: 20.0, y: 10.0 }],
+        .. Default::default()
+    };
+    let result = MultiPolygon::new(vec![left, right]).simplify_preserve_topology(0.1);
+    assert!(!result.polygons[0].nodes.contains(&shared[1]));
+    assert!(!result.polygons[1].nodes.contains(&shared[1]));
+    assert_eq!(result.polygons[0].nodes.len(), 4);
+    assert_eq!(result.polygons[1].nodes.len(), 4);
+}
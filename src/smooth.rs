@@ -0,0 +1,133 @@
+//! Chaikin corner-cutting smoothing
+
+use polygon::Polygon;
+use multi_polygon::MultiPolygon;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Smooths the polygon by cutting every corner `iterations` times
+    /// (Chaikin's algorithm)
+    ///
+    /// Every iteration doubles the number of vertices. Open chains keep
+    /// their first and last point.
+    pub fn smooth_chaikin(&self, iterations: usize) -> Self {
+        self.smooth_chaikin_impl(iterations, None)
+    }
+
+    /// Same as `smooth_chaikin`, but the smoothed boundary will not deviate
+    /// more than `max_deviation` from the original boundary
+    ///
+    /// Corners that would be cut too deep are cut less. The deviation budget is
+    /// split across the iterations (half for the first one, a quarter for the
+    /// second one, etc.), so the total deviation stays below `max_deviation`.
+    pub fn smooth_chaikin_bounded(&self, iterations: usize, max_deviation: fsize) -> Self {
+        self.smooth_chaikin_impl(iterations, Some(max_deviation))
+    }
+
+    fn smooth_chaikin_impl(&self, iterations: usize, max_deviation: Option<fsize>) -> Self {
+        let mut nodes = self.nodes.clone();
+        let mut budget = max_deviation.map(|d| d.max(0.0));
+
+        for _ in 0..iterations {
+            budget = budget.map(|b| b / 2.0);
+            nodes = chaikin_step(&nodes, self.is_closed, budget);
+        }
+
+        self.with_nodes(nodes)
+    }
+}
+
+impl<T: Clone> MultiPolygon<T> {
+
+    /// Smooths all polygons and holes, see `Polygon::smooth_chaikin`
+    ///
+    /// Holes keep their `is_hole` flag and winding order, since cutting
+    /// corners never changes the orientation of a ring.
+    pub fn smooth_chaikin(&self, iterations: usize) -> Self {
+        Self {
+            polygons: self.polygons.iter().map(|p| p.smooth_chaikin(iterations)).collect(),
+            data: self.data.clone(),
+        }
+    }
+
+    /// Smooths all polygons and holes, see `Polygon::smooth_chaikin_bounded`
+    pub fn smooth_chaikin_bounded(&self, iterations: usize, max_deviation: fsize) -> Self {
+        Self {
+            polygons: self.polygons.iter().map(|p| p.smooth_chaikin_bounded(iterations, max_deviation)).collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+/// A single iteration of Chaikin's algorithm: every corner `b` with the
+/// neighbours `a` and `c` is replaced by the points at 1/4 of the way to `a`
+/// and `c`. If a deviation bound is given, the cut is moved closer to the corner.
+fn chaikin_step(nodes: &[Point2D], is_closed: bool, max_deviation: Option<fsize>) -> Vec<Point2D> {
+
+    let len = nodes.len();
+    if len < 3 {
+        return nodes.to_vec();
+    }
+
+    let mut out = Vec::<Point2D>::with_capacity(len * 2);
+
+    let (first, last) = if is_closed { (0, len) } else { (1, len - 1) };
+
+    if !is_closed {
+        out.push(nodes[0]);
+    }
+
+    for i in first..last {
+        let a = &nodes[(i + len - 1) % len];
+        let b = &nodes[i];
+        let c = &nodes[(i + 1) % len];
+
+        let mut t = 0.25;
+
+        if let Some(max) = max_deviation {
+            let p = b.lerp(a, t);
+            let q = b.lerp(c, t);
+            let deviation = ::utils::distance_point_segment(b, &p, &q);
+            if deviation > max {
+                // the deviation grows linearly with t
+                t *= max / deviation;
+            }
+        }
+
+        if t == 0.0 {
+            out.push(*b);
+        } else {
+            out.push(b.lerp(a, t));
+            out.push(b.lerp(c, t));
+        }
+    }
+
+    if !is_closed {
+        out.push(nodes[len - 1]);
+    }
+
+    out
+}
+
+#[test]
+fn test_smooth_chaikin_bounded() {
+    let poly = Polygon {
+        nodes: vec![
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 10.0, y: 0.0 },
+            Point2D { x: 10.0, y: 10.0 },
+            Point2D { x: 0.0, y: 10.0 },
+        ],
+        .. Default::default()
+    };
+    let smooth = poly.smooth_chaikin(2);
+    assert_eq!(smooth.nodes.len(), 16);
+
+    let bounded = poly.smooth_chaikin_bounded(3, 0.5);
+    for p in &bounded.nodes {
+        // distance to the square boundary
+        let d = p.x.min(p.y).min(10.0 - p.x).min(10.0 - p.y);
+        assert!(d <= 0.5);
+    }
+}
@@ -0,0 +1,60 @@
+//! Boolean operations that yield their result contour by contour
+
+use arrangement::{Arrangement, BoundaryRings};
+use polygon::{Polygon, BoolOpType};
+
+impl Polygon {
+
+    /// Runs the boolean operation and returns the result contours one at a
+    /// time, in no particular order
+    ///
+    /// Every contour is traced from the arrangement of both polygons only when
+    /// the iterator is advanced, so the result is never stored as a whole.
+    /// Pipelines that tessellate or serialize each contour right away need less
+    /// memory than with the `Vec` returned by `clip`. Holes are returned as
+    /// clockwise contours with `is_hole` set.
+    pub fn clip_iter(&self, other: &Self, operation_type: BoolOpType) -> impl Iterator<Item = Polygon> {
+        let arrangement = Arrangement::new(::std::slice::from_ref(self), ::std::slice::from_ref(other));
+        let rings = match operation_type {
+            BoolOpType::Union => BoundaryRings::new(arrangement, |a, b| a || b),
+            BoolOpType::Intersection => BoundaryRings::new(arrangement, |a, b| a && b),
+            BoolOpType::Difference => BoundaryRings::new(arrangement, |a, b| a && !b),
+            BoolOpType::Xor => BoundaryRings::new(arrangement, |a, b| a != b),
+        };
+        rings.map(|traced| traced.polygon)
+    }
+
+    /// Streaming version of `intersection`, see `clip_iter`
+    pub fn intersection_iter(&self, other: &Self) -> impl Iterator<Item = Polygon> {
+        self.clip_iter(other, BoolOpType::Intersection)
+    }
+
+    /// Streaming version of `union`, see `clip_iter`
+    pub fn union_iter(&self, other: &Self) -> impl Iterator<Item = Polygon> {
+        self.clip_iter(other, BoolOpType::Union)
+    }
+
+    /// Streaming version of `difference`, see `clip_iter`
+    pub fn difference_iter(&self, other: &Self) -> impl Iterator<Item = Polygon> {
+        self.clip_iter(other, BoolOpType::Difference)
+    }
+
+    /// Streaming version of `xor`, see `clip_iter`
+    pub fn xor_iter(&self, other: &Self) -> impl Iterator<Item = Polygon> {
+        self.clip_iter(other, BoolOpType::Xor)
+    }
+}
+
+#[test]
+fn test_clip_iter() {
+    let a = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    let b = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+
+    let mut contours = a.intersection_iter(&b);
+    let first = contours.next().unwrap();
+    assert_eq!(first.area(), 1.0);
+    assert!(contours.next().is_none());
+
+    assert_eq!(a.xor_iter(&b).count(), 2);
+    assert_eq!(a.union_iter(&b).map(|p| p.area()).sum::<::fsize>(), 7.0);
+}
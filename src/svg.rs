@@ -0,0 +1,440 @@
+//! SVG path import and export
+
+use polygon::Polygon;
+use multi_polygon::MultiPolygon;
+use flatten::{flatten_quadratic, flatten_cubic, flatten_arc};
+use std::fmt::Write as FmtWrite;
+use std::io;
+use std::path::Path;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Parses the `d` attribute of an SVG `<path>` element
+    ///
+    /// Curves (quadratic and cubic beziers, elliptical arcs) are flattened into line
+    /// segments, which deviate at most `flatten_tolerance` from the curve. Every
+    /// subpath becomes one polygon; subpaths that end with `Z` are closed, all other
+    /// subpaths are returned with `is_closed` set to false.
+    ///
+    /// SVG decides about holes with a fill rule instead of flags, so `is_hole` is
+    /// never set - use `orient` to classify the contours. Returns `None` if the
+    /// path data is malformed.
+    pub fn from_svg_path(d: &str, flatten_tolerance: fsize) -> Option<Vec<Self>> {
+
+        let mut parser = SvgPathParser { input: d.as_bytes(), pos: 0 };
+        let mut polygons = Vec::<Polygon>::new();
+        let mut nodes = Vec::<Point2D>::new();
+
+        let mut current = Point2D { x: 0.0, y: 0.0 };
+        let mut subpath_start = current;
+        // last control point, for the smooth curve commands (S, T)
+        let mut last_cubic_ctrl: Option<Point2D> = None;
+        let mut last_quad_ctrl: Option<Point2D> = None;
+        let mut command: Option<u8> = None;
+
+        loop {
+            parser.skip_separators();
+            if parser.is_at_end() {
+                break;
+            }
+
+            if let Some(c) = parser.command() {
+                command = Some(c);
+            } else if command.is_none() {
+                return None;
+            }
+
+            let cmd = command.unwrap();
+            let relative = (cmd as char).is_ascii_lowercase();
+            let offset = if relative { current } else { Point2D { x: 0.0, y: 0.0 } };
+            let mut cubic_ctrl = None;
+            let mut quad_ctrl = None;
+
+            // drawing after Z continues at the start of the closed subpath
+            if nodes.is_empty() && cmd.to_ascii_uppercase() != b'M' && cmd.to_ascii_uppercase() != b'Z' {
+                nodes.push(current);
+            }
+
+            match cmd.to_ascii_uppercase() {
+                b'M' => {
+                    finish_subpath(&mut polygons, &mut nodes, false);
+                    current = parser.point(&offset)?;
+                    subpath_start = current;
+                    nodes.push(current);
+                    // following coordinate pairs are implicit lineto commands
+                    command = Some(if relative { b'l' } else { b'L' });
+                },
+                b'L' => {
+                    current = parser.point(&offset)?;
+                    nodes.push(current);
+                },
+                b'H' => {
+                    current = Point2D { x: parser.number()? + offset.x, y: current.y };
+                    nodes.push(current);
+                },
+                b'V' => {
+                    current = Point2D { x: current.x, y: parser.number()? + offset.y };
+                    nodes.push(current);
+                },
+                b'C' | b'S' => {
+                    let p1 = if cmd.to_ascii_uppercase() == b'C' {
+                        parser.point(&offset)?
+                    } else {
+                        reflect(last_cubic_ctrl, &current)
+                    };
+                    let p2 = parser.point(&offset)?;
+                    let p3 = parser.point(&offset)?;
+                    flatten_cubic(&current, &p1, &p2, &p3, flatten_tolerance, &mut nodes);
+                    cubic_ctrl = Some(p2);
+                    current = p3;
+                },
+                b'Q' | b'T' => {
+                    let p1 = if cmd.to_ascii_uppercase() == b'Q' {
+                        parser.point(&offset)?
+                    } else {
+                        reflect(last_quad_ctrl, &current)
+                    };
+                    let p2 = parser.point(&offset)?;
+                    flatten_quadratic(&current, &p1, &p2, flatten_tolerance, &mut nodes);
+                    quad_ctrl = Some(p1);
+                    current = p2;
+                },
+                b'A' => {
+                    let rx = parser.number()?;
+                    let ry = parser.number()?;
+                    let rotation = parser.number()?.to_radians();
+                    let large_arc = parser.flag()?;
+                    let sweep = parser.flag()?;
+                    let end = parser.point(&offset)?;
+                    arc_to(&current, rx, ry, rotation, large_arc, sweep, &end, flatten_tolerance, &mut nodes);
+                    current = end;
+                },
+                b'Z' => {
+                    finish_subpath(&mut polygons, &mut nodes, true);
+                    current = subpath_start;
+                    // no implicit repetition after closepath
+                    command = None;
+                },
+                _ => return None,
+            }
+
+            last_cubic_ctrl = cubic_ctrl;
+            last_quad_ctrl = quad_ctrl;
+        }
+
+        finish_subpath(&mut polygons, &mut nodes, false);
+
+        Some(polygons)
+    }
+}
+
+impl Polygon {
+
+    /// Formats the polygon as SVG path data (`M x y L x y ... Z`)
+    ///
+    /// Open polygons do not get a closing `Z`.
+    pub fn to_svg_path(&self) -> String {
+        let mut out = String::new();
+        write_svg_subpath(&mut out, self);
+        out
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Formats all polygons as one SVG path, with one subpath per polygon
+    ///
+    /// Holes are separate subpaths, so the path should be drawn with
+    /// `fill-rule="evenodd"`.
+    pub fn to_svg_path(&self) -> String {
+        let mut out = String::new();
+        for polygon in &self.polygons {
+            if !out.is_empty() && !polygon.nodes.is_empty() {
+                out.push(' ');
+            }
+            write_svg_subpath(&mut out, polygon);
+        }
+        out
+    }
+}
+
+fn write_svg_subpath(out: &mut String, polygon: &Polygon) {
+    for (idx, p) in polygon.nodes.iter().enumerate() {
+        let _ = write!(out, "{}{} {}", if idx == 0 { "M" } else { " L" }, p.x, p.y);
+    }
+    if polygon.is_closed && !polygon.nodes.is_empty() {
+        out.push_str(" Z");
+    }
+}
+
+/// Creates an SVG document that shows the subject, the clip polygons and the
+/// result of a boolean operation as three layers on top of each other
+///
+/// The subject is drawn in blue, the clip polygons in red and the result in green,
+/// all with `fill-rule="evenodd"`. The y axis points upwards, like in the
+/// coordinate system of the polygons. Useful for debugging incorrect results.
+pub fn svg_debug_document(subject: &[Polygon], clip: &[Polygon], result: &[Polygon]) -> String {
+
+    let mut out = String::new();
+    write_svg_header(&mut out, subject.iter().chain(clip.iter()).chain(result.iter()).flat_map(|p| p.nodes.iter()));
+
+    let layers = [
+        ("subject", subject, "#3366cc"),
+        ("clip", clip, "#cc3333"),
+        ("result", result, "#33aa33"),
+    ];
+
+    for &(name, polygons, color) in layers.iter() {
+        let path = MultiPolygon::new(polygons.to_vec()).to_svg_path();
+        let _ = writeln!(out, "<path id=\"{}\" d=\"{}\" fill=\"{}\" fill-opacity=\"0.3\" fill-rule=\"evenodd\" \
+                               stroke=\"{}\" stroke-width=\"1\" vector-effect=\"non-scaling-stroke\"/>",
+                         name, path, color, color);
+    }
+
+    write_svg_footer(&mut out);
+    out
+}
+
+/// Starts a debug document whose viewport contains all `nodes` (plus a margin),
+/// with the y axis pointing upwards. Returns the vertical extent of the viewport.
+pub(crate) fn write_svg_header<'a, I: Iterator<Item = &'a Point2D>>(out: &mut String, nodes: I) -> (fsize, fsize) {
+
+    let all_nodes: Vec<Point2D> = nodes.cloned().collect();
+
+    let (min_x, min_y, width, height) = if all_nodes.is_empty() {
+        (0.0, 0.0, 1.0, 1.0)
+    } else {
+        let bbox = ::utils::calculate_bounding_box(&all_nodes);
+        let margin = ((bbox.right - bbox.left).max(bbox.top - bbox.bottom) * 0.05).max(1.0);
+        (bbox.left - margin, bbox.bottom - margin,
+         bbox.right - bbox.left + 2.0 * margin, bbox.top - bbox.bottom + 2.0 * margin)
+    };
+
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    // flipping the y axis moves the viewport to (min_x, -(min_y + height))
+    let _ = writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+                     min_x, -(min_y + height), width, height);
+    out.push_str("<g transform=\"scale(1,-1)\">\n");
+
+    (min_y, min_y + height)
+}
+
+/// Closes a document started with `write_svg_header`
+pub(crate) fn write_svg_footer(out: &mut String) {
+    out.push_str("</g>\n</svg>\n");
+}
+
+/// Writes the document created by `svg_debug_document` to a file
+pub fn write_svg_debug<P: AsRef<Path>>(path: P, subject: &[Polygon], clip: &[Polygon], result: &[Polygon]) -> io::Result<()> {
+    use std::io::Write;
+    let mut file = ::std::fs::File::create(path)?;
+    file.write_all(svg_debug_document(subject, clip, result).as_bytes())
+}
+
+/// Reflects the last control point at the current point, or returns the
+/// current point if the previous command was not a curve of the same type
+#[inline]
+fn reflect(ctrl: Option<Point2D>, current: &Point2D) -> Point2D {
+    match ctrl {
+        Some(c) => Point2D { x: 2.0 * current.x - c.x, y: 2.0 * current.y - c.y },
+        None => *current,
+    }
+}
+
+fn finish_subpath(polygons: &mut Vec<Polygon>, nodes: &mut Vec<Point2D>, is_closed: bool) {
+    let mut subpath = ::std::mem::replace(nodes, Vec::new());
+
+    if subpath.len() > 1 && subpath.first() == subpath.last() {
+        subpath.pop();
+    }
+
+    // a lone moveto does not produce a contour
+    if subpath.len() < 2 {
+        return;
+    }
+
+    polygons.push(Polygon::from_parts(subpath, false, is_closed));
+}
+
+/// Converts an SVG arc from endpoint to center parameterization and flattens it
+/// (see the SVG specification, appendix F.6.5)
+fn arc_to(from: &Point2D, rx: fsize, ry: fsize, rotation: fsize, large_arc: bool, sweep: bool,
+          to: &Point2D, tolerance: fsize, out: &mut Vec<Point2D>)
+{
+    let mut rx = rx.abs();
+    let mut ry = ry.abs();
+
+    if from == to {
+        return;
+    }
+
+    if rx == 0.0 || ry == 0.0 {
+        out.push(*to);
+        return;
+    }
+
+    let (sin_rot, cos_rot) = rotation.sin_cos();
+    let dx = (from.x - to.x) / 2.0;
+    let dy = (from.y - to.y) / 2.0;
+    let x1 = cos_rot * dx + sin_rot * dy;
+    let y1 = -sin_rot * dx + cos_rot * dy;
+
+    // scale up radii that are too small to connect the endpoints
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coef = (num / den).max(0.0).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+
+    let cx1 = coef * rx * y1 / ry;
+    let cy1 = -coef * ry * x1 / rx;
+
+    let center = Point2D {
+        x: cos_rot * cx1 - sin_rot * cy1 + (from.x + to.x) / 2.0,
+        y: sin_rot * cx1 + cos_rot * cy1 + (from.y + to.y) / 2.0,
+    };
+
+    let start_angle = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let end_angle = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+    let two_pi = 2.0 * ::std::f64::consts::PI as fsize;
+
+    let mut delta = (end_angle - start_angle) % two_pi;
+    if !sweep && delta > 0.0 {
+        delta -= two_pi;
+    } else if sweep && delta < 0.0 {
+        delta += two_pi;
+    }
+
+    flatten_arc(&center, rx, ry, rotation, start_angle, delta, tolerance, out);
+
+    // avoid rounding errors at the end point
+    if let Some(last) = out.last_mut() {
+        *last = *to;
+    }
+}
+
+/// Tokenizer for SVG path data
+struct SvgPathParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SvgPathParser<'a> {
+
+    fn is_at_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn skip_separators(&mut self) {
+        while self.pos < self.input.len() && (self.input[self.pos] == b',' || (self.input[self.pos] as char).is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Reads a command letter, if the next token is one
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        match self.input.get(self.pos) {
+            Some(&c) if (c as char).is_ascii_alphabetic() && c != b'e' && c != b'E' => {
+                self.pos += 1;
+                Some(c)
+            },
+            _ => None,
+        }
+    }
+
+    /// Reads a number. SVG allows numbers without separators,
+    /// e.g. `1.5.5` is `1.5` followed by `.5` and `10-5` is `10` followed by `-5`
+    fn number(&mut self) -> Option<fsize> {
+        self.skip_separators();
+        let start = self.pos;
+        let input = self.input;
+
+        if self.pos < input.len() && (input[self.pos] == b'-' || input[self.pos] == b'+') {
+            self.pos += 1;
+        }
+
+        let mut seen_dot = false;
+        while self.pos < input.len() {
+            match input[self.pos] {
+                b'0'..=b'9' => self.pos += 1,
+                b'.' if !seen_dot => { seen_dot = true; self.pos += 1; },
+                _ => break,
+            }
+        }
+
+        // exponent, but not the start of a command
+        if self.pos < input.len() && (input[self.pos] == b'e' || input[self.pos] == b'E') {
+            let mut exp_end = self.pos + 1;
+            if exp_end < input.len() && (input[exp_end] == b'-' || input[exp_end] == b'+') {
+                exp_end += 1;
+            }
+            if exp_end < input.len() && (input[exp_end] as char).is_ascii_digit() {
+                self.pos = exp_end;
+                while self.pos < input.len() && (input[self.pos] as char).is_ascii_digit() {
+                    self.pos += 1;
+                }
+            }
+        }
+
+        ::std::str::from_utf8(&input[start..self.pos]).ok()?.parse().ok()
+    }
+
+    /// Reads an arc flag, which is a single `0` or `1` and may be
+    /// directly followed by the next number
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.input.get(self.pos) {
+            Some(&b'0') => false,
+            Some(&b'1') => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+
+    fn point(&mut self, offset: &Point2D) -> Option<Point2D> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Some(Point2D { x: x + offset.x, y: y + offset.y })
+    }
+}
+
+#[test]
+fn test_svg_path_subpaths() {
+    let polygons = Polygon::from_svg_path("M0,0 H10 V10 H0 Z m2 2 l6 0 0 6 -6 0z", 0.1).unwrap();
+    assert_eq!(polygons.len(), 2);
+    assert_eq!(polygons[0].nodes.len(), 4);
+    assert_eq!(polygons[1].nodes[2], Point2D { x: 8.0, y: 8.0 });
+    assert!(polygons[1].is_closed);
+
+    let polygons = Polygon::from_svg_path("M0 0 10 0 10 10z L 5 -5", 0.1).unwrap();
+    assert_eq!(polygons[1].nodes, vec![Point2D { x: 0.0, y: 0.0 }, Point2D { x: 5.0, y: -5.0 }]);
+    assert!(!polygons[1].is_closed);
+}
+
+#[test]
+fn test_svg_path_arc() {
+    // full circle of radius 5 made from two arcs
+    let polygons = Polygon::from_svg_path("M-5 0a5 5 0 1 0 10 0a5 5 0 1 0-10 0z", 0.01).unwrap();
+    assert_eq!(polygons.len(), 1);
+    for p in &polygons[0].nodes {
+        assert!((p.dist(&Point2D { x: 0.0, y: 0.0 }) - 5.0).abs() < 0.01);
+    }
+    assert!(polygons[0].nodes.len() > 20);
+}
+
+#[test]
+fn test_svg_path_export_roundtrip() {
+    let polygon = Polygon::from_svg_path("M0 0 L10 0 L10 10 Z", 0.1).unwrap().remove(0);
+    assert_eq!(polygon.to_svg_path(), "M0 0 L10 0 L10 10 Z");
+    assert!(svg_debug_document(&[polygon.clone()], &[], &[polygon]).contains("id=\"result\" d=\"M0 0"));
+}
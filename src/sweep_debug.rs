@@ -0,0 +1,190 @@
+//! Snapshots of the intersection sweep, for debugging the arrangement
+//!
+//! Every boolean operation of `ClipAlgorithm::Arrangement` (the default)
+//! starts by finding the points where the input edges meet with the sweep of
+//! `find_intersections`. The snapshots show its state after every event.
+//!
+//! There are no views of a Martinez sweep line, its active segments or the
+//! chains of its connector, as the crate has no Martinez sweep. The `active`
+//! segments of a snapshot are those of the intersection sweep, and as the
+//! arrangement traces its rings only after the sweep, a snapshot has no
+//! partial result chains either.
+
+use std::fmt::Write as FmtWrite;
+use std::io;
+use std::path::{Path, PathBuf};
+use polygon::Polygon;
+use multi_polygon::MultiPolygon;
+use options::OperationGuard;
+use segment::Segment;
+use edges::Edges;
+use Point2D;
+
+/// State of the intersection sweep after one event has been processed
+#[derive(Debug, Clone)]
+pub struct SweepSnapshot {
+    /// Number of the event, starting at 0
+    pub step: usize,
+    /// Point of the event, the sweep line is the vertical line through it
+    pub event: Point2D,
+    /// Segments that intersect the sweep line right after the event, ordered
+    /// by their y-coordinate
+    pub active: Vec<Segment>,
+    /// Segments that are completely left of the sweep line
+    pub processed: Vec<Segment>,
+    /// Crossings found so far: points where edges meet, other than the
+    /// vertices that consecutive edges of a ring share
+    pub intersections: Vec<Point2D>,
+}
+
+impl Polygon {
+
+    /// Runs the intersection sweep over the edges of both polygons and records
+    /// its state after every event, see `SweepSnapshot::to_svg`
+    ///
+    /// These are the same events that the arrangement processes for a boolean
+    /// operation of the two polygons, so an edge that is split at the wrong
+    /// point can be traced back to the step that caused it. Rings with less
+    /// than three nodes have no edges here, as in the arrangement.
+    pub fn sweep_snapshots(&self, other: &Self) -> Vec<SweepSnapshot> {
+        let segments: Vec<Segment> = [self, other].iter()
+            .filter(|polygon| polygon.nodes.len() > 2)
+            .flat_map(|polygon| Edges::new(&polygon.nodes, true).map(|(a, b)| Segment::new(a, b)))
+            .collect();
+        let mut snapshots = Vec::new();
+        let _ = ::intersections::find_intersections_observed(&segments, &mut OperationGuard::unlimited(),
+                                                             Some(&mut SnapshotRecorder::new(&mut snapshots)));
+        snapshots
+    }
+
+    /// Writes the snapshots of `sweep_snapshots` as SVG files to `directory`,
+    /// named `step-0000.svg`, `step-0001.svg` and so on
+    ///
+    /// Returns the paths of the written files.
+    pub fn write_sweep_snapshots<P: AsRef<Path>>(&self, other: &Self, directory: P) -> io::Result<Vec<PathBuf>> {
+        use std::io::Write;
+
+        let mut paths = Vec::new();
+        for snapshot in self.sweep_snapshots(other) {
+            let path = directory.as_ref().join(format!("step-{:04}.svg", snapshot.step));
+            let mut file = ::std::fs::File::create(&path)?;
+            file.write_all(snapshot.to_svg(self, other).as_bytes())?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+impl SweepSnapshot {
+
+    /// Draws the snapshot on top of the input polygons
+    ///
+    /// The subject is drawn in blue and the clip polygon in red (as in
+    /// `svg_debug_document`), processed segments in grey, active segments in
+    /// orange, the crossings found so far in green and the sweep line and the
+    /// event in black.
+    pub fn to_svg(&self, subject: &Polygon, clip: &Polygon) -> String {
+
+        let mut out = String::new();
+        let (min_y, max_y) = ::svg::write_svg_header(&mut out, subject.nodes.iter().chain(clip.nodes.iter()));
+
+        for &(polygon, color) in [(subject, "#3366cc"), (clip, "#cc3333")].iter() {
+            let path = MultiPolygon::new(vec![polygon.clone()]).to_svg_path();
+            let _ = writeln!(out, "<path d=\"{}\" fill=\"{}\" fill-opacity=\"0.15\" fill-rule=\"evenodd\" stroke=\"none\"/>",
+                             path, color);
+        }
+
+        write_segments(&mut out, "processed", &self.processed, "#999999");
+        write_segments(&mut out, "active", &self.active, "#ff8800");
+
+        let radius = (max_y - min_y) * 0.005;
+        for p in self.intersections.iter() {
+            let _ = writeln!(out, "<circle class=\"intersection\" cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#33aa33\"/>",
+                             p.x, p.y, radius);
+        }
+
+        let _ = writeln!(out, "<line class=\"sweep-line\" x1=\"{x}\" y1=\"{}\" x2=\"{x}\" y2=\"{}\" stroke=\"black\" \
+                               stroke-width=\"1\" vector-effect=\"non-scaling-stroke\"/>",
+                         min_y, max_y, x = self.event.x);
+        let _ = writeln!(out, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"black\"/>",
+                         self.event.x, self.event.y, radius);
+
+        ::svg::write_svg_footer(&mut out);
+        out
+    }
+}
+
+fn write_segments(out: &mut String, class: &str, segments: &[Segment], color: &str) {
+    for segment in segments {
+        let _ = writeln!(out, "<line class=\"{}\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"2\" \
+                               vector-effect=\"non-scaling-stroke\"/>",
+                         class, segment.a.x, segment.a.y, segment.b.x, segment.b.y, color);
+    }
+}
+
+/// Collects the snapshots while the sweep runs
+pub(crate) struct SnapshotRecorder<'a> {
+    snapshots: &'a mut Vec<SweepSnapshot>,
+    status: Vec<usize>,
+    processed: Vec<Segment>,
+    intersections: Vec<Point2D>,
+}
+
+impl<'a> SnapshotRecorder<'a> {
+
+    pub(crate) fn new(snapshots: &'a mut Vec<SweepSnapshot>) -> Self {
+        Self {
+            snapshots: snapshots,
+            status: Vec::new(),
+            processed: Vec::new(),
+            intersections: Vec::new(),
+        }
+    }
+
+    /// Records the state after the event at `p`, where the `involved` segments
+    /// meet. `status` holds the indices of the active segments, in order.
+    pub(crate) fn record(&mut self, p: Point2D, involved: &[usize], status: &[usize], segments: &[Segment]) {
+        for idx in self.status.iter().filter(|idx| !status.contains(idx)) {
+            self.processed.push(segments[*idx]);
+        }
+        self.status = status.to_vec();
+
+        let is_endpoint = |idx: &usize| segments[*idx].a == p || segments[*idx].b == p;
+        if involved.len() > 2 || (involved.len() == 2 && !involved.iter().all(is_endpoint)) {
+            self.intersections.push(p);
+        }
+
+        let step = self.snapshots.len();
+        self.snapshots.push(SweepSnapshot {
+            step: step,
+            event: p,
+            active: status.iter().map(|idx| segments[*idx]).collect(),
+            processed: self.processed.clone(),
+            intersections: self.intersections.clone(),
+        });
+    }
+}
+
+#[test]
+fn test_sweep_snapshots() {
+    let a = Polygon::rect(0.0, 0.0, 2.0, 2.0);
+    let b = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+
+    // eight vertices and two crossings
+    let snapshots = a.sweep_snapshots(&b);
+    assert_eq!(snapshots.len(), 10);
+    assert!(snapshots.iter().enumerate().all(|(idx, s)| s.step == idx));
+    assert!(snapshots.iter().all(|s| s.active.len() + s.processed.len() <= 8));
+    assert_eq!(snapshots[0].event, Point2D { x: 0.0, y: 0.0 });
+    assert_eq!(snapshots[0].active.len(), 2);
+
+    let last = snapshots.last().unwrap();
+    assert!(last.active.is_empty());
+    assert_eq!(last.processed.len(), 8);
+    assert_eq!(last.intersections, vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 2.0, y: 1.0 }]);
+
+    let svg = snapshots[0].to_svg(&a, &b);
+    assert!(svg.starts_with("<?xml"));
+    assert_eq!(svg.matches("class=\"sweep-line\"").count(), 1);
+    assert_eq!(last.to_svg(&a, &b).matches("class=\"intersection\"").count(), 2);
+}
@@ -0,0 +1,77 @@
+//! Generators for property tests with `proptest` (`testing` feature)
+//!
+//! `Point2D` and `Polygon` implement `proptest::arbitrary::Arbitrary`, so they
+//! can be used with `any::<Polygon>()` in the tests of downstream crates. The
+//! generated polygons are always simple (no self-intersections), which all
+//! of the algorithms support.
+
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::vec;
+use proptest::strategy::{BoxedStrategy, Strategy};
+use std::ops::Range;
+use generators::star_nodes;
+use polygon::Polygon;
+use {Point2D, fsize};
+
+/// Extent of the generated coordinates, see `Arbitrary for Point2D`
+const EXTENT: fsize = 1000.0;
+
+impl Arbitrary for Point2D {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Points with coordinates in [-1000, 1000)
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (-EXTENT..EXTENT, -EXTENT..EXTENT).prop_map(|(x, y)| Point2D { x: x, y: y }).boxed()
+    }
+}
+
+impl Arbitrary for Polygon {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Simple polygons with 3 to 31 vertices within [-1000, 1000), see `simple_polygon`
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        simple_polygon(3..32, EXTENT).boxed()
+    }
+}
+
+/// Strategy for counter-clockwise simple polygons with a number of vertices
+/// in the range `vertices` (at least 3), which lie within `extent` of the
+/// origin in both directions
+///
+/// The polygons are star-shaped: the vertices are placed at increasing angles
+/// and random distances around a random center, so the edges cannot cross.
+/// Their shape still varies from convex to deeply notched.
+pub fn simple_polygon(vertices: Range<usize>, extent: fsize) -> impl Strategy<Value = Polygon> {
+    let vertices = vertices.start.max(3)..vertices.end.max(4);
+    let center = (-extent / 2.0..extent / 2.0, -extent / 2.0..extent / 2.0);
+    let corners = vertices.prop_flat_map(|n| vec((50u32..100, 0.1 as fsize..1.0), n));
+
+    (center, corners).prop_map(move |((cx, cy), corners)| {
+        let corners: Vec<(u32, fsize)> = corners.into_iter().map(|(gap, radius)| (gap, radius * extent / 2.0)).collect();
+        Polygon::from_parts(star_nodes(Point2D { x: cx, y: cy }, &corners), false, true)
+    })
+}
+
+#[test]
+fn test_arbitrary_polygons() {
+    use proptest::arbitrary::any;
+    use proptest::test_runner::TestRunner;
+    use intersections::find_intersections;
+    use segment::Segment;
+
+    TestRunner::default().run(&any::<Polygon>(), |polygon| {
+        let n = polygon.nodes.len();
+        assert!(n >= 3 && n < 32);
+        assert!(::utils::calculate_signed_area(&polygon.nodes) > 0.0);
+
+        // the edges only meet at the vertices they share
+        let segments: Vec<Segment> = polygon.edges().map(|(a, b)| Segment::new(a, b)).collect();
+        for event in find_intersections(&segments) {
+            assert_eq!(event.segments.len(), 2);
+            assert!(polygon.nodes.contains(&event.point));
+        }
+        Ok(())
+    }).unwrap();
+}
@@ -0,0 +1,178 @@
+//! Clipping to slippy-map (XYZ) tiles, for vector tile encoders
+
+use grid::{bbox_of_rings, clip_rings_to_rect};
+use multi_polygon::MultiPolygon;
+use polygon::WindingOrder;
+use {Point2D, Bbox, fsize};
+
+/// Half of the width of the Web Mercator (EPSG:3857) world, in meters
+const MERCATOR_HALF_WORLD: f64 = 20037508.342789244;
+
+/// Address of an XYZ tile: `x` grows to the east, `y` grows to the south
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "RawTileId"))]
+pub struct TileId {
+    z: u8,
+    x: u32,
+    y: u32,
+}
+
+/// Unchecked fields of a deserialized `TileId`
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawTileId {
+    z: u8,
+    x: u32,
+    y: u32,
+}
+
+#[cfg(feature = "serde")]
+impl ::std::convert::TryFrom<RawTileId> for TileId {
+    type Error = &'static str;
+    fn try_from(raw: RawTileId) -> Result<Self, Self::Error> {
+        TileId::new(raw.z, raw.x, raw.y).ok_or("tile zoom or coordinates out of range")
+    }
+}
+
+impl TileId {
+
+    /// Highest zoom level, the tiles of zoom 30 are about 4 cm wide
+    pub const MAX_ZOOM: u8 = 30;
+
+    /// Returns the tile, or `None` if `z` is larger than `MAX_ZOOM` or `x` or
+    /// `y` are outside of the `2^z` tiles of the zoom level
+    pub fn new(z: u8, x: u32, y: u32) -> Option<Self> {
+        if z > Self::MAX_ZOOM || x >= 1 << z || y >= 1 << z {
+            return None;
+        }
+        Some(TileId { z: z, x: x, y: y })
+    }
+
+    /// Zoom level of the tile
+    pub fn z(&self) -> u8 {
+        self.z
+    }
+
+    /// Column of the tile, from west to east
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    /// Row of the tile, from north to south
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    /// Bounds of the tile in Web Mercator (EPSG:3857) meters
+    pub fn bounds(&self) -> Bbox {
+        let size = 2.0 * MERCATOR_HALF_WORLD / (1_u64 << self.z) as f64;
+        let min_x = -MERCATOR_HALF_WORLD + self.x as f64 * size;
+        let max_y = MERCATOR_HALF_WORLD - self.y as f64 * size;
+        Bbox::new(min_x as fsize, (max_y - size) as fsize, (min_x + size) as fsize, max_y as fsize)
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Clips the set (in Web Mercator meters) to a tile and converts it into
+    /// tile-local integer coordinates, as required by Mapbox Vector Tiles
+    ///
+    /// The tile covers `0..extent` in both directions (typically 4096), with the
+    /// y axis pointing down. The geometry is clipped `buffer` units outside of
+    /// the tile, so that lines at the tile borders are not visible when rendering.
+    /// Coordinates are rounded to whole numbers and the rings are oriented as
+    /// the MVT specification requires (exteriors with a positive area in tile
+    /// coordinates). Rings that collapse during rounding are removed.
+    pub fn clip_to_tile(&self, tile: TileId, extent: u32, buffer: u32) -> MultiPolygon {
+
+        let bounds = tile.bounds();
+        let scale = extent as fsize / bounds.width();
+        let margin = buffer as fsize / scale;
+
+        let rings: Vec<&[Point2D]> = self.polygons.iter().map(|p| &p.nodes[..]).collect();
+        let mut polygons: Vec<_> = clip_rings_to_rect(&rings, &bounds.expand(margin)).into_iter().filter_map(|polygon| {
+            let nodes: Vec<Point2D> = polygon.nodes.iter().map(|p| Point2D {
+                x: ((p.x - bounds.min().x) * scale).round(),
+                y: ((bounds.max().y - p.y) * scale).round(),
+            }).collect();
+            let polygon = polygon.with_nodes(nodes).clean(0.0);
+            if polygon.nodes.len() > 2 { Some(polygon) } else { None }
+        }).collect();
+
+        // flipping the y axis reverses every ring
+        ::orient::orient(&mut polygons, WindingOrder::CounterClockwise);
+        MultiPolygon::new(polygons)
+    }
+
+    /// Clips the set to every tile of the zoom level `z` that it overlaps,
+    /// see `clip_to_tile`. Tiles without any geometry are omitted. Returns
+    /// `None` if `z` is larger than `TileId::MAX_ZOOM`.
+    pub fn to_tiles(&self, z: u8, extent: u32, buffer: u32) -> Option<Vec<(TileId, MultiPolygon)>> {
+
+        if z > TileId::MAX_ZOOM {
+            return None;
+        }
+
+        let rings: Vec<&[Point2D]> = self.polygons.iter().map(|p| &p.nodes[..]).collect();
+        let bbox = match bbox_of_rings(&rings) {
+            Some(b) => b,
+            None => return Some(Vec::new()),
+        };
+
+        let tiles = 1_i64 << z;
+        let size = 2.0 * MERCATOR_HALF_WORLD / tiles as f64;
+        let index = |v: f64| (v / size).floor().max(0.0).min((tiles - 1) as f64) as u32;
+        let (min, max) = (bbox.min(), bbox.max());
+
+        let (min_x, max_x) = (index(min.x as f64 + MERCATOR_HALF_WORLD), index(max.x as f64 + MERCATOR_HALF_WORLD));
+        let (min_y, max_y) = (index(MERCATOR_HALF_WORLD - max.y as f64), index(MERCATOR_HALF_WORLD - min.y as f64));
+
+        let mut result = Vec::new();
+        for x in min_x..(max_x + 1) {
+            for y in min_y..(max_y + 1) {
+                let tile = TileId { z: z, x: x, y: y };
+                let clipped = self.clip_to_tile(tile, extent, buffer);
+                if !clipped.polygons.is_empty() {
+                    result.push((tile, clipped));
+                }
+            }
+        }
+        Some(result)
+    }
+}
+
+#[test]
+fn test_clip_to_tile() {
+    use polygon::Polygon;
+
+    // zoom 1: four tiles, the polygon covers the center of the world
+    let half = MERCATOR_HALF_WORLD as fsize;
+    let center = MultiPolygon::new(vec![Polygon::rect(-half / 2.0, -half / 2.0, half, half)]);
+
+    let tiles = center.to_tiles(1, 4096, 0).unwrap();
+    assert_eq!(tiles.len(), 4);
+
+    let (tile, ref clipped) = tiles[0];
+    assert_eq!(tile, TileId::new(1, 0, 0).unwrap());
+    assert_eq!(clipped.polygons.len(), 1);
+    assert_eq!(clipped.polygons[0].bounding_box(), Bbox::new(2048.0, 2048.0, 4096.0, 4096.0));
+    assert_eq!(clipped.polygons[0].winding, Some(WindingOrder::CounterClockwise));
+
+    // with a buffer, the geometry extends past the tile border
+    let buffered = center.clip_to_tile(TileId::new(1, 0, 0).unwrap(), 4096, 64);
+    assert_eq!(buffered.polygons[0].bounding_box(), Bbox::new(2048.0, 2048.0, 4160.0, 4160.0));
+}
+
+#[test]
+fn test_tile_zoom_out_of_range() {
+    use polygon::Polygon;
+
+    assert!(TileId::new(TileId::MAX_ZOOM, (1 << TileId::MAX_ZOOM) - 1, 0).is_some());
+    assert_eq!(TileId::new(TileId::MAX_ZOOM + 1, 0, 0), None);
+    assert_eq!(TileId::new(64, 0, 0), None);
+    assert_eq!(TileId::new(2, 4, 0), None);
+
+    let square = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 1.0, 1.0)]);
+    assert!(square.to_tiles(TileId::MAX_ZOOM + 1, 4096, 0).is_none());
+    assert!(square.to_tiles(255, 4096, 0).is_none());
+}
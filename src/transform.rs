@@ -0,0 +1,120 @@
+//! Affine transformations of polygons
+
+use polygon::{Polygon, WindingOrder};
+use {Point2D, Bbox, fsize};
+
+impl Polygon {
+
+    /// Applies the affine transformation `m` to all nodes and returns the new polygon
+    ///
+    /// The matrix is a row-major 2x3 matrix `[a, b, c, d, e, f]`:
+    ///
+    /// ```text
+    /// x' = a * x + b * y + c
+    /// y' = d * x + e * y + f
+    /// ```
+    ///
+    /// The cached bounding box is recalculated. If the transformation mirrors the
+    /// polygon, the cached winding order is flipped.
+    /// If the transformation is degenerate (collapses the polygon to a line), the
+    /// winding order is reset to `None`.
+    pub fn transform(&self, m: &[fsize; 6]) -> Self {
+        let mut new = self.clone();
+        new.transform_in_place(m);
+        new
+    }
+
+    /// In-place version of `transform`
+    pub fn transform_in_place(&mut self, m: &[fsize; 6]) {
+        for node in self.nodes.iter_mut() {
+            *node = transform_point(node, m);
+        }
+
+        self.bbox = self.bbox.and_then(|_| Bbox::from_points(&self.nodes));
+
+        let determinant = m[0] * m[4] - m[1] * m[3];
+        if determinant == 0.0 {
+            self.winding = None;
+        } else if determinant < 0.0 {
+            self.winding = self.winding.map(|w| match w {
+                WindingOrder::Clockwise => WindingOrder::CounterClockwise,
+                WindingOrder::CounterClockwise => WindingOrder::Clockwise,
+            });
+        }
+    }
+}
+
+impl Polygon {
+
+    /// Moves the polygon by (dx, dy)
+    pub fn translate(&self, dx: fsize, dy: fsize) -> Self {
+        self.transform(&[1.0, 0.0, dx, 0.0, 1.0, dy])
+    }
+
+    /// Rotates the polygon counter-clockwise by `angle` (in radians) around `center`
+    pub fn rotate_around(&self, center: &Point2D, angle: fsize) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        self.transform(&[
+            cos, -sin, center.x - cos * center.x + sin * center.y,
+            sin,  cos, center.y - sin * center.x - cos * center.y,
+        ])
+    }
+
+    /// Scales the polygon by (sx, sy), relative to the origin
+    ///
+    /// Negative factors mirror the polygon and flip the winding order.
+    pub fn scale(&self, sx: fsize, sy: fsize) -> Self {
+        self.transform(&[sx, 0.0, 0.0, 0.0, sy, 0.0])
+    }
+
+    /// Mirrors the polygon at the y axis (x' = -x) and flips the winding order
+    pub fn mirror_x(&self) -> Self {
+        self.scale(-1.0, 1.0)
+    }
+
+    /// Mirrors the polygon at the x axis (y' = -y) and flips the winding order
+    pub fn mirror_y(&self) -> Self {
+        self.scale(1.0, -1.0)
+    }
+}
+
+/// Applies the affine transformation `m` (see `Polygon::transform`) to a single point
+#[inline]
+pub fn transform_point(p: &Point2D, m: &[fsize; 6]) -> Point2D {
+    Point2D {
+        x: m[0] * p.x + m[1] * p.y + m[2],
+        y: m[3] * p.x + m[4] * p.y + m[5],
+    }
+}
+
+#[test]
+fn test_transform_mirror_flips_winding() {
+    let poly = Polygon {
+        nodes: vec![
+            Point2D { x: 0.0, y: 0.0 },
+            Point2D { x: 1.0, y: 0.0 },
+            Point2D { x: 0.0, y: 1.0 },
+        ],
+        winding: Some(WindingOrder::CounterClockwise),
+        .. Default::default()
+    };
+    let mirrored = poly.transform(&[-1.0, 0.0, 5.0, 0.0, 1.0, 0.0]);
+    assert_eq!(mirrored.nodes[1], Point2D { x: 4.0, y: 0.0 });
+    assert_eq!(mirrored.winding, Some(WindingOrder::Clockwise));
+    assert_eq!(::utils::calculate_winding_order(&mirrored.nodes), WindingOrder::Clockwise);
+}
+
+#[test]
+fn test_rotate_around() {
+    let poly = Polygon {
+        nodes: vec![
+            Point2D { x: 2.0, y: 1.0 },
+            Point2D { x: 3.0, y: 1.0 },
+            Point2D { x: 2.0, y: 2.0 },
+        ],
+        .. Default::default()
+    };
+    let rotated = poly.rotate_around(&Point2D { x: 2.0, y: 1.0 }, ::std::f64::consts::FRAC_PI_2 as fsize);
+    assert!(rotated.nodes[0].dist(&Point2D { x: 2.0, y: 1.0 }) < 0.0001);
+    assert!(rotated.nodes[1].dist(&Point2D { x: 2.0, y: 2.0 }) < 0.0001);
+}
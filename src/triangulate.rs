@@ -0,0 +1,550 @@
+//! Triangulation of polygons with holes by ear clipping
+//!
+//! Every hole is joined to its exterior by a bridge edge (twice, once in each
+//! direction) from the rightmost vertex of the hole to a visible vertex of the
+//! exterior, which turns the polygon into a single ring. Ears, i.e. convex
+//! vertices whose triangle contains no other vertex, are then cut off until
+//! only one triangle is left. The runtime is quadratic in the number of
+//! vertices, which is fine for the polygons that are usually rendered.
+//!
+//! `TessellationOptions` post-process the mesh: large triangles are split at
+//! the midpoint of their longest edge (together with the neighbor across that
+//! edge, so that no T-junctions are created), and edges can be flipped until
+//! the mesh is a Delaunay triangulation of its boundary.
+
+use std::collections::HashMap;
+use multi_polygon::MultiPolygon;
+use polygon::Polygon;
+use utils::{calculate_sign, Sign};
+use {Point2D, fsize};
+
+/// Options that control the mesh of `triangulate_with_options`
+///
+/// The default options create the plain ear clipping triangulation, like
+/// `triangulate_indexed`.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct TessellationOptions {
+    /// Convex rings without holes are triangulated as a fan: all triangles
+    /// share the first vertex of the ring and are emitted in order, so they
+    /// can be drawn as a triangle fan. Splitting or flipping edges changes
+    /// the triangles of the fan afterwards. Default: false
+    pub convex_fans: bool,
+    /// Triangles with a larger area are split by inserting the midpoint of
+    /// their longest edge as a new vertex (a Steiner point), until all
+    /// triangles are small enough. Default: 0.0 (no limit)
+    pub max_triangle_area: fsize,
+    /// Flips the diagonal of two neighboring triangles if the opposite
+    /// vertex lies inside the circumcircle of a triangle, which avoids thin
+    /// triangles. Boundary edges of the polygons are never flipped, neither
+    /// are edges whose flip would exceed `max_triangle_area`. Default: false
+    pub flip_edges: bool,
+}
+
+impl Polygon {
+
+    /// Triangulates the polygon and returns the counter-clockwise triangles
+    ///
+    /// The polygon is treated as closed and simple. `is_hole` is ignored, to
+    /// triangulate a polygon with holes use `MultiPolygon::triangulate`.
+    pub fn triangulate(&self) -> Vec<[Point2D; 3]> {
+        let (vertices, indices) = self.triangulate_indexed();
+        resolve(&vertices, &indices)
+    }
+
+    /// Same as `triangulate`, but returns a vertex buffer without duplicate
+    /// points and the triangles as indices into it, ready to be uploaded to the GPU
+    pub fn triangulate_indexed(&self) -> (Vec<Point2D>, Vec<[u32; 3]>) {
+        self.triangulate_with_options(&TessellationOptions::default())
+    }
+
+    /// Same as `triangulate_indexed`, with control over the mesh
+    pub fn triangulate_with_options(&self, options: &TessellationOptions) -> (Vec<Point2D>, Vec<[u32; 3]>) {
+        let mut mesh = Mesh::default();
+        mesh.add(self, &[], options.convex_fans);
+        mesh.finish(options)
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Triangulates the polygons and returns the counter-clockwise triangles
+    ///
+    /// Holes are assigned to the exteriors that contain them (see `is_hole`).
+    pub fn triangulate(&self) -> Vec<[Point2D; 3]> {
+        let (vertices, indices) = self.triangulate_indexed();
+        resolve(&vertices, &indices)
+    }
+
+    /// Same as `triangulate`, but returns a vertex buffer without duplicate
+    /// points and the triangles as indices into it. Polygons that share a
+    /// vertex also share its index, so the mesh is connected where the
+    /// polygons touch.
+    pub fn triangulate_indexed(&self) -> (Vec<Point2D>, Vec<[u32; 3]>) {
+        self.triangulate_with_options(&TessellationOptions::default())
+    }
+
+    /// Same as `triangulate_indexed`, with control over the mesh. Split
+    /// edges and flips also apply across the edges that touching polygons share.
+    pub fn triangulate_with_options(&self, options: &TessellationOptions) -> (Vec<Point2D>, Vec<[u32; 3]>) {
+        let mut mesh = Mesh::default();
+        for (exterior, holes) in self.exteriors_with_holes() {
+            mesh.add(exterior, &holes, options.convex_fans);
+        }
+        mesh.finish(options)
+    }
+}
+
+/// Triangulates a polygon with holes given in the layout of earcut
+///
+/// `data` holds the coordinates of all vertices, `dim` values per vertex of
+/// which only the first two are used. The exterior starts at vertex 0 and
+/// every hole at the vertex in `hole_indices`. Like earcut, the result are
+/// the indices of the input vertices, three per triangle, so that existing
+/// vertex buffers can be used as they are. The triangles are counter-clockwise.
+/// With less than two coordinates per vertex there is nothing to triangulate
+/// and the result is empty, as in earcut.
+pub fn earcut(data: &[fsize], hole_indices: &[usize], dim: usize) -> Vec<usize> {
+    if dim < 2 {
+        return Vec::new();
+    }
+    let points: Vec<Point2D> = data.chunks(dim)
+        .filter(|c| c.len() == dim)
+        .map(|c| Point2D { x: c[0], y: c[1] })
+        .collect();
+
+    let mut starts = vec![0];
+    starts.extend(hole_indices.iter().map(|&i| i.min(points.len())));
+    starts.push(points.len());
+    let mut rings = starts.windows(2).map(|w| Polygon::new(points[w[0]..w[1].max(w[0])].to_vec()));
+    let exterior = match rings.next() {
+        Some(exterior) => exterior,
+        None => return Vec::new(),
+    };
+    let holes: Vec<Polygon> = rings.collect();
+
+    let mut mesh = Mesh::default();
+    mesh.add(&exterior, &holes.iter().collect::<Vec<_>>(), false);
+
+    // the mesh has no duplicate vertices, so a point that occurs several
+    // times in the input is mapped back to its first occurrence
+    let mut first = HashMap::new();
+    for (i, p) in points.iter().enumerate() {
+        first.entry(*p).or_insert(i);
+    }
+    mesh.triangles.iter()
+        .flat_map(|t| t.iter().map(|&i| first[&mesh.vertices[i as usize]]).collect::<Vec<_>>())
+        .collect()
+}
+
+fn resolve(vertices: &[Point2D], indices: &[[u32; 3]]) -> Vec<[Point2D; 3]> {
+    indices.iter()
+        .map(|t| [vertices[t[0] as usize], vertices[t[1] as usize], vertices[t[2] as usize]])
+        .collect()
+}
+
+/// Vertex and index buffer that the triangles of several polygons are added to
+#[derive(Default)]
+struct Mesh {
+    vertices: Vec<Point2D>,
+    triangles: Vec<[u32; 3]>,
+    indices: HashMap<Point2D, u32>,
+}
+
+impl Mesh {
+
+    fn index(&mut self, p: Point2D) -> u32 {
+        let vertices = &mut self.vertices;
+        *self.indices.entry(p).or_insert_with(|| {
+            vertices.push(p);
+            (vertices.len() - 1) as u32
+        })
+    }
+
+    fn add(&mut self, exterior: &Polygon, holes: &[&Polygon], convex_fans: bool) {
+        let mut ring = oriented(&exterior.nodes, true);
+        if ring.len() < 3 {
+            return;
+        }
+
+        // holes further right first, so that the bridges of the holes
+        // further left can pass by the holes that are already joined
+        let mut holes: Vec<Vec<Point2D>> = holes.iter()
+            .map(|h| oriented(&h.nodes, false))
+            .filter(|h| h.len() >= 3)
+            .collect();
+
+        if convex_fans && holes.is_empty() && (0..ring.len()).all(|k| !is_reflex(&ring, k)) {
+            let indices: Vec<u32> = ring.iter().map(|&p| self.index(p)).collect();
+            for k in 1..indices.len() - 1 {
+                self.triangles.push([indices[0], indices[k], indices[k + 1]]);
+            }
+            return;
+        }
+
+        holes.sort_by_key(|h| ::std::cmp::Reverse(rightmost(h).1));
+        for hole in holes {
+            bridge(&mut ring, &hole);
+        }
+
+        for [a, b, c] in clip_ears(ring) {
+            let triangle = [self.index(a), self.index(b), self.index(c)];
+            self.triangles.push(triangle);
+        }
+    }
+
+    fn finish(mut self, options: &TessellationOptions) -> (Vec<Point2D>, Vec<[u32; 3]>) {
+        if options.max_triangle_area > 0.0 {
+            refine(&mut self.vertices, &mut self.triangles, options.max_triangle_area);
+        }
+        if options.flip_edges {
+            flip_edges(&self.vertices, &mut self.triangles, options.max_triangle_area);
+        }
+        (self.vertices, self.triangles)
+    }
+}
+
+/// Maps every directed edge of the triangles to the triangle it belongs to.
+/// Since all triangles are counter-clockwise, the neighbor across the edge
+/// (a, b) is the triangle of (b, a).
+type EdgeMap = HashMap<(u32, u32), usize>;
+
+fn edge_map(triangles: &[[u32; 3]]) -> EdgeMap {
+    let mut edges = EdgeMap::new();
+    for (k, t) in triangles.iter().enumerate() {
+        insert_edges(&mut edges, t, k);
+    }
+    edges
+}
+
+fn insert_edges(edges: &mut EdgeMap, t: &[u32; 3], k: usize) {
+    for i in 0..3 {
+        edges.insert((t[i], t[(i + 1) % 3]), k);
+    }
+}
+
+fn remove_edges(edges: &mut EdgeMap, t: &[u32; 3]) {
+    for i in 0..3 {
+        edges.remove(&(t[i], t[(i + 1) % 3]));
+    }
+}
+
+/// Rotates the triangle so that it starts with the directed edge (a, b)
+fn starting_with(t: [u32; 3], a: u32) -> [u32; 3] {
+    let i = t.iter().position(|&v| v == a).unwrap();
+    [t[i], t[(i + 1) % 3], t[(i + 2) % 3]]
+}
+
+fn triangle_area(vertices: &[Point2D], t: &[u32; 3]) -> fsize {
+    let p = |i: u32| vertices[i as usize];
+    ::utils::calculate_signed_area3(&p(t[0]), &p(t[1]), &p(t[2])) / 2.0
+}
+
+/// Splits the triangles that are larger than `max_area` at the midpoint of
+/// their longest edge, the neighbor across the edge is split at the same point
+fn refine(vertices: &mut Vec<Point2D>, triangles: &mut Vec<[u32; 3]>, max_area: fsize) {
+    let mut edges = edge_map(triangles);
+    let mut k = 0;
+
+    while k < triangles.len() {
+        let t = triangles[k];
+        let length = |i: usize| vertices[t[i] as usize].dist(&vertices[t[(i + 1) % 3] as usize]);
+        let longest = (0..3).max_by(|&i, &j| length(i).partial_cmp(&length(j)).unwrap()).unwrap();
+        let [a, b, _] = starting_with(t, t[longest]);
+
+        let (pa, pb) = (vertices[a as usize], vertices[b as usize]);
+        let m = pa.lerp(&pb, 0.5);
+        // the midpoint of very short edges can not be represented
+        if triangle_area(vertices, &t) <= max_area || m == pa || m == pb {
+            k += 1;
+            continue;
+        }
+
+        vertices.push(m);
+        let m = (vertices.len() - 1) as u32;
+        let neighbor = edges.get(&(b, a)).cloned();
+        split(triangles, &mut edges, k, a, m);
+        if let Some(j) = neighbor {
+            split(triangles, &mut edges, j, b, m);
+        }
+    }
+}
+
+/// Splits the triangle `k` into two by connecting `m`, the midpoint of the
+/// edge that starts at `a`, with the opposite vertex
+fn split(triangles: &mut Vec<[u32; 3]>, edges: &mut EdgeMap, k: usize, a: u32, m: u32) {
+    let [a, b, c] = starting_with(triangles[k], a);
+    remove_edges(edges, &[a, b, c]);
+    triangles[k] = [a, m, c];
+    triangles.push([m, b, c]);
+    insert_edges(edges, &triangles[k], k);
+    insert_edges(edges, &[m, b, c], triangles.len() - 1);
+}
+
+/// Whether `d` lies strictly inside of the circumcircle of the
+/// counter-clockwise triangle (a, b, c)
+fn in_circumcircle(a: &Point2D, b: &Point2D, c: &Point2D, d: &Point2D) -> bool {
+    let (ax, ay) = ((a.x - d.x) as f64, (a.y - d.y) as f64);
+    let (bx, by) = ((b.x - d.x) as f64, (b.y - d.y) as f64);
+    let (cx, cy) = ((c.x - d.x) as f64, (c.y - d.y) as f64);
+    let det = (ax * ax + ay * ay) * (bx * cy - cx * by)
+            - (bx * bx + by * by) * (ax * cy - cx * ay)
+            + (cx * cx + cy * cy) * (ax * by - bx * ay);
+    det > 0.0
+}
+
+/// Flips edges until no vertex lies in the circumcircle of a neighboring
+/// triangle. Edges with only one triangle are on the boundary and stay, and
+/// flips that create a triangle larger than `max_area` (if not 0.0) are skipped.
+fn flip_edges(vertices: &[Point2D], triangles: &mut [[u32; 3]], max_area: fsize) {
+    let p = |i: u32| &vertices[i as usize];
+    let mut edges = edge_map(triangles);
+
+    // every pass flips at least one edge, the limit only protects against
+    // rounding errors that make two flips undo each other
+    for _ in 0..triangles.len() {
+        let mut flipped = false;
+        for k in 0..triangles.len() {
+            for i in 0..3 {
+                let t = triangles[k];
+                let (a, b, c) = (t[i], t[(i + 1) % 3], t[(i + 2) % 3]);
+                let j = match edges.get(&(b, a)) {
+                    Some(&j) if j != k => j,
+                    _ => continue,
+                };
+                let [_, _, d] = starting_with(triangles[j], b);
+                if d == c || !in_circumcircle(p(a), p(b), p(c), p(d)) ||
+                   calculate_sign(p(c), p(a), p(d)) != Sign::Positive ||
+                   calculate_sign(p(d), p(b), p(c)) != Sign::Positive {
+                    continue;
+                }
+                let too_large = |t: &[u32; 3]| max_area > 0.0 && triangle_area(vertices, t) > max_area;
+                if too_large(&[c, a, d]) || too_large(&[d, b, c]) {
+                    continue;
+                }
+
+                remove_edges(&mut edges, &[a, b, c]);
+                remove_edges(&mut edges, &[b, a, d]);
+                triangles[k] = [c, a, d];
+                triangles[j] = [d, b, c];
+                insert_edges(&mut edges, &triangles[k], k);
+                insert_edges(&mut edges, &triangles[j], j);
+                flipped = true;
+            }
+        }
+        if !flipped {
+            break;
+        }
+    }
+}
+
+/// Removes duplicate and collinear points and orients the ring
+/// counter-clockwise (or clockwise if `ccw` is false)
+fn oriented(nodes: &[Point2D], ccw: bool) -> Vec<Point2D> {
+    let mut ring = ::clean::clean_nodes(nodes, true, 0.0);
+    if (::utils::calculate_signed_area(&ring) > 0.0) != ccw {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Index and value of the largest vertex in the order of `Point2D`, i.e. the
+/// vertex with the largest x coordinate and, of those, the largest y
+fn rightmost(ring: &[Point2D]) -> (usize, Point2D) {
+    let (i, p) = ring.iter().enumerate().max_by(|a, b| a.1.cmp(b.1)).unwrap();
+    (i, *p)
+}
+
+/// Joins the clockwise `hole` to the counter-clockwise `ring`
+///
+/// A ray from the rightmost vertex `m` of the hole in the direction of +x
+/// hits the ring at the point `i` on the edge ending in `p`. If no reflex
+/// vertex of the ring lies in the triangle (m, i, p), `p` is visible from
+/// `m`, otherwise the reflex vertex with the smallest angle to the ray is.
+fn bridge(ring: &mut Vec<Point2D>, hole: &[Point2D]) {
+    let (m_index, m) = rightmost(hole);
+    let n = ring.len();
+
+    let mut hit: Option<(fsize, usize)> = None;
+    for k in 0..n {
+        let (a, b) = (ring[k], ring[(k + 1) % n]);
+        // the ring is counter-clockwise, so the ray leaves the polygon
+        // through an edge that goes upwards
+        if !(a.y <= m.y && m.y <= b.y) || a.y == b.y {
+            continue;
+        }
+        let x = a.x + (m.y - a.y) / (b.y - a.y) * (b.x - a.x);
+        if x < m.x || hit.map_or(false, |(best, _)| x >= best) {
+            continue;
+        }
+        let p = if a.x > b.x { k } else { (k + 1) % n };
+        hit = Some((x, p));
+    }
+    let (x, mut p) = match hit {
+        Some(hit) => hit,
+        // the hole is not inside of the ring, the triangles cover the ring only
+        None => return,
+    };
+
+    let i = Point2D { x: x, y: m.y };
+    if ring[p] != i {
+        let best = |k: usize| {
+            let d = ring[k] - m;
+            (d.y.abs() / d.x, d.x)
+        };
+        let triangle = if ring[p].y < m.y { [m, ring[p], i] } else { [m, i, ring[p]] };
+        let candidates: Vec<usize> = (0..n).filter(|&k| {
+            k != p && ring[k].x > m.x && is_reflex(ring, k) && in_triangle(&triangle, &ring[k])
+        }).collect();
+        for k in candidates {
+            if best(k) < best(p) {
+                p = k;
+            }
+        }
+    }
+
+    let joined: Vec<Point2D> = ring[..=p].iter()
+        .chain(hole[m_index..].iter())
+        .chain(hole[..=m_index].iter())
+        .chain(ring[p..].iter())
+        .cloned()
+        .collect();
+    *ring = joined;
+}
+
+fn is_reflex(ring: &[Point2D], k: usize) -> bool {
+    let n = ring.len();
+    calculate_sign(&ring[(k + n - 1) % n], &ring[k], &ring[(k + 1) % n]) == Sign::Negative
+}
+
+/// Whether `p` lies inside of the counter-clockwise triangle or on its boundary
+fn in_triangle(t: &[Point2D; 3], p: &Point2D) -> bool {
+    calculate_sign(&t[0], &t[1], p) != Sign::Negative &&
+    calculate_sign(&t[1], &t[2], p) != Sign::Negative &&
+    calculate_sign(&t[2], &t[0], p) != Sign::Negative
+}
+
+/// Cuts off ears of the counter-clockwise ring until it is used up
+fn clip_ears(mut ring: Vec<Point2D>) -> Vec<[Point2D; 3]> {
+    let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2));
+    let mut k = 0;
+    let mut stalled = 0;
+
+    while ring.len() >= 3 {
+        let n = ring.len();
+        let (a, b, c) = (ring[(k + n - 1) % n], ring[k % n], ring[(k + 1) % n]);
+        let sign = calculate_sign(&a, &b, &c);
+
+        let is_ear = match sign {
+            // collinear vertices and spikes are removed without a triangle
+            Sign::Equal => true,
+            Sign::Negative => false,
+            Sign::Positive => !ring.iter().any(|p| *p != a && *p != b && *p != c && in_triangle(&[a, b, c], p)),
+        };
+
+        // rings that self-intersect have no ear left at some point, in that
+        // case the next convex vertex is cut off, so that the loop terminates
+        if is_ear || (stalled >= n && sign == Sign::Positive) || stalled >= 2 * n {
+            if sign == Sign::Positive {
+                triangles.push([a, b, c]);
+            }
+            let removed = k % n;
+            ring.remove(removed);
+            k = (removed + n - 2) % (n - 1);
+            stalled = 0;
+        } else {
+            k = (k + 1) % n;
+            stalled += 1;
+        }
+    }
+
+    triangles
+}
+
+#[test]
+fn test_triangulate() {
+    let area = |triangles: &[[Point2D; 3]]| -> fsize {
+        triangles.iter().map(|t| ::utils::calculate_signed_area3(&t[0], &t[1], &t[2]) / 2.0).sum()
+    };
+
+    // a concave polygon
+    let l_shape = Polygon::from(vec![(0.0, 0.0), (4.0, 0.0), (4.0, 1.0), (1.0, 1.0), (1.0, 4.0), (0.0, 4.0)]);
+    let triangles = l_shape.triangulate();
+    assert_eq!(triangles.len(), 4);
+    assert_eq!(area(&triangles), 7.0);
+
+    // a square with two holes, given clockwise
+    let mut hole_a = Polygon::rect(1.0, 1.0, 2.0, 2.0);
+    let mut hole_b = Polygon::rect(5.0, 4.0, 2.0, 2.0);
+    hole_a.is_hole = true;
+    hole_b.is_hole = true;
+    let mut exterior = Polygon::rect(0.0, 0.0, 8.0, 8.0);
+    exterior.nodes.reverse();
+    let multi = MultiPolygon::new(vec![exterior, hole_a, hole_b]);
+
+    let (vertices, indices) = multi.triangulate_indexed();
+    assert_eq!(vertices.len(), 12);
+    // a polygon with h holes and n vertices has n + 2h - 2 triangles
+    assert_eq!(indices.len(), 12 + 2 * 2 - 2);
+    assert!(indices.iter().all(|t| t.iter().all(|&i| (i as usize) < vertices.len())));
+    let triangles = multi.triangulate();
+    assert_eq!(area(&triangles), 64.0 - 8.0);
+    assert!(triangles.iter().all(|t| calculate_sign(&t[0], &t[1], &t[2]) == Sign::Positive));
+
+    // touching squares share the vertices on the common edge
+    let squares = MultiPolygon::new(vec![Polygon::rect(0.0, 0.0, 1.0, 1.0), Polygon::rect(1.0, 0.0, 1.0, 1.0)]);
+    let (vertices, indices) = squares.triangulate_indexed();
+    assert_eq!((vertices.len(), indices.len()), (6, 4));
+}
+
+#[test]
+fn test_tessellation_options() {
+    // a convex hexagon as a fan
+    let hexagon = Polygon::regular(6, Point2D { x: 0.0, y: 0.0 }, 1.0);
+    let options = TessellationOptions { convex_fans: true, .. Default::default() };
+    let (_, indices) = hexagon.triangulate_with_options(&options);
+    assert_eq!(indices, vec![[0, 1, 2], [0, 2, 3], [0, 3, 4], [0, 4, 5]]);
+
+    // splitting does not leave T-junctions: every inner edge has a neighbor,
+    // so the boundary edges add up to the perimeter
+    let square = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+    let options = TessellationOptions { max_triangle_area: 0.5, .. Default::default() };
+    let (vertices, indices) = square.triangulate_with_options(&options);
+    assert!(indices.iter().all(|t| triangle_area(&vertices, t) <= 0.5));
+    assert_eq!(indices.iter().map(|t| triangle_area(&vertices, t)).sum::<fsize>(), 16.0);
+    let edges = edge_map(&indices);
+    let boundary: fsize = edges.keys()
+        .filter(|&&(a, b)| !edges.contains_key(&(b, a)))
+        .map(|&(a, b)| vertices[a as usize].dist(&vertices[b as usize]))
+        .sum();
+    assert_eq!(boundary, 16.0);
+
+    // ear clipping cuts the first vertex of the kite off, which leaves the
+    // long diagonal, the flip replaces it with the short one
+    let kite = Polygon::from(vec![(5.0, -1.0), (10.0, 0.0), (5.0, 1.0), (0.0, 0.0)]);
+    let has_long_diagonal = |options: &TessellationOptions| {
+        let (vertices, indices) = kite.triangulate_with_options(options);
+        indices.iter().any(|t| t.iter().filter(|&&i| vertices[i as usize].y == 0.0).count() == 2)
+    };
+    assert!(has_long_diagonal(&TessellationOptions::default()));
+    assert!(!has_long_diagonal(&TessellationOptions { flip_edges: true, .. Default::default() }));
+}
+
+#[test]
+fn test_earcut() {
+    // a square with a square hole and a third coordinate
+    let data = [
+        0.0, 0.0, 9.0,  4.0, 0.0, 9.0,  4.0, 4.0, 9.0,  0.0, 4.0, 9.0,
+        1.0, 1.0, 9.0,  1.0, 3.0, 9.0,  3.0, 3.0, 9.0,  3.0, 1.0, 9.0,
+    ];
+    let indices = earcut(&data, &[4], 3);
+    assert_eq!(indices.len(), 8 * 3);
+    assert!(indices.iter().all(|&i| i < 8));
+
+    let p = |i: usize| Point2D { x: data[i * 3], y: data[i * 3 + 1] };
+    let area: fsize = indices.chunks(3).map(|t| ::utils::calculate_signed_area3(&p(t[0]), &p(t[1]), &p(t[2])) / 2.0).sum();
+    assert_eq!(area, 12.0);
+
+    assert!(earcut(&[], &[], 2).is_empty());
+    assert!(earcut(&data, &[4], 1).is_empty());
+    assert!(earcut(&data, &[4], 0).is_empty());
+}
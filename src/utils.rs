@@ -1,6 +1,51 @@
 use segment::Segment;
+use edges::Edges;
 use {Point2D, Bbox, fsize};
-use polygon::WindingOrder;
+use polygon::{Polygon, WindingOrder};
+use error::ClipError;
+use kernel::{Kernel, DefaultKernel};
+use std::cmp::Ordering;
+
+/// Replaces every coordinate by the index of the nearest multiple of
+/// `grid_size`, i.e. `(x / grid_size).round()`, so all coordinates become
+/// integers. Consecutive vertices that fall onto the same grid point are
+/// merged.
+///
+/// Running the boolean operations on quantized coordinates is more robust,
+/// because nearly coincident vertices and edges become exactly coincident.
+/// `dequantize` with the same `grid_size` converts the result back, running
+/// both directly after each other snaps the polygons to the grid. Note that
+/// `f32` only represents integers up to 2^24 exactly.
+pub fn quantize(polygons: &mut [Polygon], grid_size: fsize) {
+    for polygon in polygons.iter_mut() {
+        let mut nodes: Vec<Point2D> = polygon.nodes.iter()
+            .map(|p| Point2D { x: (p.x / grid_size).round(), y: (p.y / grid_size).round() })
+            .collect();
+        nodes.dedup();
+        while polygon.is_closed && nodes.len() > 1 && nodes.first() == nodes.last() {
+            nodes.pop();
+        }
+        *polygon = polygon.with_nodes(nodes);
+    }
+}
+
+/// Converts coordinates created by `quantize` back, by multiplying them with
+/// `grid_size`
+pub fn dequantize(polygons: &mut [Polygon], grid_size: fsize) {
+    for polygon in polygons.iter_mut() {
+        let nodes = polygon.nodes.iter().map(|p| Point2D { x: p.x * grid_size, y: p.y * grid_size }).collect();
+        *polygon = polygon.with_nodes(nodes);
+    }
+}
+
+/// Returns an error with the index of the first point that has a NaN or
+/// infinite coordinate. Such points break every comparison in the sweep.
+pub(crate) fn check_finite<'a, I>(points: I) -> Result<(), ClipError> where I: Iterator<Item = &'a Point2D> {
+    match points.enumerate().find(|&(_, p)| !(p.x.is_finite() && p.y.is_finite())) {
+        Some((index, _)) => Err(ClipError::NonFiniteCoordinate { index: index }),
+        None => Ok(()),
+    }
+}
 
 /// Calculate the signed area of a triangle (p0, p1, p2)
 #[inline]
@@ -22,25 +67,190 @@ pub(crate) enum Sign {
     Equal,
 }
 
-/// Calculate the sign of the triangle (p1, p2, o)
+/// Calculate the sign of the triangle (p1, p2, o), using the orientation test
+/// of `DefaultKernel` (see the `adaptive_precision` and `exact_predicates`
+/// features)
 #[inline]
 pub(crate) fn calculate_sign(p0: &Point2D, p1: &Point2D, o: &Point2D) -> Sign {
-    let det = (p0.x - o.x) * (p1.y - o.y) - (p1.x - o.x) * (p0.y - o.y);
-    if det < 0.0 {
-        Sign::Negative
-    } else if det > 0.0 {
-        Sign::Positive
-    } else {
-        Sign::Equal
+    Sign::from(DefaultKernel::orient2d(p0, p1, o))
+}
+
+impl From<Ordering> for Sign {
+    fn from(ordering: Ordering) -> Self {
+        match ordering {
+            Ordering::Greater => Sign::Positive,
+            Ordering::Less => Sign::Negative,
+            Ordering::Equal => Sign::Equal,
+        }
     }
 }
 
 /// Check if a point is inside a triangle
 #[inline]
 pub(crate) fn is_point_in_triangle(s: &Segment, o: &Point2D, p: &Point2D) -> bool {
-    let sign_first = calculate_sign(&s.begin_pt, &s.end_pt, p);
-    (sign_first == calculate_sign(&s.end_pt, o, p)) &&
-    (sign_first == calculate_sign(o, &s.begin_pt, p))
+    let sign_first = calculate_sign(&s.a, &s.b, p);
+    (sign_first == calculate_sign(&s.b, o, p)) &&
+    (sign_first == calculate_sign(o, &s.a, p))
+}
+
+/// Check if the two segments (p0, p1) and (p2, p3) intersect or touch
+///
+/// Unlike `line_intersect`, this only answers the question and does not
+/// compute the intersection point, so it is exact for collinear segments
+pub(crate) fn segments_intersect(p0: &Point2D, p1: &Point2D, p2: &Point2D, p3: &Point2D) -> bool {
+    let d1 = calculate_sign(p2, p3, p0);
+    let d2 = calculate_sign(p2, p3, p1);
+    let d3 = calculate_sign(p0, p1, p2);
+    let d4 = calculate_sign(p0, p1, p3);
+
+    if d1 != d2 && d3 != d4 && d1 != Sign::Equal && d2 != Sign::Equal && d3 != Sign::Equal && d4 != Sign::Equal {
+        return true;
+    }
+
+    (d1 == Sign::Equal && is_on_segment(p2, p3, p0)) ||
+    (d2 == Sign::Equal && is_on_segment(p2, p3, p1)) ||
+    (d3 == Sign::Equal && is_on_segment(p0, p1, p2)) ||
+    (d4 == Sign::Equal && is_on_segment(p0, p1, p3))
+}
+
+/// Distance of the point `p` to the segment (a, b)
+pub fn distance_point_segment(p: &Point2D, a: &Point2D, b: &Point2D) -> fsize {
+    let ab = *b - *a;
+    let len_sq = ab.dot(&ab);
+
+    if len_sq == 0.0 {
+        return p.dist(a);
+    }
+
+    let t = ((*p - *a).dot(&ab) / len_sq).max(0.0).min(1.0);
+    p.dist(&a.lerp(b, t))
+}
+
+/// Shortest distance between the segments (a1, a2) and (b1, b2)
+///
+/// Returns `0.0` if the segments intersect or touch. Otherwise the closest
+/// points are always at an endpoint of one of the segments.
+pub fn distance_segment_segment(a1: &Point2D, a2: &Point2D, b1: &Point2D, b2: &Point2D) -> fsize {
+    if segments_intersect(a1, a2, b1, b2) {
+        return 0.0;
+    }
+
+    distance_point_segment(a1, b1, b2)
+        .min(distance_point_segment(a2, b1, b2))
+        .min(distance_point_segment(b1, a1, a2))
+        .min(distance_point_segment(b2, a1, a2))
+}
+
+/// Given that p is collinear with (s0, s1), check if p lies within the segment
+#[inline]
+pub(crate) fn is_on_segment(s0: &Point2D, s1: &Point2D, p: &Point2D) -> bool {
+    p.x >= s0.x.min(s1.x) && p.x <= s0.x.max(s1.x) &&
+    p.y >= s0.y.min(s1.y) && p.y <= s0.y.max(s1.y)
+}
+
+/// Location of a point relative to a ring
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum PointLocation {
+    Inside,
+    Outside,
+    OnBoundary,
+}
+
+/// Locates a point relative to the closed ring `nodes` (even-odd rule) in O(n) time
+pub(crate) fn locate_point_in_ring(p: &Point2D, nodes: &[Point2D]) -> PointLocation {
+
+    let len = nodes.len();
+    if len == 0 {
+        return PointLocation::Outside;
+    }
+
+    let mut inside = false;
+
+    for i in 0..len {
+        let a = &nodes[i];
+        let b = &nodes[(i + 1) % len];
+
+        if calculate_sign(a, b, p) == Sign::Equal && is_on_segment(a, b, p) {
+            return PointLocation::OnBoundary;
+        }
+
+        // crossing number test, with a half-open rule for the vertices
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if p.x < x {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside { PointLocation::Inside } else { PointLocation::Outside }
+}
+
+/// Checks if the ring is convex: it turns in the same direction at every vertex
+/// (collinear vertices are ignored) and goes around only once, which rules out
+/// star-shaped rings like a pentagram
+pub(crate) fn is_convex(nodes: &[Point2D]) -> bool {
+    let len = nodes.len();
+    let mut sign: fsize = 0.0;
+    let mut x_direction: fsize = 0.0;
+    let mut x_flips = 0;
+
+    for i in 0..len {
+        let (a, b, c) = (&nodes[i], &nodes[(i + 1) % len], &nodes[(i + 2) % len]);
+        let turn = calculate_signed_area3(a, b, c);
+        if turn * sign < 0.0 {
+            return false;
+        }
+        if turn != 0.0 {
+            sign = turn;
+        }
+
+        let dx = b.x - a.x;
+        if dx * x_direction < 0.0 {
+            x_flips += 1;
+        }
+        if dx != 0.0 {
+            x_direction = dx;
+        }
+    }
+
+    // the first edge is compared against the last one as well
+    let first = nodes.iter().zip(nodes.iter().skip(1)).map(|(a, b)| b.x - a.x).find(|dx| *dx != 0.0);
+    if let Some(dx) = first {
+        if dx * x_direction < 0.0 {
+            x_flips += 1;
+        }
+    }
+
+    x_flips <= 2
+}
+
+/// Checks if the ring `inner` lies inside the ring `outer`, assuming the
+/// two rings do not cross (they may touch)
+pub(crate) fn is_ring_inside_ring(inner: &[Point2D], outer: &[Point2D]) -> bool {
+
+    for p in inner {
+        match locate_point_in_ring(p, outer) {
+            PointLocation::Inside => return true,
+            PointLocation::Outside => return false,
+            PointLocation::OnBoundary => { },
+        }
+    }
+
+    // all vertices are on the boundary of the outer ring, try the edge midpoints
+    let len = inner.len();
+    for i in 0..len {
+        let a = &inner[i];
+        let b = &inner[(i + 1) % len];
+        let mid = Point2D { x: (a.x + b.x) / 2.0, y: (a.y + b.y) / 2.0 };
+        match locate_point_in_ring(&mid, outer) {
+            PointLocation::Inside => return true,
+            PointLocation::Outside => return false,
+            PointLocation::OnBoundary => { },
+        }
+    }
+
+    false
 }
 
 /// Calculates the winding order of a polygon using the gaussian shoelace formula in O(n) time
@@ -48,25 +258,37 @@ pub(crate) fn is_point_in_triangle(s: &Segment, o: &Point2D, p: &Point2D) -> boo
 /// # Panics
 ///
 /// You must validate that there are at least three points in the nodes
-/// (otherwise, there is no winding order, it's just a point or a line)
+/// (otherwise, there is no winding order, it's just a point or a line).
+/// Use `try_calculate_winding_order` for nodes that come from outside.
 pub fn calculate_winding_order(nodes: &[Point2D]) -> WindingOrder {
 
     // cannot happen, since the parent function should
     // take care of early returning on invalid polygons
     assert!(nodes.len() > 2);
 
-    let iter1 = nodes.iter();
-    let mut iter2 = nodes.iter().cycle();
-    iter2.next();
+    try_calculate_winding_order(nodes).unwrap()
+}
+
+/// Same as `calculate_winding_order`, but returns `None` instead of
+/// panicking if there are less than three points
+pub fn try_calculate_winding_order(nodes: &[Point2D]) -> Option<WindingOrder> {
+    if nodes.len() < 3 {
+        return None;
+    }
 
     // shoelace formula
-    let sum: fsize = iter1.zip(iter2).map(|(p0, p1)| (p1.x - p0.x) * (p1.y + p0.y)).sum();
+    let sum: fsize = Edges::new(nodes, true).map(|(p0, p1)| (p1.x - p0.x) * (p1.y + p0.y)).sum();
     match sum > 0.0 {
-        true  => WindingOrder::Clockwise,
-        false => WindingOrder::CounterClockwise,
+        true  => Some(WindingOrder::Clockwise),
+        false => Some(WindingOrder::CounterClockwise),
     }
 }
 
+/// Signed area of a ring (shoelace formula), positive for counter-clockwise rings
+pub(crate) fn calculate_signed_area(nodes: &[Point2D]) -> fsize {
+    Edges::new(nodes, true).map(|(p0, p1)| p0.cross(&p1)).sum::<fsize>() / 2.0
+}
+
 /// Calculates the bounding box of all points in the nodes in O(n) time
 pub fn calculate_bounding_box(nodes: &[Point2D]) -> Bbox {
 
@@ -108,3 +330,48 @@ pub fn calculate_bounding_box(nodes: &[Point2D]) -> Bbox {
         right: max_x,
     }
 }
+
+#[test]
+fn test_distance_helpers() {
+    let p = |x, y| Point2D { x: x, y: y };
+
+    assert_eq!(distance_point_segment(&p(2.0, 3.0), &p(0.0, 0.0), &p(4.0, 0.0)), 3.0);
+    assert_eq!(distance_point_segment(&p(7.0, 4.0), &p(0.0, 0.0), &p(4.0, 0.0)), 5.0);
+    assert_eq!(distance_point_segment(&p(3.0, 4.0), &p(0.0, 0.0), &p(0.0, 0.0)), 5.0);
+
+    // crossing, parallel and end-to-end segments
+    assert_eq!(distance_segment_segment(&p(0.0, 0.0), &p(4.0, 4.0), &p(0.0, 4.0), &p(4.0, 0.0)), 0.0);
+    assert_eq!(distance_segment_segment(&p(0.0, 0.0), &p(4.0, 0.0), &p(1.0, 2.0), &p(3.0, 2.0)), 2.0);
+    assert_eq!(distance_segment_segment(&p(0.0, 0.0), &p(1.0, 0.0), &p(4.0, 4.0), &p(4.0, 8.0)), 5.0);
+}
+
+#[test]
+fn test_quantize() {
+    let mut polygons = vec![Polygon::from(vec![(0.1, 0.0), (0.26, 0.01), (10.04, 0.0), (9.98, 9.96), (0.0, 10.0)])];
+
+    quantize(&mut polygons, 0.5);
+    assert_eq!(polygons[0].nodes, Polygon::from(vec![(0.0, 0.0), (1.0, 0.0), (20.0, 0.0), (20.0, 20.0), (0.0, 20.0)]).nodes);
+
+    dequantize(&mut polygons, 0.5);
+    assert_eq!(polygons[0].nodes[2], Point2D { x: 10.0, y: 0.0 });
+    assert_eq!(polygons[0].area(), 100.0);
+}
+
+#[cfg(feature = "adaptive_precision")]
+#[test]
+fn test_adaptive_sign() {
+    use kernel::ExactKernel;
+
+    // the classic near-collinear grid from Shewchuk's and Kettner's papers:
+    // the f32 determinant gets the sign wrong for many of these points
+    let p0 = Point2D { x: 12.0, y: 12.0 };
+    let p1 = Point2D { x: 24.0, y: 24.0 };
+    let ulp = fsize::EPSILON / 2.0;
+
+    for i in 0..32 {
+        for j in 0..32 {
+            let o = Point2D { x: 0.5 + i as fsize * ulp, y: 0.5 + j as fsize * ulp };
+            assert_eq!(calculate_sign(&p0, &p1, &o), Sign::from(ExactKernel::orient2d(&p0, &p1, &o)));
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Checks of the result of a boolean operation, for example in the CI of an
+//! application, on a sample of real data
+//!
+//! Unlike `reference_clip`, the checks do not compute the result a second
+//! time. They only test properties that every correct result has, so they
+//! are cheaper, but can miss errors.
+
+use error::VerificationError;
+use intersections::find_intersections;
+use multi_polygon::MultiPolygon;
+use options::ClipOptions;
+use polygon::{Polygon, BoolOpType};
+use reference::{inside, rings};
+use segment::Segment;
+use utils::{calculate_signed_area, distance_point_segment, locate_point_in_ring, PointLocation};
+use edges::Edges;
+use {Point2D, Bbox, fsize};
+
+/// Number of points at which the result is compared with the inputs
+const SAMPLES: usize = 1024;
+
+/// Differences between areas that have to be equal, see `check_partition`
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PartitionDeltas {
+    /// area(A ∩ B) + area(A \ B) + area(B \ A) - area(A ∪ B)
+    pub union: fsize,
+    /// area(A ∩ B) + area(A \ B) - area(A)
+    pub a: fsize,
+    /// area(A ∩ B) + area(B \ A) - area(B)
+    pub b: fsize,
+}
+
+impl PartitionDeltas {
+
+    /// The largest absolute delta
+    pub fn max(&self) -> fsize {
+        self.union.abs().max(self.a.abs()).max(self.b.abs())
+    }
+}
+
+/// Checks that the intersection and the two differences of `a` and `b`
+/// partition their union, by comparing the areas
+///
+/// Returns the deltas in both cases. They are within the tolerance if none
+/// of them is larger than `tolerance` times area(A) + area(B), so the
+/// tolerance is relative. This is a cheap check that can run in debug builds,
+/// but it only finds errors that change the area.
+pub fn check_partition(a: &[Polygon], b: &[Polygon], tolerance: fsize) -> Result<PartitionDeltas, PartitionDeltas> {
+    let (a, b) = (MultiPolygon::new(a.to_vec()), MultiPolygon::new(b.to_vec()));
+    let area = |op: BoolOpType, first: &MultiPolygon, second: &MultiPolygon| {
+        let rings = rings(&first.clip(second, op, &ClipOptions::default()).polygons);
+        even_odd_area(&rings)
+    };
+
+    let intersection = area(BoolOpType::Intersection, &a, &b);
+    let a_minus_b = area(BoolOpType::Difference, &a, &b);
+    let b_minus_a = area(BoolOpType::Difference, &b, &a);
+    let union = area(BoolOpType::Union, &a, &b);
+    let (area_a, area_b) = (even_odd_area(&rings(&a.polygons)), even_odd_area(&rings(&b.polygons)));
+
+    let deltas = PartitionDeltas {
+        union: intersection + a_minus_b + b_minus_a - union,
+        a: intersection + a_minus_b - area_a,
+        b: intersection + b_minus_a - area_b,
+    };
+    if deltas.max() <= tolerance * (area_a + area_b) { Ok(deltas) } else { Err(deltas) }
+}
+
+/// Checks that `result` can be the result of `a <operation_type> b`
+///
+/// The inputs and the result are sets of closed rings with the even-odd rule,
+/// like the operations of `MultiPolygon`. The checks are, in this order:
+///
+/// - the rings of the result are simple and do not cross each other
+/// - outer rings are counter-clockwise and holes are clockwise
+/// - the area of the result is within the bounds of the operation, for
+///   example the intersection is not larger than either input
+/// - at 1024 points spread over the bounding box of the inputs, the result
+///   covers exactly the points that the operation selects. Points close to
+///   one of the boundaries are skipped.
+pub fn verify_result(a: &[Polygon], b: &[Polygon], operation_type: BoolOpType, result: &[Polygon])
+                     -> Result<(), VerificationError>
+{
+    let (a_rings, b_rings, result_rings) = (rings(a), rings(b), rings(result));
+
+    // validity
+    if let Some(point) = find_crossing(result_rings.iter().map(|ring| &ring[..]), true) {
+        return Err(VerificationError::SelfIntersection { point: point });
+    }
+
+    for (ring, polygon) in result.iter().enumerate().filter(|&(_, p)| p.nodes.len() > 2) {
+        if (calculate_signed_area(&polygon.nodes) < 0.0) != polygon.is_hole {
+            return Err(VerificationError::WrongOrientation { ring: ring });
+        }
+    }
+
+    // area
+    let (area_a, area_b, area_result) = (even_odd_area(&a_rings), even_odd_area(&b_rings), even_odd_area(&result_rings));
+    let (min, max) = match operation_type {
+        BoolOpType::Intersection => (0.0, area_a.min(area_b)),
+        BoolOpType::Union => (area_a.max(area_b), area_a + area_b),
+        BoolOpType::Difference => ((area_a - area_b).max(0.0), area_a),
+        BoolOpType::Xor => ((area_a - area_b).abs(), area_a + area_b),
+    };
+    let tolerance = (area_a + area_b) * 1e-4;
+    if area_result < min - tolerance || area_result > max + tolerance {
+        return Err(VerificationError::ImplausibleArea { area: area_result, min: min, max: max });
+    }
+
+    // containment at the sample points
+    let all_rings: Vec<&Vec<Point2D>> = a_rings.iter().chain(b_rings.iter()).chain(result_rings.iter()).collect();
+    let bbox = all_rings.iter().filter_map(|ring| Bbox::from_points(ring))
+        .fold(None, |acc: Option<Bbox>, bbox| Some(acc.map_or(bbox, |acc| acc.union(&bbox))));
+    let bbox = match bbox {
+        Some(bbox) => bbox,
+        None => return Ok(()),
+    };
+    let margin = (bbox.width() + bbox.height()) * 1e-4;
+
+    for i in 0..SAMPLES {
+        let p = Point2D {
+            x: bbox.left + bbox.width() * halton(i + 1, 2),
+            y: bbox.bottom + bbox.height() * halton(i + 1, 3),
+        };
+        let near_boundary = all_rings.iter()
+            .any(|ring| Edges::new(ring, true).any(|(s, t)| distance_point_segment(&p, &s, &t) <= margin));
+        if near_boundary {
+            continue;
+        }
+
+        let (in_a, in_b) = (inside(&p, &a_rings, None), inside(&p, &b_rings, None));
+        let expected = match operation_type {
+            BoolOpType::Intersection => in_a && in_b,
+            BoolOpType::Union => in_a || in_b,
+            BoolOpType::Difference => in_a && !in_b,
+            BoolOpType::Xor => in_a != in_b,
+        };
+        if inside(&p, &result_rings, None) != expected {
+            return Err(VerificationError::PointMismatch { point: p, expected: expected });
+        }
+    }
+
+    Ok(())
+}
+
+/// First point where two edges of the paths cross, or where an edge passes
+/// through a vertex that it does not end in. Edges that only share their
+/// end points do not count.
+pub(crate) fn find_crossing<'a, I>(paths: I, is_closed: bool) -> Option<Point2D> where I: IntoIterator<Item = &'a [Point2D]> {
+    let segments: Vec<Segment> = paths.into_iter()
+        .flat_map(|path| Edges::new(path, is_closed).map(|(p, q)| Segment::new(p, q)))
+        .collect();
+    find_intersections(&segments).into_iter()
+        .find(|event| event.segments.iter().any(|&idx| segments[idx].a != event.point && segments[idx].b != event.point))
+        .map(|event| event.point)
+}
+
+/// Area covered by rings that do not cross each other, with the even-odd
+/// rule: rings that lie inside of an odd number of other rings are holes
+fn even_odd_area(rings: &[Vec<Point2D>]) -> fsize {
+    rings.iter().enumerate().map(|(idx, ring)| {
+        // a vertex that is not shared with the other ring decides if the ring lies inside of it
+        let depth = rings.iter().enumerate().filter(|&(other, _)| other != idx).filter(|&(_, other)| {
+            let locations = ring.iter().map(|p| locate_point_in_ring(p, other));
+            locations.clone().find(|&l| l != PointLocation::OnBoundary).unwrap_or(PointLocation::Inside) == PointLocation::Inside
+        }).count();
+        let area = calculate_signed_area(ring).abs();
+        if depth % 2 == 0 { area } else { -area }
+    }).sum()
+}
+
+/// Element `index` of the Halton sequence with the given base, in [0, 1)
+fn halton(mut index: usize, base: usize) -> fsize {
+    let mut result = 0.0;
+    let mut factor = 1.0;
+    while index > 0 {
+        factor /= base as fsize;
+        result += factor * (index % base) as fsize;
+        index /= base;
+    }
+    result
+}
+
+#[test]
+fn test_verify_result() {
+    use multi_polygon::MultiPolygon;
+    use options::ClipOptions;
+
+    let a = vec![Polygon::rect(0.0, 0.0, 4.0, 4.0), Polygon::rect(1.0, 1.0, 1.0, 1.0)];
+    let b = vec![Polygon::rect(2.0, -1.0, 3.0, 3.0)];
+    for &op in [BoolOpType::Intersection, BoolOpType::Union, BoolOpType::Difference, BoolOpType::Xor].iter() {
+        let result = MultiPolygon::new(a.clone()).clip(&MultiPolygon::new(b.clone()), op, &ClipOptions::default());
+        assert_eq!(verify_result(&a, &b, op, &result.polygons), Ok(()), "{:?}", op);
+    }
+
+    // the union instead of the intersection
+    let union = MultiPolygon::new(a.clone()).union(&MultiPolygon::new(b.clone())).polygons;
+    match verify_result(&a, &b, BoolOpType::Intersection, &union) {
+        Err(VerificationError::ImplausibleArea { .. }) => { },
+        other => panic!("{:?}", other),
+    }
+
+    // a square of the right size in the wrong place
+    let misplaced = [Polygon::rect(0.0, 0.0, 2.0, 2.0)];
+    match verify_result(&a[..1], &b, BoolOpType::Intersection, &misplaced) {
+        Err(VerificationError::PointMismatch { .. }) => { },
+        other => panic!("{:?}", other),
+    }
+
+    // a missing hole
+    let square = [Polygon::rect(0.0, 0.0, 4.0, 4.0)];
+    match verify_result(&a, &[], BoolOpType::Union, &square) {
+        Err(VerificationError::ImplausibleArea { area, .. }) => assert_eq!(area, 16.0),
+        other => panic!("{:?}", other),
+    }
+
+    // invalid rings
+    let mut clockwise = Polygon::rect(0.0, 0.0, 4.0, 4.0);
+    clockwise.nodes.reverse();
+    assert_eq!(verify_result(&square, &[], BoolOpType::Union, &[clockwise]), Err(VerificationError::WrongOrientation { ring: 0 }));
+    let bowtie = Polygon::from(vec![(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0)]);
+    assert_eq!(verify_result(&square, &[], BoolOpType::Union, &[bowtie]),
+               Err(VerificationError::SelfIntersection { point: Point2D { x: 2.0, y: 2.0 } }));
+}
+
+#[test]
+fn test_check_partition() {
+    let a = [Polygon::rect(0.0, 0.0, 4.0, 4.0), Polygon::rect(1.0, 1.0, 1.0, 1.0)];
+    let b = [Polygon::rect(2.0, -1.0, 3.0, 3.0), Polygon::circle(Point2D { x: 0.3, y: 3.7 }, 1.5, 0.01)];
+    let deltas = check_partition(&a, &b, 1e-5).unwrap();
+    assert!(deltas.max() < 1e-4);
+
+    assert_eq!(check_partition(&a, &[], 0.0), Ok(PartitionDeltas::default()));
+    assert_eq!(check_partition(&[], &[], 0.0), Ok(PartitionDeltas::default()));
+}
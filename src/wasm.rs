@@ -0,0 +1,139 @@
+//! JavaScript bindings (requires the `wasm` feature)
+//!
+//! Polygons are passed as flat typed arrays (`Float64Array`) of the form
+//! `[x0, y0, x1, y1, ...]`. The result of an operation is a `ClipResult`,
+//! which stores all result rings in one flat coordinate array. The operations
+//! are those of `MultiPolygon`.
+//!
+//! The crate is only built as an `rlib`, so that other users do not pay for a
+//! `cdylib`. To build the WebAssembly module, pass the crate type on the
+//! command line and run `wasm-bindgen` on the result:
+//!
+//! ```text
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/polyclip.wasm
+//! ```
+//!
+//! `wasm-pack` needs the `cdylib` crate type in the manifest. To use it, create
+//! a wrapper crate with `crate-type = ["cdylib"]` that depends on polyclip with
+//! the `wasm` feature and re-exports it (`pub use polyclip::*;`).
+
+use wasm_bindgen::prelude::*;
+use multi_polygon::MultiPolygon;
+use options::ClipOptions;
+use polygon::{Polygon, BoolOpType};
+use {Point2D, fsize};
+
+/// Result of a boolean operation, returned to JavaScript
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct ClipResult {
+    coords: Vec<f64>,
+    offsets: Vec<u32>,
+    holes: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl ClipResult {
+
+    /// Coordinates of all rings, as `[x0, y0, x1, y1, ...]`
+    pub fn coords(&self) -> Vec<f64> {
+        self.coords.clone()
+    }
+
+    /// Start index of every ring in `coords()`, followed by the length of `coords()`,
+    /// so ring `i` is `coords.slice(offsets[i], offsets[i + 1])`
+    pub fn ring_offsets(&self) -> Vec<u32> {
+        self.offsets.clone()
+    }
+
+    /// One entry per ring, 1 if the ring is a hole, 0 otherwise
+    pub fn hole_flags(&self) -> Vec<u8> {
+        self.holes.clone()
+    }
+
+    /// Number of rings in the result
+    pub fn ring_count(&self) -> usize {
+        self.holes.len()
+    }
+}
+
+/// Union of two polygons
+#[wasm_bindgen]
+pub fn union(subject: &[f64], clip: &[f64]) -> ClipResult {
+    clip_flat(subject, clip, BoolOpType::Union)
+}
+
+/// Intersection of two polygons
+#[wasm_bindgen]
+pub fn intersection(subject: &[f64], clip: &[f64]) -> ClipResult {
+    clip_flat(subject, clip, BoolOpType::Intersection)
+}
+
+/// Difference (subject minus clip) of two polygons
+#[wasm_bindgen]
+pub fn difference(subject: &[f64], clip: &[f64]) -> ClipResult {
+    clip_flat(subject, clip, BoolOpType::Difference)
+}
+
+/// Symmetric difference of two polygons
+#[wasm_bindgen]
+pub fn xor(subject: &[f64], clip: &[f64]) -> ClipResult {
+    clip_flat(subject, clip, BoolOpType::Xor)
+}
+
+fn clip_flat(subject: &[f64], clip: &[f64], operation_type: BoolOpType) -> ClipResult {
+    let subject = MultiPolygon::new(vec![polygon_from_flat(subject)]);
+    let clip = MultiPolygon::new(vec![polygon_from_flat(clip)]);
+    to_clip_result(subject.clip(&clip, operation_type, &ClipOptions::default()).polygons)
+}
+
+/// Reads `[x0, y0, x1, y1, ...]`, a trailing odd coordinate is ignored
+fn polygon_from_flat(coords: &[f64]) -> Polygon {
+    Polygon {
+        nodes: coords.chunks(2).filter(|c| c.len() == 2).map(|c| Point2D {
+            x: c[0] as fsize,
+            y: c[1] as fsize,
+        }).collect(),
+        .. Default::default()
+    }
+}
+
+fn to_clip_result(polygons: Vec<Polygon>) -> ClipResult {
+    let mut result = ClipResult::default();
+    for polygon in polygons {
+        result.offsets.push(result.coords.len() as u32);
+        result.holes.push(polygon.is_hole as u8);
+        for p in &polygon.nodes {
+            result.coords.push(p.x as f64);
+            result.coords.push(p.y as f64);
+        }
+    }
+    result.offsets.push(result.coords.len() as u32);
+    result
+}
+
+#[test]
+fn test_overlapping_squares() {
+    let a = [-5.0, -5.0, 5.0, -5.0, 5.0, 5.0, -5.0, 5.0];
+    let b = [0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0];
+
+    let area = |result: &ClipResult| -> f64 {
+        let offsets = result.ring_offsets();
+        let coords = result.coords();
+        offsets.windows(2).map(|w| {
+            let ring: Vec<Point2D> = coords[w[0] as usize..w[1] as usize].chunks(2)
+                .map(|c| Point2D { x: c[0] as fsize, y: c[1] as fsize })
+                .collect();
+            ::utils::calculate_signed_area(&ring) as f64
+        }).sum::<f64>().abs()
+    };
+
+    let union = union(&a, &b);
+    assert_eq!(union.ring_count(), 1);
+    assert_eq!(area(&union), 175.0);
+    assert_eq!(area(&intersection(&a, &b)), 25.0);
+    assert_eq!(area(&difference(&a, &b)), 75.0);
+    assert_eq!(xor(&a, &b).ring_count(), 2);
+    assert_eq!(intersection(&a, &[20.0, 20.0, 21.0, 20.0, 21.0, 21.0]).ring_count(), 0);
+}
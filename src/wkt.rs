@@ -0,0 +1,274 @@
+//! WKT (well-known text) reading and writing
+
+use polygon::Polygon;
+use multi_polygon::MultiPolygon;
+use std::fmt;
+use {Point2D, fsize};
+
+impl Polygon {
+
+    /// Parses a WKT `POLYGON` or `MULTIPOLYGON`
+    ///
+    /// Returns all rings, with every exterior ring followed by its holes (which
+    /// have `is_hole` set). Additional coordinate dimensions (Z, M) are ignored.
+    /// Returns `None` if the text is not a valid polygon or multipolygon.
+    pub fn from_wkt(wkt: &str) -> Option<Vec<Self>> {
+        MultiPolygon::from_wkt(wkt).map(|m| m.polygons)
+    }
+
+    /// Formats the polygon as a WKT `POLYGON` with a single ring
+    ///
+    /// Open polygons are formatted as a `LINESTRING`.
+    pub fn to_wkt(&self) -> String {
+        let mut out = String::new();
+        if self.nodes.is_empty() {
+            out.push_str(if self.is_closed { "POLYGON EMPTY" } else { "LINESTRING EMPTY" });
+        } else if self.is_closed {
+            out.push_str("POLYGON (");
+            write_ring(&mut out, &self.nodes, true);
+            out.push(')');
+        } else {
+            out.push_str("LINESTRING ");
+            write_ring(&mut out, &self.nodes, false);
+        }
+        out
+    }
+}
+
+impl MultiPolygon {
+
+    /// Parses a WKT `POLYGON` or `MULTIPOLYGON`, see `Polygon::from_wkt`
+    pub fn from_wkt(wkt: &str) -> Option<Self> {
+        let mut parser = WktParser { input: wkt.as_bytes(), pos: 0 };
+        let keyword = parser.keyword();
+
+        let polygons = if keyword.eq_ignore_ascii_case("POLYGON") {
+            parser.polygon()?
+        } else if keyword.eq_ignore_ascii_case("MULTIPOLYGON") {
+            parser.multi_polygon()?
+        } else {
+            return None;
+        };
+
+        parser.skip_whitespace();
+        if parser.pos != parser.input.len() {
+            return None;
+        }
+
+        Some(MultiPolygon::new(polygons))
+    }
+}
+
+impl<T> MultiPolygon<T> {
+
+    /// Formats the set as a WKT `MULTIPOLYGON`
+    ///
+    /// Every hole is written into the polygon of the exterior ring that contains it.
+    pub fn to_wkt(&self) -> String {
+        let groups = self.exteriors_with_holes();
+        if groups.is_empty() {
+            return String::from("MULTIPOLYGON EMPTY");
+        }
+
+        let mut out = String::from("MULTIPOLYGON (");
+        for (idx, (exterior, holes)) in groups.into_iter().enumerate() {
+            if idx != 0 {
+                out.push_str(", ");
+            }
+            out.push('(');
+            write_ring(&mut out, &exterior.nodes, true);
+            for hole in holes {
+                out.push_str(", ");
+                write_ring(&mut out, &hole.nodes, true);
+            }
+            out.push(')');
+        }
+        out.push(')');
+        out
+    }
+}
+
+impl fmt::Display for Polygon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_wkt())
+    }
+}
+
+impl<T> fmt::Display for MultiPolygon<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_wkt())
+    }
+}
+
+/// Writes `(x y, x y, ...)`, repeating the first point for closed rings
+fn write_ring(out: &mut String, nodes: &[Point2D], is_closed: bool) {
+    use std::fmt::Write;
+
+    out.push('(');
+    for (idx, p) in nodes.iter().enumerate() {
+        if idx != 0 {
+            out.push_str(", ");
+        }
+        let _ = write!(out, "{} {}", p.x, p.y);
+    }
+    if is_closed {
+        if let Some(first) = nodes.first() {
+            let _ = write!(out, ", {} {}", first.x, first.y);
+        }
+    }
+    out.push(')');
+}
+
+/// Minimal recursive-descent parser for the WKT polygon types
+struct WktParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WktParser<'a> {
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.input.len() && (self.input[self.pos] as char).is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    /// Reads the next alphabetic word, i.e. `POLYGON`, `EMPTY` or `Z`
+    fn keyword(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.pos < self.input.len() && (self.input[self.pos] as char).is_ascii_alphabetic() {
+            self.pos += 1;
+        }
+        ::std::str::from_utf8(&self.input[start..self.pos]).unwrap_or("")
+    }
+
+    /// Consumes `c` if it is the next non-whitespace character
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_whitespace();
+        if self.pos < self.input.len() && self.input[self.pos] == c {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skips an optional dimension marker (`Z`, `M`, `ZM`) and
+    /// returns true if the geometry is `EMPTY`
+    fn dimension_or_empty(&mut self) -> bool {
+        let before = self.pos;
+        let word = self.keyword();
+        if word.eq_ignore_ascii_case("EMPTY") {
+            return true;
+        }
+        if !(word.eq_ignore_ascii_case("Z") || word.eq_ignore_ascii_case("M") || word.eq_ignore_ascii_case("ZM")) {
+            self.pos = before;
+            return false;
+        }
+        let before = self.pos;
+        if self.keyword().eq_ignore_ascii_case("EMPTY") {
+            return true;
+        }
+        self.pos = before;
+        false
+    }
+
+    fn number(&mut self) -> Option<fsize> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.pos < self.input.len() {
+            match self.input[self.pos] {
+                b'0'..=b'9' | b'.' | b'-' | b'+' | b'e' | b'E' => self.pos += 1,
+                _ => break,
+            }
+        }
+        ::std::str::from_utf8(&self.input[start..self.pos]).ok()?.parse().ok()
+    }
+
+    /// `x y [z [m]]`
+    fn coordinate(&mut self) -> Option<Point2D> {
+        let x = self.number()?;
+        let y = self.number()?;
+        loop {
+            self.skip_whitespace();
+            match self.input.get(self.pos) {
+                Some(&b',') | Some(&b')') => break,
+                _ => { self.number()?; },
+            }
+        }
+        Some(Point2D { x: x, y: y })
+    }
+
+    /// `(x y, x y, ...)`
+    fn ring(&mut self) -> Option<Vec<Point2D>> {
+        if !self.eat(b'(') {
+            return None;
+        }
+        let mut nodes = vec![self.coordinate()?];
+        while self.eat(b',') {
+            nodes.push(self.coordinate()?);
+        }
+        if !self.eat(b')') {
+            return None;
+        }
+        // WKT rings repeat the first point at the end
+        if nodes.len() > 1 && nodes.first() == nodes.last() {
+            nodes.pop();
+        }
+        Some(nodes)
+    }
+
+    /// `((ring), (hole), ...)` or `EMPTY`
+    fn polygon(&mut self) -> Option<Vec<Polygon>> {
+        if self.dimension_or_empty() {
+            return Some(Vec::new());
+        }
+        self.polygon_body()
+    }
+
+    fn polygon_body(&mut self) -> Option<Vec<Polygon>> {
+        if !self.eat(b'(') {
+            return None;
+        }
+        let mut rings = vec![self.ring()?];
+        while self.eat(b',') {
+            rings.push(self.ring()?);
+        }
+        if !self.eat(b')') {
+            return None;
+        }
+        Some(rings.into_iter().enumerate().map(|(idx, nodes)| Polygon::from_parts(nodes, idx > 0, true)).collect())
+    }
+
+    /// `(((ring), ...), ((ring), ...))` or `EMPTY`
+    fn multi_polygon(&mut self) -> Option<Vec<Polygon>> {
+        if self.dimension_or_empty() {
+            return Some(Vec::new());
+        }
+        if !self.eat(b'(') {
+            return None;
+        }
+        let mut polygons = self.polygon_body()?;
+        while self.eat(b',') {
+            polygons.extend(self.polygon_body()?);
+        }
+        if !self.eat(b')') {
+            return None;
+        }
+        Some(polygons)
+    }
+}
+
+#[test]
+fn test_wkt_roundtrip() {
+    let wkt = "MULTIPOLYGON (((0 0, 10 0, 10 10, 0 10, 0 0), (2 2, 2 8, 8 8, 8 2, 2 2)), ((20 0, 30 0, 30 10, 20 0)))";
+    let multi = MultiPolygon::from_wkt(wkt).unwrap();
+    assert_eq!(multi.polygons.len(), 3);
+    assert!(multi.polygons[1].is_hole);
+    assert_eq!(multi.to_wkt(), wkt);
+
+    let poly = Polygon::from_wkt("polygon z ((0 0 1, 1 0 1, 1 1 1, 0 0 1))").unwrap();
+    assert_eq!(poly[0].to_string(), "POLYGON ((0 0, 1 0, 1 1, 0 0))");
+    assert!(Polygon::from_wkt("POLYGON ((0 0, 1 0").is_none());
+}